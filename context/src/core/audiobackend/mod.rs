@@ -0,0 +1,407 @@
+// implements playing, pausing and stopping of `AudioBackend` on `SourceParameters`,
+// the backend needs `parameters` and `parameters_mut`
+macro_rules! impl_source_playback {
+    () => {
+        fn play(&mut self, source: SourceId) -> VerboseResult<()> {
+            let parameters = self.parameters_mut(source)?;
+
+            if parameters.state != SourceState::Paused {
+                parameters.cursor = 0.0;
+            }
+
+            parameters.state = SourceState::Playing;
+
+            Ok(())
+        }
+
+        fn pause(&mut self, source: SourceId) -> VerboseResult<()> {
+            let parameters = self.parameters_mut(source)?;
+
+            if parameters.state == SourceState::Playing {
+                parameters.state = SourceState::Paused;
+            }
+
+            Ok(())
+        }
+
+        fn stop(&mut self, source: SourceId) -> VerboseResult<()> {
+            let parameters = self.parameters_mut(source)?;
+
+            parameters.state = SourceState::Stopped;
+            parameters.cursor = 0.0;
+
+            Ok(())
+        }
+
+        fn state(&self, source: SourceId) -> VerboseResult<SourceState> {
+            Ok(self.parameters(source)?.state)
+        }
+
+        fn set_looping(&mut self, source: SourceId, looping: bool) -> VerboseResult<()> {
+            self.parameters_mut(source)?.looping = looping;
+
+            Ok(())
+        }
+    };
+}
+
+// implements the parts of `AudioBackend`, that only change `SourceParameters` and
+// `ListenerParameters`, the backend needs `parameters_mut` and `listener`
+macro_rules! impl_source_parameters {
+    () => {
+        fn set_volume(&mut self, source: SourceId, volume: f32) -> VerboseResult<()> {
+            self.parameters_mut(source)?.volume = volume;
+
+            Ok(())
+        }
+
+        fn set_position(&mut self, source: SourceId, position: [f32; 3]) -> VerboseResult<()> {
+            self.parameters_mut(source)?.position = position;
+
+            Ok(())
+        }
+
+        fn set_direction(&mut self, source: SourceId, direction: [f32; 3]) -> VerboseResult<()> {
+            self.parameters_mut(source)?.direction = direction;
+
+            Ok(())
+        }
+
+        fn set_attenuation(&mut self, source: SourceId, attenuation: f32) -> VerboseResult<()> {
+            self.parameters_mut(source)?.attenuation = attenuation;
+
+            Ok(())
+        }
+
+        fn set_max_volume(&mut self, source: SourceId, max_volume: f32) -> VerboseResult<()> {
+            self.parameters_mut(source)?.max_volume = max_volume;
+
+            Ok(())
+        }
+
+        fn set_min_volume(&mut self, source: SourceId, min_volume: f32) -> VerboseResult<()> {
+            self.parameters_mut(source)?.min_volume = min_volume;
+
+            Ok(())
+        }
+
+        fn set_max_distance(&mut self, source: SourceId, max_distance: f32) -> VerboseResult<()> {
+            self.parameters_mut(source)?.max_distance = max_distance;
+
+            Ok(())
+        }
+
+        fn set_reference_distance(&mut self, source: SourceId, distance: f32) -> VerboseResult<()> {
+            self.parameters_mut(source)?.reference_distance = distance;
+
+            Ok(())
+        }
+
+        fn set_relative(&mut self, source: SourceId, relative: bool) -> VerboseResult<()> {
+            self.parameters_mut(source)?.relative = relative;
+
+            Ok(())
+        }
+
+        fn set_pitch(&mut self, source: SourceId, pitch: f32) -> VerboseResult<()> {
+            self.parameters_mut(source)?.pitch = pitch;
+
+            Ok(())
+        }
+
+        fn set_air_absorption_factor(
+            &mut self,
+            source: SourceId,
+            factor: f32,
+        ) -> VerboseResult<()> {
+            self.parameters_mut(source)?.air_absorption_factor = factor;
+
+            Ok(())
+        }
+
+        fn set_filters(
+            &mut self,
+            source: SourceId,
+            low_pass: Option<f32>,
+            high_pass: Option<f32>,
+        ) -> VerboseResult<()> {
+            let parameters = self.parameters_mut(source)?;
+
+            parameters.low_pass = low_pass;
+            parameters.high_pass = high_pass;
+
+            Ok(())
+        }
+
+        fn set_listener_position(&mut self, position: [f32; 3]) {
+            self.listener.position = position;
+        }
+
+        fn listener_position(&self) -> [f32; 3] {
+            self.listener.position
+        }
+
+        fn set_listener_orientation(&mut self, direction: [f32; 3], up: [f32; 3]) {
+            self.listener.direction = direction;
+            self.listener.up = up;
+        }
+
+        fn listener_orientation(&self) -> ([f32; 3], [f32; 3]) {
+            (self.listener.direction, self.listener.up)
+        }
+
+        fn set_listener_volume(&mut self, volume: f32) {
+            self.listener.volume = volume;
+        }
+
+        fn listener_volume(&self) -> f32 {
+            self.listener.volume
+        }
+    };
+}
+
+pub mod nullbackend;
+#[cfg(feature = "audio")]
+pub mod openalbackend;
+pub mod softwarebackend;
+pub mod wavbuffer;
+
+use presentation::prelude::*;
+
+use self::wavbuffer::WavBuffer;
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub(crate) type SharedBackend = Arc<Mutex<Box<dyn AudioBackend>>>;
+
+/// Handle of a playable source inside of a backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SourceId(u32);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SourceState {
+    Initial,
+    Playing,
+    Paused,
+    Stopped,
+}
+
+/// Parameters of an EFX (EAX) reverb, the default is the generic preset
+///
+/// Backends without reverb ignore it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Reverb {
+    pub density: f32,
+    pub diffusion: f32,
+    pub gain: f32,
+    pub gain_hf: f32,
+    pub gain_lf: f32,
+    /// in seconds
+    pub decay_time: f32,
+    pub decay_hf_ratio: f32,
+    pub decay_lf_ratio: f32,
+    pub reflections_gain: f32,
+    /// in seconds
+    pub reflections_delay: f32,
+    pub reflections_pan: [f32; 3],
+    pub late_reverb_gain: f32,
+    /// in seconds
+    pub late_reverb_delay: f32,
+    pub late_reverb_pan: [f32; 3],
+    pub echo_time: f32,
+    pub echo_depth: f32,
+    pub modulation_time: f32,
+    pub modulation_depth: f32,
+    pub air_absorption_gain_hf: f32,
+    /// in Hz
+    pub hf_reference: f32,
+    /// in Hz
+    pub lf_reference: f32,
+    pub room_rolloff_factor: f32,
+    pub decay_hf_limit: bool,
+}
+
+impl Default for Reverb {
+    fn default() -> Self {
+        Reverb {
+            density: 1.0,
+            diffusion: 1.0,
+            gain: 0.3162,
+            gain_hf: 0.8913,
+            gain_lf: 1.0,
+            decay_time: 1.49,
+            decay_hf_ratio: 0.83,
+            decay_lf_ratio: 1.0,
+            reflections_gain: 0.05,
+            reflections_delay: 0.007,
+            reflections_pan: [0.0, 0.0, 0.0],
+            late_reverb_gain: 1.2589,
+            late_reverb_delay: 0.011,
+            late_reverb_pan: [0.0, 0.0, 0.0],
+            echo_time: 0.25,
+            echo_depth: 0.0,
+            modulation_time: 0.25,
+            modulation_depth: 0.0,
+            air_absorption_gain_hf: 0.9943,
+            hf_reference: 5000.0,
+            lf_reference: 250.0,
+            room_rolloff_factor: 0.0,
+            decay_hf_limit: true,
+        }
+    }
+}
+
+/// Everything `SoundHandler` needs from an audio library
+///
+/// Positions, directions and the listener use the same coordinate system as OpenAL.
+pub trait AudioBackend: Send {
+    /// Sounds are loaded completely, backends may share the data of the same path
+    fn load_sound(&mut self, path: &str) -> VerboseResult<SourceId>;
+
+    /// Music may be streamed
+    fn load_music(&mut self, path: &str) -> VerboseResult<SourceId>;
+
+    fn remove_source(&mut self, source: SourceId);
+
+    /// Drops the shared data of all paths
+    fn clear_cache(&mut self);
+
+    /// Restarts stopped sources and continues paused ones
+    fn play(&mut self, source: SourceId) -> VerboseResult<()>;
+    fn pause(&mut self, source: SourceId) -> VerboseResult<()>;
+    fn stop(&mut self, source: SourceId) -> VerboseResult<()>;
+    fn state(&self, source: SourceId) -> VerboseResult<SourceState>;
+    fn duration(&self, source: SourceId) -> VerboseResult<Duration>;
+
+    fn set_looping(&mut self, source: SourceId, looping: bool) -> VerboseResult<()>;
+    fn set_volume(&mut self, source: SourceId, volume: f32) -> VerboseResult<()>;
+    fn set_position(&mut self, source: SourceId, position: [f32; 3]) -> VerboseResult<()>;
+    fn set_direction(&mut self, source: SourceId, direction: [f32; 3]) -> VerboseResult<()>;
+    fn set_attenuation(&mut self, source: SourceId, attenuation: f32) -> VerboseResult<()>;
+    fn set_max_volume(&mut self, source: SourceId, max_volume: f32) -> VerboseResult<()>;
+    fn set_min_volume(&mut self, source: SourceId, min_volume: f32) -> VerboseResult<()>;
+    fn set_max_distance(&mut self, source: SourceId, max_distance: f32) -> VerboseResult<()>;
+    fn set_reference_distance(&mut self, source: SourceId, distance: f32) -> VerboseResult<()>;
+    fn set_relative(&mut self, source: SourceId, relative: bool) -> VerboseResult<()>;
+    fn set_pitch(&mut self, source: SourceId, pitch: f32) -> VerboseResult<()>;
+    fn set_air_absorption_factor(&mut self, source: SourceId, factor: f32) -> VerboseResult<()>;
+    fn connect(&mut self, source: SourceId, reverb: &Option<Reverb>) -> VerboseResult<()>;
+
+    /// false, if `set_filters` fails for cutoffs, e.g. for `OpenALBackend`, which has no EFX
+    /// filters
+    fn supports_filters(&self) -> bool {
+        true
+    }
+
+    /// Cutoff frequencies in Hz
+    fn set_filters(
+        &mut self,
+        source: SourceId,
+        low_pass: Option<f32>,
+        high_pass: Option<f32>,
+    ) -> VerboseResult<()>;
+
+    fn set_listener_position(&mut self, position: [f32; 3]);
+    fn listener_position(&self) -> [f32; 3];
+    fn set_listener_orientation(&mut self, direction: [f32; 3], up: [f32; 3]);
+    fn listener_orientation(&self) -> ([f32; 3], [f32; 3]);
+    fn set_listener_volume(&mut self, volume: f32);
+    fn listener_volume(&self) -> f32;
+
+    /// Advances all sources by `duration`, offline backends mix it into their output
+    fn render(&mut self, _duration: Duration) -> VerboseResult<()> {
+        Ok(())
+    }
+
+    /// Takes everything, that was rendered since the last call
+    fn take_output(&mut self) -> VerboseResult<Option<WavBuffer>> {
+        Ok(None)
+    }
+}
+
+// settings of a source for backends, that don't keep them in an audio library
+#[derive(Debug, Clone)]
+pub(crate) struct SourceParameters {
+    pub(crate) state: SourceState,
+    // position in frames of the source data
+    pub(crate) cursor: f64,
+
+    pub(crate) looping: bool,
+    pub(crate) volume: f32,
+    pub(crate) min_volume: f32,
+    pub(crate) max_volume: f32,
+    pub(crate) pitch: f32,
+
+    pub(crate) position: [f32; 3],
+    pub(crate) direction: [f32; 3],
+    pub(crate) relative: bool,
+    pub(crate) attenuation: f32,
+    pub(crate) reference_distance: f32,
+    pub(crate) max_distance: f32,
+    pub(crate) air_absorption_factor: f32,
+
+    pub(crate) low_pass: Option<f32>,
+    pub(crate) high_pass: Option<f32>,
+}
+
+impl Default for SourceParameters {
+    // same defaults as OpenAL
+    fn default() -> Self {
+        SourceParameters {
+            state: SourceState::Initial,
+            cursor: 0.0,
+
+            looping: false,
+            volume: 1.0,
+            min_volume: 0.0,
+            max_volume: 1.0,
+            pitch: 1.0,
+
+            position: [0.0, 0.0, 0.0],
+            direction: [0.0, 0.0, 0.0],
+            relative: false,
+            attenuation: 1.0,
+            reference_distance: 1.0,
+            max_distance: f32::MAX,
+            air_absorption_factor: 0.0,
+
+            low_pass: None,
+            high_pass: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct ListenerParameters {
+    pub(crate) position: [f32; 3],
+    pub(crate) direction: [f32; 3],
+    pub(crate) up: [f32; 3],
+    pub(crate) volume: f32,
+}
+
+impl Default for ListenerParameters {
+    fn default() -> Self {
+        ListenerParameters {
+            position: [0.0, 0.0, 0.0],
+            direction: [0.0, 0.0, -1.0],
+            up: [0.0, 1.0, 0.0],
+            volume: 1.0,
+        }
+    }
+}
+
+// hands out unique source ids
+#[derive(Debug, Default)]
+pub(crate) struct SourceIdGenerator {
+    next: u32,
+}
+
+impl SourceIdGenerator {
+    pub(crate) fn create(&mut self) -> SourceId {
+        let id = SourceId(self.next);
+        self.next += 1;
+
+        id
+    }
+}
@@ -0,0 +1,197 @@
+use presentation::prelude::*;
+
+use super::{
+    wavbuffer::WavBuffer, AudioBackend, ListenerParameters, Reverb, SourceId, SourceIdGenerator,
+    SourceParameters, SourceState,
+};
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct NullSource {
+    parameters: SourceParameters,
+    duration: Duration,
+
+    // playback time before the last start or resume
+    elapsed: Duration,
+    playing_since: Option<Instant>,
+}
+
+impl NullSource {
+    fn position(&self, now: Instant) -> Duration {
+        match self.playing_since {
+            Some(playing_since) => self.elapsed + now.saturating_duration_since(playing_since),
+            None => self.elapsed,
+        }
+    }
+
+    // a source, that played to its end, is stopped
+    fn state(&self, now: Instant) -> SourceState {
+        if self.parameters.state == SourceState::Playing
+            && !self.parameters.looping
+            && self.position(now) >= self.duration
+        {
+            SourceState::Stopped
+        } else {
+            self.parameters.state
+        }
+    }
+}
+
+/// Backend without any output, e.g. for machines without an audio device
+///
+/// Sources play in real time and non-looping ones stop at the end of their wav file.
+/// Files in other formats can't be probed, they have a duration of zero and stop right away.
+/// Pitch is ignored.
+#[derive(Debug, Default)]
+pub struct NullBackend {
+    ids: SourceIdGenerator,
+    sources: HashMap<SourceId, NullSource>,
+
+    listener: ListenerParameters,
+
+    durations: HashMap<String, Duration>,
+}
+
+impl NullBackend {
+    pub fn new() -> Self {
+        NullBackend::default()
+    }
+
+    fn source(&self, source: SourceId) -> VerboseResult<&NullSource> {
+        match self.sources.get(&source) {
+            Some(source) => Ok(source),
+            None => create_error!(format!("audio source ({:?}) does not exist", source)),
+        }
+    }
+
+    fn source_mut(&mut self, source: SourceId) -> VerboseResult<&mut NullSource> {
+        match self.sources.get_mut(&source) {
+            Some(source) => Ok(source),
+            None => create_error!(format!("audio source ({:?}) does not exist", source)),
+        }
+    }
+
+    fn parameters_mut(&mut self, source: SourceId) -> VerboseResult<&mut SourceParameters> {
+        Ok(&mut self.source_mut(source)?.parameters)
+    }
+
+    fn create_source(&mut self, path: &str) -> SourceId {
+        let duration = match self.durations.get(path) {
+            Some(duration) => *duration,
+            None => {
+                let duration = WavBuffer::probe_duration(path).unwrap_or_default();
+                self.durations.insert(path.to_string(), duration);
+
+                duration
+            }
+        };
+
+        let source = self.ids.create();
+
+        self.sources.insert(
+            source,
+            NullSource {
+                parameters: SourceParameters::default(),
+                duration,
+
+                elapsed: Duration::default(),
+                playing_since: None,
+            },
+        );
+
+        source
+    }
+}
+
+impl AudioBackend for NullBackend {
+    fn load_sound(&mut self, path: &str) -> VerboseResult<SourceId> {
+        Ok(self.create_source(path))
+    }
+
+    fn load_music(&mut self, path: &str) -> VerboseResult<SourceId> {
+        Ok(self.create_source(path))
+    }
+
+    fn remove_source(&mut self, source: SourceId) {
+        self.sources.remove(&source);
+    }
+
+    fn clear_cache(&mut self) {
+        self.durations.clear();
+    }
+
+    fn play(&mut self, source: SourceId) -> VerboseResult<()> {
+        let now = Instant::now();
+        let source = self.source_mut(source)?;
+
+        if source.state(now) != SourceState::Paused {
+            source.elapsed = Duration::default();
+        }
+
+        source.parameters.state = SourceState::Playing;
+        source.playing_since = Some(now);
+
+        Ok(())
+    }
+
+    fn pause(&mut self, source: SourceId) -> VerboseResult<()> {
+        let now = Instant::now();
+        let source = self.source_mut(source)?;
+
+        if source.state(now) == SourceState::Playing {
+            source.elapsed = source.position(now);
+            source.playing_since = None;
+            source.parameters.state = SourceState::Paused;
+        }
+
+        Ok(())
+    }
+
+    fn stop(&mut self, source: SourceId) -> VerboseResult<()> {
+        let source = self.source_mut(source)?;
+
+        source.parameters.state = SourceState::Stopped;
+        source.elapsed = Duration::default();
+        source.playing_since = None;
+
+        Ok(())
+    }
+
+    fn state(&self, source: SourceId) -> VerboseResult<SourceState> {
+        Ok(self.source(source)?.state(Instant::now()))
+    }
+
+    fn set_looping(&mut self, source: SourceId, looping: bool) -> VerboseResult<()> {
+        let now = Instant::now();
+        let source = self.source_mut(source)?;
+
+        // the current repetition of a looping source is played to its end
+        if source.parameters.looping && !looping && source.duration > Duration::default() {
+            let position = source.position(now).as_secs_f64() % source.duration.as_secs_f64();
+
+            source.elapsed = Duration::from_secs_f64(position);
+
+            if source.playing_since.is_some() {
+                source.playing_since = Some(now);
+            }
+        }
+
+        source.parameters.looping = looping;
+
+        Ok(())
+    }
+
+    fn duration(&self, source: SourceId) -> VerboseResult<Duration> {
+        Ok(self.source(source)?.duration)
+    }
+
+    fn connect(&mut self, source: SourceId, _reverb: &Option<Reverb>) -> VerboseResult<()> {
+        self.source(source)?;
+
+        Ok(())
+    }
+
+    impl_source_parameters!();
+}
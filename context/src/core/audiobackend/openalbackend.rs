@@ -0,0 +1,395 @@
+use ears::{
+    init, listener, AudioController, Music as ALMusic, ReverbEffect, ReverbPreset,
+    Sound as ALSound, SoundData, State,
+};
+
+use presentation::prelude::*;
+
+use super::{AudioBackend, Reverb, SourceId, SourceIdGenerator, SourceState};
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::time::Duration;
+
+enum OpenALSource {
+    Sound(ALSound),
+    Music(ALMusic),
+}
+
+// calls the same AudioController function on sounds and music
+macro_rules! with_controller {
+    ($source:expr, $controller:ident => $call:expr) => {
+        match $source {
+            OpenALSource::Sound($controller) => $call,
+            OpenALSource::Music($controller) => $call,
+        }
+    };
+}
+
+/// Backend on top of ears, needs an audio device
+pub struct OpenALBackend {
+    ids: SourceIdGenerator,
+    sources: HashMap<SourceId, OpenALSource>,
+
+    // 'clever' data handling
+    data: HashMap<String, Rc<RefCell<SoundData>>>,
+
+    // effects are shared by all sources with the same reverb
+    reverbs: Vec<(Reverb, Option<ReverbEffect>)>,
+    connections: HashMap<SourceId, Reverb>,
+}
+
+impl OpenALBackend {
+    pub fn new() -> VerboseResult<Self> {
+        init()?;
+
+        Ok(OpenALBackend {
+            ids: SourceIdGenerator::default(),
+            sources: HashMap::new(),
+
+            data: HashMap::new(),
+
+            reverbs: Vec::new(),
+            connections: HashMap::new(),
+        })
+    }
+
+    fn source(&self, source: SourceId) -> VerboseResult<&OpenALSource> {
+        match self.sources.get(&source) {
+            Some(source) => Ok(source),
+            None => create_error!(format!("audio source ({:?}) does not exist", source)),
+        }
+    }
+
+    fn source_mut(&mut self, source: SourceId) -> VerboseResult<&mut OpenALSource> {
+        match self.sources.get_mut(&source) {
+            Some(source) => Ok(source),
+            None => create_error!(format!("audio source ({:?}) does not exist", source)),
+        }
+    }
+
+    fn insert(&mut self, source: OpenALSource) -> SourceId {
+        let id = self.ids.create();
+        self.sources.insert(id, source);
+
+        id
+    }
+
+    // index of the effect in `reverbs`, it is created if no source uses the reverb yet
+    fn reverb_effect(&mut self, reverb: &Reverb) -> VerboseResult<usize> {
+        if let Some(index) = self.reverbs.iter().position(|(r, _)| r == reverb) {
+            return Ok(index);
+        }
+
+        let mut properties = ReverbPreset::Generic.properties();
+
+        properties.density = reverb.density;
+        properties.diffusion = reverb.diffusion;
+        properties.gain = reverb.gain;
+        properties.gain_hf = reverb.gain_hf;
+        properties.gain_lf = reverb.gain_lf;
+        properties.decay_time = reverb.decay_time;
+        properties.decay_hf_ratio = reverb.decay_hf_ratio;
+        properties.decay_lf_ratio = reverb.decay_lf_ratio;
+        properties.reflections_gain = reverb.reflections_gain;
+        properties.reflections_delay = reverb.reflections_delay;
+        properties.reflections_pan = reverb.reflections_pan;
+        properties.late_reverb_gain = reverb.late_reverb_gain;
+        properties.late_reverb_delay = reverb.late_reverb_delay;
+        properties.late_reverb_pan = reverb.late_reverb_pan;
+        properties.echo_time = reverb.echo_time;
+        properties.echo_depth = reverb.echo_depth;
+        properties.modulation_time = reverb.modulation_time;
+        properties.modulation_depth = reverb.modulation_depth;
+        properties.air_absorption_gain_hf = reverb.air_absorption_gain_hf;
+        properties.hf_reference = reverb.hf_reference;
+        properties.lf_reference = reverb.lf_reference;
+        properties.room_rolloff_factor = reverb.room_rolloff_factor;
+        properties.decay_hf_limit = reverb.decay_hf_limit as _;
+
+        let effect = match ReverbEffect::preset(properties) {
+            Ok(effect) => effect,
+            Err(_) => create_error!("failed creating OpenAL reverb effect"),
+        };
+
+        self.reverbs.push((*reverb, Some(effect)));
+
+        Ok(self.reverbs.len() - 1)
+    }
+
+    // drops effects, that aren't connected to a source anymore
+    fn release_reverbs(&mut self) {
+        let connections = &self.connections;
+
+        self.reverbs
+            .retain(|(reverb, _)| connections.values().any(|r| r == reverb));
+    }
+}
+
+impl From<ReverbPreset> for Reverb {
+    fn from(preset: ReverbPreset) -> Self {
+        let properties = preset.properties();
+
+        Reverb {
+            density: properties.density,
+            diffusion: properties.diffusion,
+            gain: properties.gain,
+            gain_hf: properties.gain_hf,
+            gain_lf: properties.gain_lf,
+            decay_time: properties.decay_time,
+            decay_hf_ratio: properties.decay_hf_ratio,
+            decay_lf_ratio: properties.decay_lf_ratio,
+            reflections_gain: properties.reflections_gain,
+            reflections_delay: properties.reflections_delay,
+            reflections_pan: properties.reflections_pan,
+            late_reverb_gain: properties.late_reverb_gain,
+            late_reverb_delay: properties.late_reverb_delay,
+            late_reverb_pan: properties.late_reverb_pan,
+            echo_time: properties.echo_time,
+            echo_depth: properties.echo_depth,
+            modulation_time: properties.modulation_time,
+            modulation_depth: properties.modulation_depth,
+            air_absorption_gain_hf: properties.air_absorption_gain_hf,
+            hf_reference: properties.hf_reference,
+            lf_reference: properties.lf_reference,
+            room_rolloff_factor: properties.room_rolloff_factor,
+            decay_hf_limit: properties.decay_hf_limit != 0,
+        }
+    }
+}
+
+impl AudioBackend for OpenALBackend {
+    fn load_sound(&mut self, path: &str) -> VerboseResult<SourceId> {
+        let sound = match self.data.get(path) {
+            Some(data) => ALSound::new_with_data(data.clone())?,
+            None => {
+                let sound = ALSound::new(path)?;
+                self.data.insert(path.to_string(), sound.get_datas());
+
+                sound
+            }
+        };
+
+        Ok(self.insert(OpenALSource::Sound(sound)))
+    }
+
+    fn load_music(&mut self, path: &str) -> VerboseResult<SourceId> {
+        let music = ALMusic::new(path)?;
+
+        Ok(self.insert(OpenALSource::Music(music)))
+    }
+
+    fn remove_source(&mut self, source: SourceId) {
+        self.sources.remove(&source);
+
+        if self.connections.remove(&source).is_some() {
+            self.release_reverbs();
+        }
+    }
+
+    fn clear_cache(&mut self) {
+        self.data.clear();
+    }
+
+    fn play(&mut self, source: SourceId) -> VerboseResult<()> {
+        with_controller!(self.source_mut(source)?, controller => controller.play());
+
+        Ok(())
+    }
+
+    fn pause(&mut self, source: SourceId) -> VerboseResult<()> {
+        with_controller!(self.source_mut(source)?, controller => controller.pause());
+
+        Ok(())
+    }
+
+    fn stop(&mut self, source: SourceId) -> VerboseResult<()> {
+        with_controller!(self.source_mut(source)?, controller => controller.stop());
+
+        Ok(())
+    }
+
+    fn state(&self, source: SourceId) -> VerboseResult<SourceState> {
+        let state = with_controller!(self.source(source)?, controller => controller.get_state());
+
+        Ok(match state {
+            State::Initial => SourceState::Initial,
+            State::Playing => SourceState::Playing,
+            State::Paused => SourceState::Paused,
+            State::Stopped => SourceState::Stopped,
+        })
+    }
+
+    fn duration(&self, source: SourceId) -> VerboseResult<Duration> {
+        Ok(with_controller!(self.source(source)?, controller => controller.get_duration()))
+    }
+
+    fn set_looping(&mut self, source: SourceId, looping: bool) -> VerboseResult<()> {
+        with_controller!(self.source_mut(source)?, controller => controller.set_looping(looping));
+
+        Ok(())
+    }
+
+    fn set_volume(&mut self, source: SourceId, volume: f32) -> VerboseResult<()> {
+        with_controller!(self.source_mut(source)?, controller => controller.set_volume(volume));
+
+        Ok(())
+    }
+
+    fn set_position(&mut self, source: SourceId, position: [f32; 3]) -> VerboseResult<()> {
+        with_controller!(self.source_mut(source)?, controller => controller.set_position(position));
+
+        Ok(())
+    }
+
+    fn set_direction(&mut self, source: SourceId, direction: [f32; 3]) -> VerboseResult<()> {
+        with_controller!(
+            self.source_mut(source)?,
+            controller => controller.set_direction(direction)
+        );
+
+        Ok(())
+    }
+
+    fn set_attenuation(&mut self, source: SourceId, attenuation: f32) -> VerboseResult<()> {
+        with_controller!(
+            self.source_mut(source)?,
+            controller => controller.set_attenuation(attenuation)
+        );
+
+        Ok(())
+    }
+
+    fn set_max_volume(&mut self, source: SourceId, max_volume: f32) -> VerboseResult<()> {
+        with_controller!(
+            self.source_mut(source)?,
+            controller => controller.set_max_volume(max_volume)
+        );
+
+        Ok(())
+    }
+
+    fn set_min_volume(&mut self, source: SourceId, min_volume: f32) -> VerboseResult<()> {
+        with_controller!(
+            self.source_mut(source)?,
+            controller => controller.set_min_volume(min_volume)
+        );
+
+        Ok(())
+    }
+
+    fn set_max_distance(&mut self, source: SourceId, max_distance: f32) -> VerboseResult<()> {
+        with_controller!(
+            self.source_mut(source)?,
+            controller => controller.set_max_distance(max_distance)
+        );
+
+        Ok(())
+    }
+
+    fn set_reference_distance(&mut self, source: SourceId, distance: f32) -> VerboseResult<()> {
+        with_controller!(
+            self.source_mut(source)?,
+            controller => controller.set_reference_distance(distance)
+        );
+
+        Ok(())
+    }
+
+    fn set_relative(&mut self, source: SourceId, relative: bool) -> VerboseResult<()> {
+        with_controller!(self.source_mut(source)?, controller => controller.set_relative(relative));
+
+        Ok(())
+    }
+
+    fn set_pitch(&mut self, source: SourceId, pitch: f32) -> VerboseResult<()> {
+        with_controller!(self.source_mut(source)?, controller => controller.set_pitch(pitch));
+
+        Ok(())
+    }
+
+    fn set_air_absorption_factor(&mut self, source: SourceId, factor: f32) -> VerboseResult<()> {
+        // ears only supports air absorption for sounds
+        if let OpenALSource::Sound(sound) = self.source_mut(source)? {
+            sound.set_air_absorption_factor(factor);
+        }
+
+        Ok(())
+    }
+
+    fn connect(&mut self, source: SourceId, reverb: &Option<Reverb>) -> VerboseResult<()> {
+        self.source(source)?;
+
+        match reverb {
+            Some(reverb) => {
+                let index = self.reverb_effect(reverb)?;
+
+                if let Some(al_source) = self.sources.get_mut(&source) {
+                    with_controller!(
+                        al_source,
+                        controller => controller.connect(&self.reverbs[index].1)
+                    );
+                }
+
+                self.connections.insert(source, *reverb);
+            }
+            None => {
+                with_controller!(self.source_mut(source)?, controller => controller.connect(&None));
+
+                self.connections.remove(&source);
+            }
+        }
+
+        self.release_reverbs();
+
+        Ok(())
+    }
+
+    // ears neither exposes EFX filters nor the OpenAL source ids, that they would be attached to
+    fn supports_filters(&self) -> bool {
+        false
+    }
+
+    fn set_filters(
+        &mut self,
+        source: SourceId,
+        low_pass: Option<f32>,
+        high_pass: Option<f32>,
+    ) -> VerboseResult<()> {
+        self.source(source)?;
+
+        if low_pass.is_some() || high_pass.is_some() {
+            create_error!("OpenAL backend doesn't support filters");
+        }
+
+        Ok(())
+    }
+
+    fn set_listener_position(&mut self, position: [f32; 3]) {
+        listener::set_position(position);
+    }
+
+    fn listener_position(&self) -> [f32; 3] {
+        listener::get_position()
+    }
+
+    fn set_listener_orientation(&mut self, direction: [f32; 3], up: [f32; 3]) {
+        listener::set_orientation(direction, up);
+    }
+
+    fn listener_orientation(&self) -> ([f32; 3], [f32; 3]) {
+        listener::get_orientation()
+    }
+
+    fn set_listener_volume(&mut self, volume: f32) {
+        listener::set_volume(volume);
+    }
+
+    fn listener_volume(&self) -> f32 {
+        listener::get_volume()
+    }
+}
+
+// safe since, OpenAL is thread safe
+unsafe impl Send for OpenALBackend {}
@@ -0,0 +1,415 @@
+use presentation::prelude::*;
+
+use super::{
+    wavbuffer::WavBuffer, AudioBackend, ListenerParameters, Reverb, SourceId, SourceIdGenerator,
+    SourceParameters, SourceState,
+};
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+// previous outputs of the one pole filters of a channel
+#[derive(Debug, Clone, Copy, Default)]
+struct FilterState {
+    low_pass: f32,
+    high_pass: f32,
+}
+
+struct SoftwareSource {
+    data: Arc<WavBuffer>,
+    parameters: SourceParameters,
+    filters: Vec<FilterState>,
+}
+
+/// Offline mixer, that renders into a stereo `WavBuffer` instead of an audio device
+///
+/// Sources are read from wav files or from data registered with `insert_data`. Mono sources
+/// are panned and attenuated like with OpenAL's default inverse distance model, reverb,
+/// air absorption and cones are ignored.
+pub struct SoftwareBackend {
+    sample_rate: u32,
+
+    ids: SourceIdGenerator,
+    sources: HashMap<SourceId, SoftwareSource>,
+
+    listener: ListenerParameters,
+
+    data: HashMap<String, Arc<WavBuffer>>,
+
+    // interleaved stereo samples, that weren't taken yet
+    output: Vec<f32>,
+    // fraction of a frame, that is left over from the last render
+    remainder: f64,
+}
+
+impl SoftwareBackend {
+    pub fn new(sample_rate: u32) -> VerboseResult<Self> {
+        if sample_rate == 0 {
+            create_error!("sample rate of the software backend must not be 0");
+        }
+
+        Ok(SoftwareBackend {
+            sample_rate,
+
+            ids: SourceIdGenerator::default(),
+            sources: HashMap::new(),
+
+            listener: ListenerParameters::default(),
+
+            data: HashMap::new(),
+
+            output: Vec::new(),
+            remainder: 0.0,
+        })
+    }
+
+    /// Sources of `path` use `data` instead of reading the file
+    pub fn insert_data(&mut self, path: &str, data: WavBuffer) {
+        self.data.insert(path.to_string(), Arc::new(data));
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn parameters(&self, source: SourceId) -> VerboseResult<&SourceParameters> {
+        match self.sources.get(&source) {
+            Some(source) => Ok(&source.parameters),
+            None => create_error!(format!("audio source ({:?}) does not exist", source)),
+        }
+    }
+
+    fn parameters_mut(&mut self, source: SourceId) -> VerboseResult<&mut SourceParameters> {
+        match self.sources.get_mut(&source) {
+            Some(source) => Ok(&mut source.parameters),
+            None => create_error!(format!("audio source ({:?}) does not exist", source)),
+        }
+    }
+
+    fn create_source(&mut self, path: &str) -> VerboseResult<SourceId> {
+        let data = match self.data.get(path) {
+            Some(data) => data.clone(),
+            None => {
+                let data = Arc::new(WavBuffer::from_file(path)?);
+                self.data.insert(path.to_string(), data.clone());
+
+                data
+            }
+        };
+
+        let source = self.ids.create();
+
+        self.sources.insert(
+            source,
+            SoftwareSource {
+                filters: vec![FilterState::default(); data.channels() as usize],
+                data,
+                parameters: SourceParameters::default(),
+            },
+        );
+
+        Ok(source)
+    }
+
+    fn render_source(
+        source: &mut SoftwareSource,
+        listener: &ListenerParameters,
+        sample_rate: u32,
+        output: &mut [f32],
+    ) {
+        let data = &source.data;
+        let parameters = &mut source.parameters;
+
+        let frames = data.frames();
+        let channels = data.channels() as usize;
+        let samples = data.samples();
+
+        if frames == 0 {
+            parameters.state = SourceState::Stopped;
+            return;
+        }
+
+        let (left_gain, right_gain) = Self::gains(parameters, channels, listener);
+        let step =
+            parameters.pitch.max(0.0) as f64 * data.sample_rate() as f64 / sample_rate as f64;
+
+        let low_pass = parameters
+            .low_pass
+            .map(|cutoff| Self::filter_factor(cutoff, sample_rate));
+        let high_pass = parameters
+            .high_pass
+            .map(|cutoff| Self::filter_factor(cutoff, sample_rate));
+
+        for frame in output.chunks_exact_mut(2) {
+            let index = parameters.cursor as usize;
+            let next = if index + 1 < frames {
+                index + 1
+            } else if parameters.looping {
+                0
+            } else {
+                index
+            };
+            let fraction = (parameters.cursor - index as f64) as f32;
+
+            // stereo sources keep their channels, additional channels are dropped
+            for channel in 0..channels.min(2) {
+                let current = samples[index * channels + channel];
+                let mut sample =
+                    current + (samples[next * channels + channel] - current) * fraction;
+
+                let filter = &mut source.filters[channel];
+
+                if let Some(factor) = low_pass {
+                    filter.low_pass += factor * (sample - filter.low_pass);
+                    sample = filter.low_pass;
+                }
+
+                if let Some(factor) = high_pass {
+                    filter.high_pass += factor * (sample - filter.high_pass);
+                    sample -= filter.high_pass;
+                }
+
+                if channels == 1 {
+                    frame[0] += sample * left_gain;
+                    frame[1] += sample * right_gain;
+                } else if channel == 0 {
+                    frame[0] += sample * left_gain;
+                } else {
+                    frame[1] += sample * right_gain;
+                }
+            }
+
+            parameters.cursor += step;
+
+            if parameters.cursor >= frames as f64 {
+                if parameters.looping {
+                    parameters.cursor %= frames as f64;
+                } else {
+                    parameters.state = SourceState::Stopped;
+                    parameters.cursor = 0.0;
+
+                    break;
+                }
+            }
+        }
+    }
+
+    // volume of the left and right channel
+    fn gains(
+        parameters: &SourceParameters,
+        channels: usize,
+        listener: &ListenerParameters,
+    ) -> (f32, f32) {
+        // like OpenAL, only mono sources are spatialized
+        if channels != 1 {
+            let gain = Self::clamp_volume(parameters, parameters.volume) * listener.volume;
+
+            return (gain, gain);
+        }
+
+        let offset = if parameters.relative {
+            parameters.position
+        } else {
+            sub(parameters.position, listener.position)
+        };
+
+        let distance = length(offset);
+
+        // inverse distance clamped
+        let reference = parameters.reference_distance;
+        let clamped = distance.max(reference).min(parameters.max_distance);
+        let denominator = reference + parameters.attenuation * (clamped - reference);
+
+        let attenuation = if reference > 0.0 && denominator > 0.0 {
+            reference / denominator
+        } else {
+            1.0
+        };
+
+        let gain =
+            Self::clamp_volume(parameters, parameters.volume * attenuation) * listener.volume;
+
+        // relative sources aren't rotated with the listener
+        let right = if parameters.relative {
+            [1.0, 0.0, 0.0]
+        } else {
+            normalize(cross(listener.direction, listener.up))
+        };
+
+        let pan = if distance > f32::EPSILON {
+            dot(normalize(offset), right)
+        } else {
+            0.0
+        };
+
+        (gain * (1.0 - pan).min(1.0), gain * (1.0 + pan).min(1.0))
+    }
+
+    fn clamp_volume(parameters: &SourceParameters, volume: f32) -> f32 {
+        volume.max(parameters.min_volume).min(parameters.max_volume)
+    }
+
+    // smoothing factor of a one pole filter
+    fn filter_factor(cutoff: f32, sample_rate: u32) -> f32 {
+        1.0 - (-2.0 * std::f32::consts::PI * cutoff.max(0.0) / sample_rate as f32).exp()
+    }
+}
+
+impl AudioBackend for SoftwareBackend {
+    fn load_sound(&mut self, path: &str) -> VerboseResult<SourceId> {
+        self.create_source(path)
+    }
+
+    fn load_music(&mut self, path: &str) -> VerboseResult<SourceId> {
+        self.create_source(path)
+    }
+
+    fn remove_source(&mut self, source: SourceId) {
+        self.sources.remove(&source);
+    }
+
+    fn clear_cache(&mut self) {
+        self.data.clear();
+    }
+
+    fn duration(&self, source: SourceId) -> VerboseResult<Duration> {
+        match self.sources.get(&source) {
+            Some(source) => Ok(source.data.duration()),
+            None => create_error!(format!("audio source ({:?}) does not exist", source)),
+        }
+    }
+
+    fn connect(&mut self, source: SourceId, _reverb: &Option<Reverb>) -> VerboseResult<()> {
+        self.parameters(source)?;
+
+        Ok(())
+    }
+
+    impl_source_playback!();
+    impl_source_parameters!();
+
+    fn render(&mut self, duration: Duration) -> VerboseResult<()> {
+        let frames = duration.as_secs_f64() * self.sample_rate as f64 + self.remainder;
+
+        self.remainder = frames.fract();
+
+        let mut buffer = vec![0.0; frames as usize * 2];
+
+        for source in self.sources.values_mut() {
+            if source.parameters.state == SourceState::Playing {
+                Self::render_source(source, &self.listener, self.sample_rate, &mut buffer);
+            }
+        }
+
+        self.output.append(&mut buffer);
+
+        Ok(())
+    }
+
+    fn take_output(&mut self) -> VerboseResult<Option<WavBuffer>> {
+        let samples = std::mem::take(&mut self.output);
+
+        Ok(Some(WavBuffer::new(self.sample_rate, 2, samples)?))
+    }
+}
+
+fn sub(lhs: [f32; 3], rhs: [f32; 3]) -> [f32; 3] {
+    [lhs[0] - rhs[0], lhs[1] - rhs[1], lhs[2] - rhs[2]]
+}
+
+fn dot(lhs: [f32; 3], rhs: [f32; 3]) -> f32 {
+    lhs[0] * rhs[0] + lhs[1] * rhs[1] + lhs[2] * rhs[2]
+}
+
+fn cross(lhs: [f32; 3], rhs: [f32; 3]) -> [f32; 3] {
+    [
+        lhs[1] * rhs[2] - lhs[2] * rhs[1],
+        lhs[2] * rhs[0] - lhs[0] * rhs[2],
+        lhs[0] * rhs[1] - lhs[1] * rhs[0],
+    ]
+}
+
+fn length(vector: [f32; 3]) -> f32 {
+    dot(vector, vector).sqrt()
+}
+
+fn normalize(vector: [f32; 3]) -> [f32; 3] {
+    let length = length(vector);
+
+    if length > f32::EPSILON {
+        [vector[0] / length, vector[1] / length, vector[2] / length]
+    } else {
+        vector
+    }
+}
+
+#[test]
+fn software_backend_renders_until_the_end() {
+    let mut backend = SoftwareBackend::new(100).unwrap();
+    backend.insert_data("constant", WavBuffer::new(100, 1, vec![0.5; 10]).unwrap());
+
+    let source = backend.load_sound("constant").unwrap();
+    assert_eq!(
+        backend.duration(source).unwrap(),
+        Duration::from_millis(100)
+    );
+
+    // sources, that aren't playing, render silence
+    backend.render(Duration::from_millis(20)).unwrap();
+
+    backend.play(source).unwrap();
+    backend.render(Duration::from_millis(50)).unwrap();
+    assert_eq!(backend.state(source).unwrap(), SourceState::Playing);
+
+    backend.set_volume(source, 0.5).unwrap();
+    backend.render(Duration::from_millis(100)).unwrap();
+    assert_eq!(backend.state(source).unwrap(), SourceState::Stopped);
+
+    let output = backend.take_output().unwrap().unwrap();
+
+    assert_eq!(output.sample_rate(), 100);
+    assert_eq!(output.channels(), 2);
+    assert_eq!(output.frames(), 17);
+
+    let left = output.channel(0);
+    assert_eq!(left, output.channel(1));
+    assert_eq!(&left[0..2], &[0.0; 2]);
+    assert_eq!(&left[2..7], &[0.5; 5]);
+    assert_eq!(&left[7..12], &[0.25; 5]);
+    assert_eq!(&left[12..], &[0.0; 5]);
+
+    // the output was taken
+    assert_eq!(backend.take_output().unwrap().unwrap().frames(), 0);
+}
+
+#[test]
+fn software_backend_pans_attenuates_and_loops() {
+    let mut backend = SoftwareBackend::new(100).unwrap();
+    backend.insert_data("constant", WavBuffer::new(100, 1, vec![0.5; 10]).unwrap());
+
+    let source = backend.load_sound("constant").unwrap();
+
+    // to the right of the listener, twice the reference distance away
+    backend.set_position(source, [2.0, 0.0, 0.0]).unwrap();
+    backend.set_looping(source, true).unwrap();
+    backend.play(source).unwrap();
+
+    // fractions of a frame are carried over to the next render
+    backend.render(Duration::from_millis(5)).unwrap();
+    assert_eq!(backend.take_output().unwrap().unwrap().frames(), 0);
+
+    backend.render(Duration::from_millis(5)).unwrap();
+    backend.render(Duration::from_millis(240)).unwrap();
+    assert_eq!(backend.state(source).unwrap(), SourceState::Playing);
+
+    let output = backend.take_output().unwrap().unwrap();
+
+    assert_eq!(output.frames(), 25);
+    assert!(output.channel(0).iter().all(|sample| *sample == 0.0));
+    assert!(output.channel(1).iter().all(|sample| *sample == 0.25));
+
+    backend.pause(source).unwrap();
+    backend.render(Duration::from_millis(100)).unwrap();
+    assert_eq!(backend.take_output().unwrap().unwrap().peak(), 0.0);
+}
@@ -0,0 +1,359 @@
+use presentation::prelude::*;
+
+use std::convert::TryInto;
+use std::fs;
+use std::time::Duration;
+
+const PCM_FORMAT: u16 = 1;
+const FLOAT_FORMAT: u16 = 3;
+const EXTENSIBLE_FORMAT: u16 = 0xFFFE;
+
+struct WavFormat {
+    tag: u16,
+    channels: u16,
+    sample_rate: u32,
+    bits: u16,
+}
+
+/// Interleaved samples in [-1, 1], can be read from and written to WAV files
+#[derive(Debug, Clone, PartialEq)]
+pub struct WavBuffer {
+    sample_rate: u32,
+    channels: u16,
+    samples: Vec<f32>,
+}
+
+impl WavBuffer {
+    pub fn new(sample_rate: u32, channels: u16, samples: Vec<f32>) -> VerboseResult<Self> {
+        if sample_rate == 0 {
+            create_error!("sample rate of a wav buffer must not be 0");
+        }
+
+        if channels == 0
+            || !samples
+                .chunks_exact(channels as usize)
+                .remainder()
+                .is_empty()
+        {
+            create_error!(format!(
+                "{} samples can't be split into {} channels",
+                samples.len(),
+                channels
+            ));
+        }
+
+        Ok(WavBuffer {
+            sample_rate,
+            channels,
+            samples,
+        })
+    }
+
+    pub fn from_file(path: &str) -> VerboseResult<Self> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(err) => create_error!(format!("failed reading wav file ({}): {}", path, err)),
+        };
+
+        Self::from_bytes(&bytes)
+    }
+
+    /// Duration of a wav file, without decoding its samples
+    pub fn probe_duration(path: &str) -> VerboseResult<Duration> {
+        let bytes = match fs::read(path) {
+            Ok(bytes) => bytes,
+            Err(err) => create_error!(format!("failed reading wav file ({}): {}", path, err)),
+        };
+
+        let (format, data) = Self::chunks(&bytes)?;
+        let frame_size = format.channels as usize * (format.bits as usize / 8);
+
+        if format.sample_rate == 0 || frame_size == 0 {
+            create_error!(format!("wav file ({}) has an invalid format", path));
+        }
+
+        Ok(Duration::from_secs_f64(
+            (data.len() / frame_size) as f64 / format.sample_rate as f64,
+        ))
+    }
+
+    /// Reads 8, 16, 24 and 32 bit integer and 32 bit float files
+    pub fn from_bytes(bytes: &[u8]) -> VerboseResult<Self> {
+        let (format, data) = Self::chunks(bytes)?;
+
+        let samples = match (format.tag, format.bits) {
+            (PCM_FORMAT, 8) => data
+                .iter()
+                .map(|&byte| (byte as f32 - 128.0) / 128.0)
+                .collect(),
+            (PCM_FORMAT, 16) => data
+                .chunks_exact(2)
+                .map(|bytes| i16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 32768.0)
+                .collect(),
+            (PCM_FORMAT, 24) => data
+                .chunks_exact(3)
+                .map(|bytes| {
+                    // shifting back keeps the sign
+                    (i32::from_le_bytes([0, bytes[0], bytes[1], bytes[2]]) >> 8) as f32
+                        / 8_388_608.0
+                })
+                .collect(),
+            (PCM_FORMAT, 32) => data
+                .chunks_exact(4)
+                .map(|bytes| i32::from_le_bytes(bytes.try_into().unwrap()) as f32 / 2_147_483_648.0)
+                .collect(),
+            (FLOAT_FORMAT, 32) => data
+                .chunks_exact(4)
+                .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+                .collect(),
+            _ => create_error!(format!(
+                "unsupported wav format ({}) with {} bits per sample",
+                format.tag, format.bits
+            )),
+        };
+
+        Self::new(format.sample_rate, format.channels, samples)
+    }
+
+    // fmt and data chunk of a wav file
+    fn chunks(bytes: &[u8]) -> VerboseResult<(WavFormat, &[u8])> {
+        if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            create_error!("data is not a wav file");
+        }
+
+        let mut format = None;
+        let mut data = None;
+        let mut offset = 12;
+
+        while offset + 8 <= bytes.len() {
+            let id = &bytes[offset..offset + 4];
+            let size = read_u32(bytes, offset + 4) as usize;
+            let start = offset + 8;
+            let end = (start + size).min(bytes.len());
+
+            match id {
+                b"fmt " => {
+                    if end - start < 16 {
+                        create_error!("fmt chunk of wav file is too small");
+                    }
+
+                    let mut tag = read_u16(bytes, start);
+
+                    // the actual format is the start of the sub format guid
+                    if tag == EXTENSIBLE_FORMAT && end - start >= 26 {
+                        tag = read_u16(bytes, start + 24);
+                    }
+
+                    format = Some(WavFormat {
+                        tag,
+                        channels: read_u16(bytes, start + 2),
+                        sample_rate: read_u32(bytes, start + 4),
+                        bits: read_u16(bytes, start + 14),
+                    });
+                }
+                b"data" => data = Some(&bytes[start..end]),
+                _ => (),
+            }
+
+            // chunks are aligned to 2 bytes
+            offset = start + size + size % 2;
+        }
+
+        let format = match format {
+            Some(format) => format,
+            None => create_error!("wav file has no fmt chunk"),
+        };
+
+        match data {
+            Some(data) => Ok((format, data)),
+            None => create_error!("wav file has no data chunk"),
+        }
+    }
+
+    /// 32 bit float wav file
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let data_size = (self.samples.len() * 4) as u32;
+        let block_align = self.channels * 4;
+
+        let mut bytes = Vec::with_capacity(44 + data_size as usize);
+
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data_size).to_le_bytes());
+        bytes.extend_from_slice(b"WAVE");
+
+        bytes.extend_from_slice(b"fmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&FLOAT_FORMAT.to_le_bytes());
+        bytes.extend_from_slice(&self.channels.to_le_bytes());
+        bytes.extend_from_slice(&self.sample_rate.to_le_bytes());
+        bytes.extend_from_slice(&(self.sample_rate * block_align as u32).to_le_bytes());
+        bytes.extend_from_slice(&block_align.to_le_bytes());
+        bytes.extend_from_slice(&32u16.to_le_bytes());
+
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&data_size.to_le_bytes());
+
+        for sample in self.samples.iter() {
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+
+        bytes
+    }
+
+    pub fn write(&self, path: &str) -> VerboseResult<()> {
+        if let Err(err) = fs::write(path, self.to_bytes()) {
+            create_error!(format!("failed writing wav file ({}): {}", path, err));
+        }
+
+        Ok(())
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Interleaved samples of all channels
+    pub fn samples(&self) -> &[f32] {
+        &self.samples
+    }
+
+    /// Samples of a single channel
+    pub fn channel(&self, channel: u16) -> Vec<f32> {
+        self.samples
+            .iter()
+            .skip(channel as usize)
+            .step_by(self.channels as usize)
+            .cloned()
+            .collect()
+    }
+
+    /// Number of samples per channel
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels as usize
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.frames() as f64 / self.sample_rate as f64)
+    }
+
+    /// Highest absolute sample value
+    pub fn peak(&self) -> f32 {
+        self.samples
+            .iter()
+            .fold(0.0, |peak: f32, sample| peak.max(sample.abs()))
+    }
+
+    /// Root mean square of all samples
+    pub fn rms(&self) -> f32 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+
+        let sum: f32 = self.samples.iter().map(|sample| sample * sample).sum();
+
+        (sum / self.samples.len() as f32).sqrt()
+    }
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        bytes[offset],
+        bytes[offset + 1],
+        bytes[offset + 2],
+        bytes[offset + 3],
+    ])
+}
+
+#[test]
+fn wav_buffer_parses_sample_formats() {
+    fn wav(tag: u16, channels: u16, bits: u16, data: &[u8]) -> Vec<u8> {
+        let block_align = channels * bits / 8;
+
+        let mut bytes = Vec::new();
+
+        bytes.extend_from_slice(b"RIFF");
+        bytes.extend_from_slice(&(36 + data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(b"WAVE");
+
+        bytes.extend_from_slice(b"fmt ");
+        bytes.extend_from_slice(&16u32.to_le_bytes());
+        bytes.extend_from_slice(&tag.to_le_bytes());
+        bytes.extend_from_slice(&channels.to_le_bytes());
+        bytes.extend_from_slice(&8000u32.to_le_bytes());
+        bytes.extend_from_slice(&(8000 * block_align as u32).to_le_bytes());
+        bytes.extend_from_slice(&block_align.to_le_bytes());
+        bytes.extend_from_slice(&bits.to_le_bytes());
+
+        // unknown chunks with an odd size are padded and skipped
+        bytes.extend_from_slice(b"LIST");
+        bytes.extend_from_slice(&3u32.to_le_bytes());
+        bytes.extend_from_slice(&[1, 2, 3, 0]);
+
+        bytes.extend_from_slice(b"data");
+        bytes.extend_from_slice(&(data.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(data);
+
+        bytes
+    }
+
+    let buffer = WavBuffer::from_bytes(&wav(PCM_FORMAT, 1, 8, &[0, 128, 192])).unwrap();
+    assert_eq!(buffer.samples(), &[-1.0, 0.0, 0.5]);
+    assert_eq!(buffer.sample_rate(), 8000);
+
+    let data: Vec<u8> = [i16::MIN, 0, 16384]
+        .iter()
+        .flat_map(|sample| sample.to_le_bytes().to_vec())
+        .collect();
+    let buffer = WavBuffer::from_bytes(&wav(PCM_FORMAT, 1, 16, &data)).unwrap();
+    assert_eq!(buffer.samples(), &[-1.0, 0.0, 0.5]);
+
+    // -2^23, 2^22 and -1
+    let data = [0x00, 0x00, 0x80, 0x00, 0x00, 0x40, 0xFF, 0xFF, 0xFF];
+    let buffer = WavBuffer::from_bytes(&wav(PCM_FORMAT, 1, 24, &data)).unwrap();
+    assert_eq!(buffer.samples(), &[-1.0, 0.5, -1.0 / 8_388_608.0]);
+
+    let data: Vec<u8> = [i32::MIN, 1 << 30]
+        .iter()
+        .flat_map(|sample| sample.to_le_bytes().to_vec())
+        .collect();
+    let buffer = WavBuffer::from_bytes(&wav(PCM_FORMAT, 2, 32, &data)).unwrap();
+    assert_eq!(buffer.channels(), 2);
+    assert_eq!(buffer.frames(), 1);
+    assert_eq!(buffer.channel(1), vec![0.5]);
+
+    let data: Vec<u8> = [0.25f32, -0.75]
+        .iter()
+        .flat_map(|sample| sample.to_le_bytes().to_vec())
+        .collect();
+    let buffer = WavBuffer::from_bytes(&wav(FLOAT_FORMAT, 1, 32, &data)).unwrap();
+    assert_eq!(buffer.samples(), &[0.25, -0.75]);
+
+    assert!(WavBuffer::from_bytes(&wav(FLOAT_FORMAT, 1, 64, &[0; 8])).is_err());
+    assert!(WavBuffer::from_bytes(b"RIFF\0\0\0\0WAVE").is_err());
+    assert!(WavBuffer::from_bytes(b"not a wav file").is_err());
+}
+
+#[test]
+fn wav_buffer_round_trip() {
+    let samples = vec![0.0, 1.0, -1.0, 0.5, -0.25, 0.125];
+    let buffer = WavBuffer::new(44100, 2, samples.clone()).unwrap();
+
+    let bytes = buffer.to_bytes();
+    assert_eq!(bytes.len(), 44 + samples.len() * 4);
+
+    let read = WavBuffer::from_bytes(&bytes).unwrap();
+
+    assert_eq!(read.sample_rate(), 44100);
+    assert_eq!(read.channels(), 2);
+    assert_eq!(read.frames(), 3);
+    assert_eq!(read.samples(), samples.as_slice());
+    assert_eq!(read.peak(), 1.0);
+}
@@ -1,6 +1,8 @@
 #![allow(unused)]
 
 use super::configs::WindowConfig;
+use super::framestats::{FrameStats, FrameTiming};
+use super::gameloop::{FrameClock, FrameTime, LoopMode};
 use super::osspecific::osspecific::OsSpecific;
 use super::vulkancore::VulkanCore;
 
@@ -15,13 +17,13 @@ use std::collections::HashMap;
 use std::env::set_var;
 use std::path::Path;
 use std::rc::Rc;
-use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
 use std::time::{Duration, Instant};
 
 pub trait ContextObject {
     fn name(&self) -> &str;
 
-    fn update(&self) -> VerboseResult<()>;
+    fn update(&self, frame_time: &FrameTime) -> VerboseResult<()>;
 
     fn event(&self, event: PresentationEventType) -> VerboseResult<()>;
 }
@@ -37,6 +39,8 @@ pub struct Context {
     os_specific: OsSpecific,
 
     application_start_time: Instant,
+    frame_clock: Mutex<FrameClock>,
+    frame_stats: Mutex<FrameStats>,
 
     context_object: RwLock<Option<Arc<dyn ContextObject + Send + Sync>>>,
 
@@ -46,6 +50,24 @@ pub struct Context {
 
     // queue timer
     last_check: Mutex<Duration>,
+
+    // declared last, so that it is dropped after the render core and the presentation
+    _allocation_report: AllocationReport,
+}
+
+/// Reports the memory allocations, that are still alive when the `Context` is dropped
+struct AllocationReport {
+    device: Arc<Device>,
+}
+
+impl Drop for AllocationReport {
+    fn drop(&mut self) {
+        if cfg!(debug_assertions) {
+            if let Err(err) = self.device.report_live_allocations() {
+                println!("failed reporting live allocations: {}", err.message());
+            }
+        }
+    }
 }
 
 impl Context {
@@ -71,6 +93,9 @@ impl Context {
             PresentationBackend::OpenVR(_vri) => {
                 create_error!("OpenVR backend has no window config")
             }
+            PresentationBackend::Headless(_headless) => {
+                create_error!("Headless backend has no window config")
+            }
         }
     }
 
@@ -90,6 +115,11 @@ impl Context {
 
     pub fn run(&self) -> VerboseResult<()> {
         'running: loop {
+            // the frame starts before the events are polled, so that `limit` covers the
+            // whole frame
+            let frame_time = self.frame_clock.lock()?.tick(self.application_start_time);
+            let frame_start = Instant::now();
+
             match self.presentation.event_system().poll_events() {
                 Ok(res) => {
                     if !res {
@@ -103,15 +133,35 @@ impl Context {
                 }
             }
 
-            if let Err(err) = self.update() {
+            let events_end = Instant::now();
+
+            if let Err(err) = self.update(&frame_time) {
                 if let Some(fallback) = &self.fallback.lock()?.as_ref() {
                     (fallback)(&err.message())?;
                 }
             }
 
+            let update_end = Instant::now();
+
             if !self.render_core.next_frame()? {
                 break 'running;
             }
+
+            let render_end = Instant::now();
+            let present = self.render_core.last_present_duration();
+
+            self.frame_clock.lock()?.limit();
+
+            self.frame_stats.lock()?.record(FrameTiming {
+                frame_index: frame_time.frame_index,
+                events: events_end - frame_start,
+                update: update_end - events_end,
+                render: (render_end - update_end)
+                    .checked_sub(present)
+                    .unwrap_or_default(),
+                present,
+                total: frame_start.elapsed(),
+            });
         }
 
         self.set_context_object(None)?;
@@ -150,6 +200,22 @@ impl Context {
         self.application_start_time.elapsed()
     }
 
+    /// CPU frame timings of the latest frames
+    pub fn frame_stats(&self) -> VerboseResult<MutexGuard<'_, FrameStats>> {
+        Ok(self.frame_stats.lock()?)
+    }
+
+    pub fn loop_mode(&self) -> VerboseResult<LoopMode> {
+        Ok(self.frame_clock.lock()?.mode())
+    }
+
+    /// Takes effect with the next frame, pending fixed step time is discarded
+    pub fn set_loop_mode(&self, loop_mode: LoopMode) -> VerboseResult<()> {
+        self.frame_clock.lock()?.set_mode(loop_mode);
+
+        Ok(())
+    }
+
     pub fn controllers(&self) -> VerboseResult<RwLockReadGuard<'_, Vec<Arc<RwLock<Controller>>>>> {
         self.presentation.event_system().controllers()
     }
@@ -163,6 +229,56 @@ impl Context {
             .event_system()
             .set_active_controller(controller)
     }
+
+    /// Forwards events of all connected controllers instead of only the active one
+    pub fn set_all_controller_events(&self, all_controller_events: bool) {
+        self.presentation
+            .event_system()
+            .set_all_controller_events(all_controller_events)
+    }
+
+    pub fn controller_by_slot(
+        &self,
+        player_slot: usize,
+    ) -> VerboseResult<Option<Arc<RwLock<Controller>>>> {
+        self.presentation
+            .event_system()
+            .controller_by_slot(player_slot)
+    }
+
+    pub fn set_key_repeat(&self, key_repeat: bool) {
+        self.presentation.event_system().set_key_repeat(key_repeat)
+    }
+
+    /// Enables `TextInput` and `TextEditing` events, e.g. while a text field has focus
+    pub fn start_text_input(&self) -> VerboseResult<()> {
+        self.presentation.event_system().start_text_input()
+    }
+
+    pub fn stop_text_input(&self) -> VerboseResult<()> {
+        self.presentation.event_system().stop_text_input()
+    }
+
+    /// Writes all input events to `file_name`, they can be replayed with
+    /// `ContextBuilder::set_input_replay`
+    pub fn start_input_recording(&self, file_name: &str) -> VerboseResult<()> {
+        self.presentation.event_system().start_recording(file_name)
+    }
+
+    pub fn stop_input_recording(&self) -> VerboseResult<()> {
+        self.presentation.event_system().stop_recording()
+    }
+
+    /// false once the input replay of `ContextBuilder::set_input_replay` is finished,
+    /// `run` returns then as well
+    pub fn is_replaying_input(&self) -> VerboseResult<bool> {
+        self.presentation.event_system().is_replaying()
+    }
+
+    /// Actions and axes, that are sent as `PresentationEventType::Action`
+    pub fn input_map(&self) -> VerboseResult<RwLockWriteGuard<'_, InputMap>> {
+        self.presentation.event_system().input_map()
+    }
 }
 
 impl std::fmt::Debug for Context {
@@ -173,9 +289,9 @@ impl std::fmt::Debug for Context {
 
 impl Context {
     #[inline]
-    fn update(&self) -> VerboseResult<()> {
+    fn update(&self, frame_time: &FrameTime) -> VerboseResult<()> {
         if let Some(context_object) = self.context_object.read()?.as_ref() {
-            if let Err(err) = context_object.update() {
+            if let Err(err) = context_object.update(frame_time) {
                 return Err(err);
             }
         }
@@ -186,6 +302,11 @@ impl Context {
             event()?;
         }
 
+        #[cfg(feature = "audio")]
+        {
+            self.sound()?.update(frame_time.delta)?;
+        }
+
         let one_second = Duration::from_secs(1);
         let mut last_check = self.last_check.lock()?;
 
@@ -205,6 +326,8 @@ impl Context {
 pub struct ContextBuilder {
     #[cfg(feature = "audio")]
     volume_info: Option<VolumeInfo>,
+    #[cfg(feature = "audio")]
+    audio_backend: Option<Box<dyn AudioBackend>>,
 
     #[cfg(any(feature = "openvr", feature = "openxr"))]
     vr_mode: Option<VRMode>,
@@ -214,12 +337,19 @@ pub struct ContextBuilder {
 
     enable_backtrace: bool,
 
+    // game loop
+    loop_mode: LoopMode,
+    hitch_threshold: Option<Duration>,
+
     // app info
     app_info: ApplicationInfo,
 
     // window information
     window_create_info: WindowCreateInfo,
 
+    // headless information, replaces the window if set
+    headless_create_info: Option<HeadlessCreateInfo>,
+
     // os specifics
     os_specific_config: OsSpecificConfig,
 
@@ -235,8 +365,14 @@ pub struct ContextBuilder {
     // input settings
     enable_mouse: bool,
     enable_keyboard: bool,
+    enable_key_repeat: bool,
     enable_controller: bool,
     controller_deadzone: f32,
+    input_map: Option<InputMap>,
+
+    // input recording
+    input_recording: Option<String>,
+    input_replay: Option<(String, Option<Duration>)>,
 }
 
 impl<'a> Default for ContextBuilder {
@@ -244,6 +380,8 @@ impl<'a> Default for ContextBuilder {
         ContextBuilder {
             #[cfg(feature = "audio")]
             volume_info: None,
+            #[cfg(feature = "audio")]
+            audio_backend: None,
 
             #[cfg(any(feature = "openvr", feature = "openxr"))]
             vr_mode: None,
@@ -253,6 +391,10 @@ impl<'a> Default for ContextBuilder {
 
             enable_backtrace: false,
 
+            // game loop
+            loop_mode: LoopMode::default(),
+            hitch_threshold: None,
+
             // app info
             app_info: ApplicationInfo {
                 application_name: "not set".to_string(),
@@ -270,6 +412,8 @@ impl<'a> Default for ContextBuilder {
                 requested_display: None,
             },
 
+            headless_create_info: None,
+
             // os specifics
             os_specific_config: OsSpecificConfig::default(),
 
@@ -280,7 +424,10 @@ impl<'a> Default for ContextBuilder {
             render_core_create_info: RenderCoreCreateInfo {
                 format: VK_FORMAT_R8G8B8A8_UNORM,
                 usage: 0.into(),
-                vsync: false,
+                present_modes: Swapchain::vsync_present_modes(false),
+                image_count: 2,
+                color_spaces: Vec::new(),
+                frames_in_flight: 2,
             },
 
             // vulkan debug extension selection
@@ -289,8 +436,14 @@ impl<'a> Default for ContextBuilder {
             // input settings
             enable_mouse: false,
             enable_keyboard: false,
+            enable_key_repeat: false,
             enable_controller: false,
             controller_deadzone: 0.2,
+            input_map: None,
+
+            // input recording
+            input_recording: None,
+            input_replay: None,
         }
     }
 }
@@ -303,6 +456,14 @@ impl ContextBuilder {
         self
     }
 
+    /// Replaces OpenAL, e.g. with the `NullBackend` for machines without an audio device
+    #[cfg(feature = "audio")]
+    pub fn set_audio_backend(mut self, audio_backend: Box<dyn AudioBackend>) -> Self {
+        self.audio_backend = Some(audio_backend);
+
+        self
+    }
+
     #[cfg(any(feature = "openvr", feature = "openxr"))]
     pub fn set_vr_mode(mut self, vr_mode: VRMode) -> Self {
         self.vr_mode = Some(vr_mode);
@@ -317,6 +478,19 @@ impl ContextBuilder {
         self
     }
 
+    pub fn set_loop_mode(mut self, loop_mode: LoopMode) -> Self {
+        self.loop_mode = loop_mode;
+
+        self
+    }
+
+    /// Frames, which take longer than `threshold`, are reported on stdout
+    pub fn set_hitch_threshold(mut self, threshold: Duration) -> Self {
+        self.hitch_threshold = Some(threshold);
+
+        self
+    }
+
     pub fn set_app_info(mut self, app_info: ApplicationInfo) -> Self {
         self.app_info = app_info;
 
@@ -329,6 +503,14 @@ impl ContextBuilder {
         self
     }
 
+    /// Renders into device owned images instead of a window,
+    /// takes precedence over the window and VR settings
+    pub fn set_headless_info(mut self, headless_info: HeadlessCreateInfo) -> Self {
+        self.headless_create_info = Some(headless_info);
+
+        self
+    }
+
     pub fn set_os_specific_info(mut self, os_specific: OsSpecificConfig) -> Self {
         self.os_specific_config = os_specific;
 
@@ -359,11 +541,41 @@ impl ContextBuilder {
         usage: impl Into<VkImageUsageFlagBits>,
         vsync: bool,
     ) -> Self {
-        self.render_core_create_info = RenderCoreCreateInfo {
-            format,
-            usage: usage.into(),
-            vsync,
-        };
+        self.render_core_create_info.format = format;
+        self.render_core_create_info.usage = usage.into();
+        self.render_core_create_info.present_modes = Swapchain::vsync_present_modes(vsync);
+
+        self
+    }
+
+    /// Replaces the vsync preference of `set_render_core_info`, the first supported
+    /// present mode is used, FIFO is the fallback
+    pub fn set_present_modes(mut self, present_modes: &[VkPresentModeKHR]) -> Self {
+        self.render_core_create_info.present_modes = present_modes.to_vec();
+
+        self
+    }
+
+    /// The first color space supported by the surface is used, sRGB is the fallback
+    ///
+    /// HDR color spaces require a fitting format, e.g. `VK_FORMAT_R16G16B16A16_SFLOAT` for
+    /// `VK_COLOR_SPACE_EXTENDED_SRGB_LINEAR_EXT` (scRGB) or `VK_FORMAT_A2B10G10R10_UNORM_PACK32`
+    /// for `VK_COLOR_SPACE_HDR10_ST2084_EXT` (HDR10 PQ)
+    pub fn set_color_spaces(mut self, color_spaces: &[VkColorSpaceKHR]) -> Self {
+        self.render_core_create_info.color_spaces = color_spaces.to_vec();
+
+        self
+    }
+
+    pub fn set_swapchain_image_count(mut self, image_count: u32) -> Self {
+        self.render_core_create_info.image_count = image_count;
+
+        self
+    }
+
+    /// Number of frames, the CPU may record ahead of the GPU, at least 1
+    pub fn set_frames_in_flight(mut self, frames_in_flight: usize) -> Self {
+        self.render_core_create_info.frames_in_flight = frames_in_flight.max(1);
 
         self
     }
@@ -392,6 +604,13 @@ impl ContextBuilder {
         self
     }
 
+    /// Sends repeated key down events while a key is held
+    pub fn enable_key_repeat(mut self) -> Self {
+        self.enable_key_repeat = true;
+
+        self
+    }
+
     pub fn enable_controller(mut self) -> Self {
         self.enable_controller = true;
 
@@ -404,7 +623,32 @@ impl ContextBuilder {
         self
     }
 
-    pub fn build(self) -> VerboseResult<Arc<Context>> {
+    /// Actions and axes, e.g. loaded with `InputMap::load`
+    pub fn set_input_map(mut self, input_map: InputMap) -> Self {
+        self.input_map = Some(input_map);
+
+        self
+    }
+
+    /// Records all input events of the run to `file_name`
+    pub fn set_input_recording(mut self, file_name: &str) -> Self {
+        self.input_recording = Some(file_name.to_string());
+
+        self
+    }
+
+    /// Replays a recording instead of using real input, the context stops once the
+    /// replay is finished
+    ///
+    /// With `fixed_frame_time` every frame advances by exactly that time, which makes the
+    /// run independent of the actual frame rate
+    pub fn set_input_replay(mut self, file_name: &str, fixed_frame_time: Option<Duration>) -> Self {
+        self.input_replay = Some((file_name.to_string(), fixed_frame_time));
+
+        self
+    }
+
+    pub fn build(mut self) -> VerboseResult<Arc<Context>> {
         if self.enable_backtrace {
             // set environment variable for Rust-debug-trace
             set_var("RUST_BACKTRACE", "1");
@@ -413,10 +657,14 @@ impl ContextBuilder {
         #[cfg(feature = "openxr")]
         self.use_openxr_json();
 
-        let vr_mode = self.get_vr_mode();
+        let presentation = match &self.headless_create_info {
+            Some(headless_create_info) => PresentationCore::headless(headless_create_info)?,
+            None => {
+                let vr_mode = self.get_vr_mode();
 
-        let presentation =
-            PresentationCore::new(vr_mode, &self.window_create_info, self.app_info.clone())?;
+                PresentationCore::new(vr_mode, &self.window_create_info, self.app_info.clone())?
+            }
+        };
 
         // vulkan core objects (VkInstance, VkDevice, ...)
         let core = VulkanCore::new(
@@ -432,9 +680,11 @@ impl ContextBuilder {
             &presentation,
             core.device(),
             core.queue(),
-            self.render_core_create_info,
+            self.render_core_create_info.clone(),
         )?;
 
+        let device = core.device().clone();
+
         let context = Arc::new(Context {
             core,
             presentation,
@@ -446,6 +696,8 @@ impl ContextBuilder {
             os_specific,
 
             application_start_time: Instant::now(),
+            frame_clock: Mutex::new(FrameClock::new(self.loop_mode)),
+            frame_stats: Mutex::new(FrameStats::new(self.hitch_threshold)),
 
             context_object: RwLock::new(None),
 
@@ -454,6 +706,8 @@ impl ContextBuilder {
             push_events: Mutex::new(Vec::new()),
 
             last_check: Mutex::new(Duration::from_secs(0)),
+
+            _allocation_report: AllocationReport { device },
         });
 
         let weak_context = Arc::downgrade(&context);
@@ -486,10 +740,41 @@ impl ContextBuilder {
             context.presentation.event_system().enable_keyboard()?;
         }
 
+        context
+            .presentation
+            .event_system()
+            .set_key_repeat(self.enable_key_repeat);
+
         if self.enable_controller {
             context.presentation.event_system().enable_controller()?;
         }
 
+        if let Some(input_map) = self.input_map {
+            context
+                .presentation
+                .event_system()
+                .set_input_map(input_map)?;
+        }
+
+        if let Some(file_name) = &self.input_recording {
+            context
+                .presentation
+                .event_system()
+                .start_recording(file_name)?;
+        }
+
+        if let Some((file_name, fixed_frame_time)) = &self.input_replay {
+            context
+                .presentation
+                .event_system()
+                .start_replay(file_name)?;
+
+            context
+                .frame_clock
+                .lock()?
+                .set_fixed_frame_time(*fixed_frame_time);
+        }
+
         Ok(context)
     }
 
@@ -540,10 +825,15 @@ impl ContextBuilder {
     }
 
     #[cfg(feature = "audio")]
-    fn create_sound_handler(&self) -> VerboseResult<SoundHandler> {
-        match self.volume_info {
-            Some(volume_info) => SoundHandler::new(volume_info),
+    fn create_sound_handler(&mut self) -> VerboseResult<SoundHandler> {
+        let volume_info = match self.volume_info {
+            Some(volume_info) => volume_info,
             None => create_error!("No volume info present, consider disabling 'audio' feature"),
+        };
+
+        match self.audio_backend.take() {
+            Some(audio_backend) => SoundHandler::new(volume_info, audio_backend),
+            None => SoundHandler::with_default_backend(volume_info),
         }
     }
 }
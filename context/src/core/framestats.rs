@@ -0,0 +1,253 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+const DEFAULT_HISTORY_SIZE: usize = 300;
+
+/// CPU time of a single frame, split into the phases of `Context::run`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameTiming {
+    pub frame_index: u64,
+
+    /// polling and dispatching of events
+    pub events: Duration,
+
+    /// `ContextObject::update` and pushed events
+    pub update: Duration,
+
+    /// `RenderCore::next_frame` including the fence wait, without presenting
+    pub render: Duration,
+
+    /// presenting the image, as reported by the render core
+    pub present: Duration,
+
+    /// complete frame, including the wait of a frame cap
+    pub total: Duration,
+}
+
+/// Aggregated frame times over the recorded history
+#[derive(Debug, Clone, Copy)]
+pub struct FrameTimeSummary {
+    pub frame_count: usize,
+
+    pub average: Duration,
+    pub min: Duration,
+    pub max: Duration,
+
+    /// average of the slowest 1% of all frames
+    pub one_percent_low: Duration,
+}
+
+impl FrameTimeSummary {
+    pub fn average_fps(&self) -> f32 {
+        Self::fps(self.average)
+    }
+
+    pub fn one_percent_low_fps(&self) -> f32 {
+        Self::fps(self.one_percent_low)
+    }
+
+    fn fps(frame_time: Duration) -> f32 {
+        if frame_time == Duration::default() {
+            0.0
+        } else {
+            1.0 / frame_time.as_secs_f32()
+        }
+    }
+}
+
+/// Records the timings of the latest frames
+pub struct FrameStats {
+    history: VecDeque<FrameTiming>,
+    history_size: usize,
+
+    frame_count: u64,
+
+    hitch_threshold: Option<Duration>,
+    hitch_count: u64,
+}
+
+impl FrameStats {
+    pub(crate) fn new(hitch_threshold: Option<Duration>) -> Self {
+        FrameStats {
+            history: VecDeque::with_capacity(DEFAULT_HISTORY_SIZE),
+            history_size: DEFAULT_HISTORY_SIZE,
+
+            frame_count: 0,
+
+            hitch_threshold,
+            hitch_count: 0,
+        }
+    }
+
+    pub(crate) fn record(&mut self, timing: FrameTiming) {
+        if let Some(threshold) = self.hitch_threshold {
+            if timing.total > threshold {
+                self.hitch_count += 1;
+
+                println!(
+                    "Hitch in frame {}: {:.2} ms (events: {:.2} ms, update: {:.2} ms, render: {:.2} ms, present: {:.2} ms)",
+                    timing.frame_index,
+                    Self::millis(timing.total),
+                    Self::millis(timing.events),
+                    Self::millis(timing.update),
+                    Self::millis(timing.render),
+                    Self::millis(timing.present),
+                );
+            }
+        }
+
+        if self.history.len() == self.history_size {
+            self.history.pop_front();
+        }
+
+        self.history.push_back(timing);
+        self.frame_count += 1;
+    }
+
+    /// Number of frames, that were recorded since the start
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// Number of frames, that exceeded the hitch threshold
+    pub fn hitch_count(&self) -> u64 {
+        self.hitch_count
+    }
+
+    pub fn hitch_threshold(&self) -> Option<Duration> {
+        self.hitch_threshold
+    }
+
+    /// Frames, which take longer than `threshold`, are reported on stdout
+    pub fn set_hitch_threshold(&mut self, threshold: Option<Duration>) {
+        self.hitch_threshold = threshold;
+    }
+
+    pub fn history_size(&self) -> usize {
+        self.history_size
+    }
+
+    /// Drops the oldest frames, if the history shrinks
+    pub fn set_history_size(&mut self, history_size: usize) {
+        self.history_size = history_size.max(1);
+
+        while self.history.len() > self.history_size {
+            self.history.pop_front();
+        }
+    }
+
+    /// Recorded frames, from oldest to newest
+    pub fn history(&self) -> impl Iterator<Item = &FrameTiming> {
+        self.history.iter()
+    }
+
+    pub fn last_frame(&self) -> Option<&FrameTiming> {
+        self.history.back()
+    }
+
+    /// `None` if no frame has been recorded yet
+    pub fn summary(&self) -> Option<FrameTimeSummary> {
+        if self.history.is_empty() {
+            return None;
+        }
+
+        let mut frame_times: Vec<Duration> =
+            self.history.iter().map(|timing| timing.total).collect();
+        frame_times.sort();
+
+        let frame_count = frame_times.len();
+        let total: Duration = frame_times.iter().sum();
+
+        // at least one frame belongs to the slowest 1%
+        let low_count = (frame_count / 100).max(1);
+        let low_total: Duration = frame_times[(frame_count - low_count)..].iter().sum();
+
+        Some(FrameTimeSummary {
+            frame_count,
+
+            average: total / frame_count as u32,
+            min: frame_times[0],
+            max: frame_times[frame_count - 1],
+
+            one_percent_low: low_total / low_count as u32,
+        })
+    }
+
+    fn millis(duration: Duration) -> f64 {
+        duration.as_secs_f64() * 1000.0
+    }
+}
+
+#[test]
+fn frame_stats_summary_and_hitches() {
+    let mut frame_stats = FrameStats::new(Some(Duration::from_millis(30)));
+    assert!(frame_stats.summary().is_none());
+
+    // 198 frames of 10 ms and two hitches of 50 and 40 ms
+    for frame_index in 0..200 {
+        let total = match frame_index {
+            50 => Duration::from_millis(50),
+            150 => Duration::from_millis(40),
+            _ => Duration::from_millis(10),
+        };
+
+        frame_stats.record(FrameTiming {
+            frame_index,
+            total,
+            ..FrameTiming::default()
+        });
+    }
+
+    assert_eq!(frame_stats.frame_count(), 200);
+    assert_eq!(frame_stats.hitch_count(), 2);
+
+    let summary = frame_stats.summary().unwrap();
+
+    assert_eq!(summary.frame_count, 200);
+    assert_eq!(summary.min, Duration::from_millis(10));
+    assert_eq!(summary.max, Duration::from_millis(50));
+    assert_eq!(summary.average, Duration::from_micros(10_350));
+    // the slowest 1% of 200 frames are the two hitches
+    assert_eq!(summary.one_percent_low, Duration::from_millis(45));
+    assert!((summary.average_fps() - 96.62).abs() < 0.01);
+}
+
+#[test]
+fn frame_stats_history_size() {
+    let mut frame_stats = FrameStats::new(None);
+
+    for frame_index in 0..10 {
+        frame_stats.record(FrameTiming {
+            frame_index,
+            total: Duration::from_millis(frame_index),
+            ..FrameTiming::default()
+        });
+    }
+
+    frame_stats.set_history_size(4);
+    frame_stats.record(FrameTiming {
+        frame_index: 10,
+        ..FrameTiming::default()
+    });
+
+    let frames: Vec<u64> = frame_stats
+        .history()
+        .map(|timing| timing.frame_index)
+        .collect();
+
+    assert_eq!(frames, vec![7, 8, 9, 10]);
+    assert_eq!(frame_stats.last_frame().unwrap().frame_index, 10);
+    assert_eq!(frame_stats.frame_count(), 11);
+    assert_eq!(frame_stats.hitch_count(), 0);
+
+    // less than 100 frames still have a slowest frame
+    assert_eq!(
+        frame_stats.summary().unwrap().one_percent_low,
+        Duration::from_millis(9)
+    );
+
+    // the history can't be empty
+    frame_stats.set_history_size(0);
+    assert_eq!(frame_stats.history_size(), 1);
+    assert_eq!(frame_stats.history().count(), 1);
+}
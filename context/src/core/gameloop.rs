@@ -0,0 +1,276 @@
+use presentation::prelude::*;
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+// remaining time, that is spun instead of slept, since sleeping is too coarse
+const SPIN_THRESHOLD: Duration = Duration::from_millis(2);
+
+/// Defines how `Context::run` advances time and paces frames
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum LoopMode {
+    /// Every frame is updated with the time since the previous frame,
+    /// frames are rendered as fast as possible (or as vsync allows)
+    #[default]
+    Variable,
+
+    /// The simulation advances in steps of `step`, independent of the frame rate
+    ///
+    /// `ContextObject::update` is called once per frame with the number of steps, that
+    /// are due. At most `max_steps` are taken per frame, the remaining time is dropped.
+    FixedStep { step: Duration, max_steps: u32 },
+
+    /// Like `Variable`, but a frame takes at least `frame_time`
+    FrameCap { frame_time: Duration },
+}
+
+impl LoopMode {
+    /// `updates_per_second` has to be greater than 0
+    pub fn fixed_rate(updates_per_second: f64, max_steps: u32) -> VerboseResult<Self> {
+        Ok(LoopMode::FixedStep {
+            step: Self::interval(updates_per_second, "fixed update rate")?,
+            max_steps,
+        })
+    }
+
+    /// `frames_per_second` has to be greater than 0
+    pub fn frame_cap(frames_per_second: f64) -> VerboseResult<Self> {
+        Ok(LoopMode::FrameCap {
+            frame_time: Self::interval(frames_per_second, "frame cap")?,
+        })
+    }
+
+    fn interval(rate: f64, name: &str) -> VerboseResult<Duration> {
+        // also rejects NaN and rates, whose interval is too short to be represented
+        if !(rate > 0.0 && rate.is_finite()) {
+            create_error!(format!("{} ({}) has to be greater than 0", name, rate));
+        }
+
+        let interval = Duration::from_secs_f64(1.0 / rate);
+
+        if interval == Duration::default() {
+            create_error!(format!("{} ({}) is too high", name, rate));
+        }
+
+        Ok(interval)
+    }
+}
+
+/// Timing information of the current frame, passed to `ContextObject::update`
+#[derive(Debug, Clone, Copy)]
+pub struct FrameTime {
+    /// running number of the frame, starting at 0
+    pub frame_index: u64,
+
+    /// time since the previous frame
+    pub delta: Duration,
+
+    /// time since the start of the application
+    pub total: Duration,
+
+    /// number of fixed steps, that have to be simulated this frame
+    ///
+    /// only used in `LoopMode::FixedStep`, otherwise 0
+    pub fixed_steps: u32,
+
+    /// size of a fixed step, `Duration::default()` if not in `LoopMode::FixedStep`
+    pub fixed_delta: Duration,
+
+    /// progress into the next fixed step in [0, 1), can be used to interpolate
+    /// between the last two simulation states while rendering
+    pub alpha: f32,
+}
+
+impl FrameTime {
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+}
+
+pub(crate) struct FrameClock {
+    mode: LoopMode,
+
+    frame_index: u64,
+    frame_start: Option<Instant>,
+    accumulator: Duration,
+
+    // replaces the measured frame time, e.g. for deterministic replays
+    fixed_frame_time: Option<Duration>,
+    fixed_total: Duration,
+}
+
+impl FrameClock {
+    pub(crate) fn new(mode: LoopMode) -> Self {
+        FrameClock {
+            mode,
+
+            frame_index: 0,
+            frame_start: None,
+            accumulator: Duration::default(),
+
+            fixed_frame_time: None,
+            fixed_total: Duration::default(),
+        }
+    }
+
+    pub(crate) fn mode(&self) -> LoopMode {
+        self.mode
+    }
+
+    pub(crate) fn set_mode(&mut self, mode: LoopMode) {
+        self.mode = mode;
+        self.accumulator = Duration::default();
+    }
+
+    pub(crate) fn set_fixed_frame_time(&mut self, fixed_frame_time: Option<Duration>) {
+        self.fixed_frame_time = fixed_frame_time;
+        self.fixed_total = Duration::default();
+    }
+
+    /// Starts a new frame
+    pub(crate) fn tick(&mut self, application_start: Instant) -> FrameTime {
+        let now = Instant::now();
+
+        let delta = match (self.frame_start, self.fixed_frame_time) {
+            (Some(_), Some(fixed_frame_time)) => fixed_frame_time,
+            (Some(frame_start), None) => now - frame_start,
+            (None, _) => Duration::default(),
+        };
+
+        self.frame_start = Some(now);
+        self.fixed_total += delta;
+
+        let total = match self.fixed_frame_time {
+            Some(_) => self.fixed_total,
+            None => now - application_start,
+        };
+
+        let mut frame_time = FrameTime {
+            frame_index: self.frame_index,
+            delta,
+            total,
+            fixed_steps: 0,
+            fixed_delta: Duration::default(),
+            alpha: 0.0,
+        };
+
+        self.frame_index += 1;
+
+        if let LoopMode::FixedStep { step, max_steps } = self.mode {
+            // a step of zero would never advance the simulation
+            if step == Duration::default() {
+                return frame_time;
+            }
+
+            self.accumulator += delta;
+
+            let mut steps = 0;
+
+            while self.accumulator >= step && steps < max_steps {
+                self.accumulator -= step;
+                steps += 1;
+            }
+
+            // we can't catch up, so drop the time instead of spiraling
+            if self.accumulator >= step {
+                self.accumulator =
+                    Duration::from_nanos((self.accumulator.as_nanos() % step.as_nanos()) as u64);
+            }
+
+            frame_time.fixed_steps = steps;
+            frame_time.fixed_delta = step;
+            frame_time.alpha = self.accumulator.as_secs_f32() / step.as_secs_f32();
+        }
+
+        frame_time
+    }
+
+    /// Waits until the minimal frame time is reached, only has an effect in
+    /// `LoopMode::FrameCap`
+    pub(crate) fn limit(&self) {
+        let (frame_time, frame_start) = match (self.mode, self.frame_start) {
+            (LoopMode::FrameCap { frame_time }, Some(frame_start)) => (frame_time, frame_start),
+            _ => return,
+        };
+
+        let deadline = frame_start + frame_time;
+        let now = Instant::now();
+
+        if now >= deadline {
+            return;
+        }
+
+        let remaining = deadline - now;
+
+        if remaining > SPIN_THRESHOLD {
+            thread::sleep(remaining - SPIN_THRESHOLD);
+        }
+
+        while Instant::now() < deadline {
+            thread::yield_now();
+        }
+    }
+}
+
+#[test]
+fn loop_mode_rejects_invalid_rates() {
+    assert!(LoopMode::fixed_rate(0.0, 5).is_err());
+    assert!(LoopMode::fixed_rate(-60.0, 5).is_err());
+    assert!(LoopMode::fixed_rate(f64::NAN, 5).is_err());
+    assert!(LoopMode::frame_cap(0.0).is_err());
+    assert!(LoopMode::frame_cap(f64::INFINITY).is_err());
+
+    assert_eq!(
+        LoopMode::frame_cap(50.0).unwrap(),
+        LoopMode::FrameCap {
+            frame_time: Duration::from_millis(20)
+        }
+    );
+}
+
+#[test]
+fn loop_mode_fixed_step_timing() {
+    let start = Instant::now();
+
+    let mut clock = FrameClock::new(LoopMode::fixed_rate(100.0, 3).unwrap());
+    clock.set_fixed_frame_time(Some(Duration::from_millis(25)));
+
+    // the first frame has no previous frame to measure against
+    let frame_time = clock.tick(start);
+    assert_eq!(frame_time.frame_index, 0);
+    assert_eq!(frame_time.fixed_steps, 0);
+
+    // 25 ms are 2 steps of 10 ms, 5 ms remain
+    let frame_time = clock.tick(start);
+    assert_eq!(frame_time.fixed_steps, 2);
+    assert_eq!(frame_time.fixed_delta, Duration::from_millis(10));
+    assert!((frame_time.alpha - 0.5).abs() < 1e-3);
+
+    // 5 ms + 25 ms are 3 steps
+    let frame_time = clock.tick(start);
+    assert_eq!(frame_time.fixed_steps, 3);
+    assert!(frame_time.alpha.abs() < 1e-3);
+    assert_eq!(frame_time.total, Duration::from_millis(50));
+
+    // a hitch is limited to max_steps and the time, that can't be caught up, is dropped
+    clock.set_fixed_frame_time(Some(Duration::from_millis(95)));
+    let frame_time = clock.tick(start);
+    assert_eq!(frame_time.fixed_steps, 3);
+    assert!((frame_time.alpha - 0.5).abs() < 1e-3);
+}
+
+#[test]
+fn loop_mode_variable_has_no_fixed_steps() {
+    let start = Instant::now();
+
+    let mut clock = FrameClock::new(LoopMode::Variable);
+    clock.set_fixed_frame_time(Some(Duration::from_millis(16)));
+
+    clock.tick(start);
+    let frame_time = clock.tick(start);
+
+    assert_eq!(frame_time.frame_index, 1);
+    assert_eq!(frame_time.delta, Duration::from_millis(16));
+    assert_eq!(frame_time.fixed_steps, 0);
+    assert_eq!(frame_time.fixed_delta, Duration::default());
+}
@@ -0,0 +1,436 @@
+use presentation::prelude::*;
+
+use super::audiobackend::Reverb;
+use super::soundhandler::VolumeInfo;
+
+use std::time::Duration;
+
+/// Root of all buses, sounds end up here
+pub const MASTER_BUS: &str = "master";
+
+/// Bus of all music, child of the master bus
+pub const MUSIC_BUS: &str = "music";
+
+/// Shape of a volume ramp
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum FadeCurve {
+    Linear,
+    /// starts and ends slowly
+    #[default]
+    SmoothStep,
+    /// sine shaped, keeps the perceived loudness of a crossfade constant
+    EqualPower,
+}
+
+impl FadeCurve {
+    // maps the progress of a ramp in [0, 1] to the progress of its value
+    fn apply(self, progress: f32, rising: bool) -> f32 {
+        match self {
+            FadeCurve::Linear => progress,
+            FadeCurve::SmoothStep => progress * progress * (3.0 - 2.0 * progress),
+            FadeCurve::EqualPower => {
+                let angle = progress * std::f32::consts::FRAC_PI_2;
+
+                if rising {
+                    angle.sin()
+                } else {
+                    1.0 - angle.cos()
+                }
+            }
+        }
+    }
+}
+
+/// Volume, that moves smoothly to a target over a given time
+///
+/// Times are taken from the clock of the `SoundHandler`, that `SoundHandler::update` advances.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct VolumeRamp {
+    start: f32,
+    target: f32,
+    start_time: Duration,
+    duration: Duration,
+    curve: FadeCurve,
+}
+
+impl VolumeRamp {
+    pub(crate) fn new(volume: f32) -> Self {
+        VolumeRamp {
+            start: volume,
+            target: volume,
+            start_time: Duration::default(),
+            duration: Duration::default(),
+            curve: FadeCurve::default(),
+        }
+    }
+
+    pub(crate) fn value(&self, now: Duration) -> f32 {
+        let progress = self
+            .curve
+            .apply(self.progress(now), self.target >= self.start);
+
+        self.start + (self.target - self.start) * progress
+    }
+
+    pub(crate) fn target(&self) -> f32 {
+        self.target
+    }
+
+    /// Starts a new ramp from the current value
+    pub(crate) fn ramp_to(&mut self, target: f32, duration: Duration, now: Duration) {
+        self.ramp_with(target, duration, FadeCurve::default(), now);
+    }
+
+    pub(crate) fn ramp_with(
+        &mut self,
+        target: f32,
+        duration: Duration,
+        curve: FadeCurve,
+        now: Duration,
+    ) {
+        self.start = self.value(now);
+        self.target = target;
+        self.start_time = now;
+        self.duration = duration;
+        self.curve = curve;
+    }
+
+    pub(crate) fn is_active(&self, now: Duration) -> bool {
+        self.progress(now) < 1.0
+    }
+
+    fn progress(&self, now: Duration) -> f32 {
+        if self.duration == Duration::default() {
+            return 1.0;
+        }
+
+        let elapsed = now.checked_sub(self.start_time).unwrap_or_default();
+
+        (elapsed.as_secs_f32() / self.duration.as_secs_f32()).min(1.0)
+    }
+}
+
+/// Lowers the volume of `bus` while a sound on `trigger` (or one of its children) is playing,
+/// e.g. music under dialogue
+#[derive(Debug, Clone)]
+pub struct Ducking {
+    pub bus: String,
+    pub trigger: String,
+
+    /// volume factor of `bus` while ducked
+    pub volume: f32,
+    pub attack: Duration,
+    pub release: Duration,
+}
+
+pub struct MixerBus {
+    name: String,
+    parent: Option<String>,
+
+    volume: VolumeRamp,
+    // product of all duckings, that target this bus
+    ducking: VolumeRamp,
+
+    muted: bool,
+    solo: bool,
+
+    low_pass: Option<f32>,
+    high_pass: Option<f32>,
+    reverb: Option<Reverb>,
+}
+
+impl MixerBus {
+    fn new(name: &str, parent: Option<&str>, volume: f32) -> Self {
+        MixerBus {
+            name: name.to_string(),
+            parent: parent.map(|parent| parent.to_string()),
+
+            volume: VolumeRamp::new(volume),
+            ducking: VolumeRamp::new(1.0),
+
+            muted: false,
+            solo: false,
+
+            low_pass: None,
+            high_pass: None,
+            reverb: None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// `None` for the master bus
+    pub fn parent(&self) -> Option<&str> {
+        self.parent.as_deref()
+    }
+
+    /// Target volume, if a ramp is in progress
+    pub fn volume(&self) -> f32 {
+        self.volume.target()
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    pub fn is_solo(&self) -> bool {
+        self.solo
+    }
+
+    /// Cutoff frequency in Hz, only rendered by backends, that support filters
+    /// (see `SoundHandler::supports_filters`)
+    pub fn low_pass(&self) -> Option<f32> {
+        self.low_pass
+    }
+
+    /// Cutoff frequency in Hz, only rendered by backends, that support filters
+    pub fn high_pass(&self) -> Option<f32> {
+        self.high_pass
+    }
+
+    pub fn reverb(&self) -> &Option<Reverb> {
+        &self.reverb
+    }
+}
+
+/// Hierarchy of buses, every bus except master has a parent
+pub(crate) struct Mixer {
+    buses: Vec<MixerBus>,
+    duckings: Vec<Ducking>,
+
+    default_volume: f32,
+}
+
+impl Mixer {
+    pub(crate) fn new(volume_info: &VolumeInfo) -> Self {
+        Mixer {
+            buses: vec![
+                MixerBus::new(MASTER_BUS, None, volume_info.master_volume),
+                MixerBus::new(MUSIC_BUS, Some(MASTER_BUS), volume_info.music_volume),
+            ],
+            duckings: Vec::new(),
+
+            default_volume: volume_info.other_volume,
+        }
+    }
+
+    pub(crate) fn create_bus(&mut self, name: &str, parent: &str) -> VerboseResult<()> {
+        if self.bus(name).is_some() {
+            create_error!(format!("mixer bus ({}) already exists", name));
+        }
+
+        if self.bus(parent).is_none() {
+            create_error!(format!("parent mixer bus ({}) does not exist", parent));
+        }
+
+        self.buses
+            .push(MixerBus::new(name, Some(parent), self.default_volume));
+
+        Ok(())
+    }
+
+    /// Buses for sound types, that weren't created explicitly, are children of master
+    pub(crate) fn ensure_bus(&mut self, name: &str) {
+        if self.bus(name).is_none() {
+            self.buses
+                .push(MixerBus::new(name, Some(MASTER_BUS), self.default_volume));
+        }
+    }
+
+    pub(crate) fn bus(&self, name: &str) -> Option<&MixerBus> {
+        self.buses.iter().find(|bus| bus.name == name)
+    }
+
+    pub(crate) fn bus_mut(&mut self, name: &str) -> VerboseResult<&mut MixerBus> {
+        match self.buses.iter_mut().find(|bus| bus.name == name) {
+            Some(bus) => Ok(bus),
+            None => create_error!(format!("mixer bus ({}) does not exist", name)),
+        }
+    }
+
+    pub(crate) fn buses(&self) -> &[MixerBus] {
+        &self.buses
+    }
+
+    pub(crate) fn set_volume(
+        &mut self,
+        name: &str,
+        volume: f32,
+        duration: Duration,
+        now: Duration,
+    ) -> VerboseResult<()> {
+        self.bus_mut(name)?.volume.ramp_to(volume, duration, now);
+
+        Ok(())
+    }
+
+    pub(crate) fn set_muted(&mut self, name: &str, muted: bool) -> VerboseResult<()> {
+        self.bus_mut(name)?.muted = muted;
+
+        Ok(())
+    }
+
+    pub(crate) fn set_solo(&mut self, name: &str, solo: bool) -> VerboseResult<()> {
+        self.bus_mut(name)?.solo = solo;
+
+        Ok(())
+    }
+
+    pub(crate) fn set_low_pass(&mut self, name: &str, cutoff: Option<f32>) -> VerboseResult<()> {
+        self.bus_mut(name)?.low_pass = cutoff;
+
+        Ok(())
+    }
+
+    pub(crate) fn set_high_pass(&mut self, name: &str, cutoff: Option<f32>) -> VerboseResult<()> {
+        self.bus_mut(name)?.high_pass = cutoff;
+
+        Ok(())
+    }
+
+    pub(crate) fn set_reverb(&mut self, name: &str, reverb: Option<Reverb>) -> VerboseResult<()> {
+        self.bus_mut(name)?.reverb = reverb;
+
+        Ok(())
+    }
+
+    pub(crate) fn add_ducking(&mut self, ducking: Ducking) -> VerboseResult<()> {
+        if self.bus(&ducking.bus).is_none() {
+            create_error!(format!("mixer bus ({}) does not exist", ducking.bus));
+        }
+
+        if self.bus(&ducking.trigger).is_none() {
+            create_error!(format!("mixer bus ({}) does not exist", ducking.trigger));
+        }
+
+        self.duckings
+            .retain(|d| d.bus != ducking.bus || d.trigger != ducking.trigger);
+        self.duckings.push(ducking);
+
+        Ok(())
+    }
+
+    pub(crate) fn remove_ducking(&mut self, bus: &str, trigger: &str) {
+        self.duckings
+            .retain(|d| d.bus != bus || d.trigger != trigger);
+    }
+
+    /// Starts ducking ramps, `is_playing` tells if a sound is playing on a bus
+    pub(crate) fn update_ducking(&mut self, is_playing: impl Fn(&str) -> bool, now: Duration) {
+        for bus_index in 0..self.buses.len() {
+            let mut target = 1.0;
+            let mut duration = None;
+
+            for ducking in self.duckings.iter() {
+                if ducking.bus != self.buses[bus_index].name {
+                    continue;
+                }
+
+                let active = self
+                    .buses
+                    .iter()
+                    .filter(|bus| self.is_in_chain(&bus.name, &ducking.trigger))
+                    .any(|bus| is_playing(&bus.name));
+
+                if active {
+                    target *= ducking.volume;
+                    duration = Some(ducking.attack);
+                } else if duration.is_none() {
+                    duration = Some(ducking.release);
+                }
+            }
+
+            let bus = &mut self.buses[bus_index];
+
+            if let Some(duration) = duration {
+                if bus.ducking.target() != target {
+                    bus.ducking.ramp_to(target, duration, now);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn is_ramping(&self, now: Duration) -> bool {
+        self.buses
+            .iter()
+            .any(|bus| bus.volume.is_active(now) || bus.ducking.is_active(now))
+    }
+
+    /// Effective volume of a sound on the bus, including all parents, mute and solo
+    pub(crate) fn gain(&self, name: &str, now: Duration) -> f32 {
+        let chain = self.chain(name);
+
+        if chain.iter().any(|bus| bus.muted) {
+            return 0.0;
+        }
+
+        // while any bus is soloed, only sounds below a soloed bus are audible
+        if self.buses.iter().any(|bus| bus.solo) && !chain.iter().any(|bus| bus.solo) {
+            return 0.0;
+        }
+
+        chain
+            .iter()
+            .map(|bus| bus.volume.value(now) * bus.ducking.value(now))
+            .product()
+    }
+
+    /// Reverb of the nearest bus in the chain, that has one
+    pub(crate) fn reverb(&self, name: &str) -> &Option<Reverb> {
+        let chain = self.chain(name);
+
+        // the chain always ends at master
+        match chain.iter().find(|bus| bus.reverb.is_some()) {
+            Some(bus) => &bus.reverb,
+            None => &chain[chain.len() - 1].reverb,
+        }
+    }
+
+    /// Lowest low pass cutoff of the bus and its parents
+    pub(crate) fn low_pass(&self, name: &str) -> Option<f32> {
+        self.chain(name)
+            .iter()
+            .filter_map(|bus| bus.low_pass)
+            .fold(None, |lowest, cutoff| match lowest {
+                Some(lowest) if lowest < cutoff => Some(lowest),
+                _ => Some(cutoff),
+            })
+    }
+
+    /// Highest high pass cutoff of the bus and its parents
+    pub(crate) fn high_pass(&self, name: &str) -> Option<f32> {
+        self.chain(name)
+            .iter()
+            .filter_map(|bus| bus.high_pass)
+            .fold(None, |highest, cutoff| match highest {
+                Some(highest) if highest > cutoff => Some(highest),
+                _ => Some(cutoff),
+            })
+    }
+
+    /// true, if `name` is `ancestor` or one of its children
+    pub(crate) fn is_in_chain(&self, name: &str, ancestor: &str) -> bool {
+        self.chain(name).iter().any(|bus| bus.name == ancestor)
+    }
+
+    // the bus itself followed by all of its parents, unknown buses resolve to master
+    fn chain(&self, name: &str) -> Vec<&MixerBus> {
+        let mut chain = Vec::new();
+        let mut current = self.bus(name).or_else(|| self.bus(MASTER_BUS));
+
+        while let Some(bus) = current {
+            // guards against cycles
+            if chain.len() > self.buses.len() {
+                break;
+            }
+
+            chain.push(bus);
+
+            current = bus.parent.as_ref().and_then(|parent| self.bus(parent));
+        }
+
+        chain
+    }
+}
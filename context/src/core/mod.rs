@@ -2,9 +2,15 @@
 pub mod osspecific;
 mod vulkancore;
 
-#[cfg(feature = "audio")]
+// only the OpenAL backend needs the `audio` feature
+pub mod audiobackend;
+pub mod mixer;
+pub mod musicplayer;
 pub mod soundhandler;
+pub mod voicemanager;
 
 pub mod context;
 
 pub mod configs;
+pub mod framestats;
+pub mod gameloop;
@@ -0,0 +1,521 @@
+use presentation::prelude::*;
+
+use super::mixer::{FadeCurve, VolumeRamp};
+use super::soundhandler::Music;
+
+use rand::seq::SliceRandom;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RepeatMode {
+    Off,
+    /// repeats the current track
+    One,
+    /// starts the playlist again after the last track
+    All,
+}
+
+/// How the player moves from one track to the next
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MusicTransition {
+    /// silence between two tracks
+    Gap(Duration),
+
+    /// the next track starts in the frame, in which the current one ends
+    Gapless,
+
+    /// the next track fades in, while the current one fades out
+    Crossfade {
+        duration: Duration,
+        curve: FadeCurve,
+    },
+}
+
+struct MusicVoice {
+    music: Arc<Music>,
+    fade: VolumeRamp,
+
+    // playback time before the last start or resume
+    elapsed: Duration,
+    playing_since: Option<Duration>,
+
+    // the voice was replaced by the next track, it is dropped once it is silent
+    retired: bool,
+}
+
+impl MusicVoice {
+    fn position(&self, now: Duration) -> Duration {
+        match self.playing_since {
+            Some(playing_since) => {
+                self.elapsed + now.checked_sub(playing_since).unwrap_or_default()
+            }
+            None => self.elapsed,
+        }
+    }
+
+    fn remaining(&self, now: Duration) -> Duration {
+        self.music
+            .duration()
+            .checked_sub(self.position(now))
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PlayerState {
+    Stopped,
+    Playing,
+    Pausing,
+    Paused,
+}
+
+/// Plays a playlist of music, ticked every frame by the `SoundHandler`
+pub struct MusicPlayer {
+    playlist: Vec<Arc<Music>>,
+
+    // play order of the playlist indices, shuffled if enabled
+    order: Vec<usize>,
+    position: usize,
+
+    shuffle: bool,
+    repeat: RepeatMode,
+    transition: MusicTransition,
+    pause_fade: Duration,
+
+    state: PlayerState,
+    pause_ramp: VolumeRamp,
+    voices: Vec<MusicVoice>,
+
+    // start of the next track after a gap
+    next_start: Option<Duration>,
+    // clock of the last update, calls between two updates happen at this time
+    now: Duration,
+}
+
+impl MusicPlayer {
+    pub(crate) fn new() -> Self {
+        MusicPlayer {
+            playlist: Vec::new(),
+
+            order: Vec::new(),
+            position: 0,
+
+            shuffle: false,
+            repeat: RepeatMode::All,
+            transition: MusicTransition::Gapless,
+            pause_fade: Duration::from_millis(500),
+
+            state: PlayerState::Stopped,
+            pause_ramp: VolumeRamp::new(1.0),
+            voices: Vec::new(),
+
+            next_start: None,
+            now: Duration::default(),
+        }
+    }
+
+    /// Replaces the playlist and stops the playback, music is loaded with
+    /// `SoundHandler::load_music`
+    pub fn set_playlist(&mut self, playlist: Vec<Arc<Music>>) -> VerboseResult<()> {
+        self.stop_voices()?;
+
+        self.playlist = playlist;
+        self.position = 0;
+        self.reorder();
+
+        self.state = PlayerState::Stopped;
+
+        Ok(())
+    }
+
+    pub fn playlist(&self) -> &[Arc<Music>] {
+        &self.playlist
+    }
+
+    pub fn set_shuffle(&mut self, shuffle: bool) {
+        if self.shuffle != shuffle {
+            self.shuffle = shuffle;
+            self.reorder();
+        }
+    }
+
+    pub fn shuffle(&self) -> bool {
+        self.shuffle
+    }
+
+    pub fn set_repeat(&mut self, repeat: RepeatMode) {
+        self.repeat = repeat;
+    }
+
+    pub fn repeat(&self) -> RepeatMode {
+        self.repeat
+    }
+
+    pub fn set_transition(&mut self, transition: MusicTransition) {
+        self.transition = transition;
+    }
+
+    pub fn transition(&self) -> MusicTransition {
+        self.transition
+    }
+
+    /// Fade duration of `SoundHandler::pause` and `SoundHandler::resume`
+    pub fn set_pause_fade(&mut self, pause_fade: Duration) {
+        self.pause_fade = pause_fade;
+    }
+
+    pub fn pause_fade(&self) -> Duration {
+        self.pause_fade
+    }
+
+    /// Track, that is currently playing
+    pub fn current(&self) -> Option<&Arc<Music>> {
+        self.voices
+            .iter()
+            .rev()
+            .find(|voice| !voice.retired)
+            .map(|voice| &voice.music)
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.state == PlayerState::Playing
+    }
+
+    /// Starts the playlist at the current track, `fade_in` of zero starts immediately
+    pub fn play(&mut self, fade_in: Duration) -> VerboseResult<()> {
+        if self.playlist.is_empty() {
+            create_error!("music player has an empty playlist");
+        }
+
+        self.stop_voices()?;
+
+        self.state = PlayerState::Playing;
+        self.pause_ramp = VolumeRamp::new(1.0);
+
+        self.start_track(Some((fade_in, FadeCurve::default())), self.now)
+    }
+
+    /// Fades out all tracks and stops the playback
+    pub fn stop(&mut self, fade_out: Duration) {
+        let now = self.now;
+
+        for voice in self.voices.iter_mut() {
+            voice.retired = true;
+            voice.fade.ramp_to(0.0, fade_out, now);
+        }
+
+        self.state = PlayerState::Stopped;
+        self.next_start = None;
+    }
+
+    /// Moves to the next track with the configured transition, ignores `RepeatMode::One`
+    pub fn next_track(&mut self) -> VerboseResult<()> {
+        self.skip(1)
+    }
+
+    pub fn previous_track(&mut self) -> VerboseResult<()> {
+        self.skip(-1)
+    }
+
+    pub(crate) fn pause(&mut self) {
+        if self.state == PlayerState::Playing {
+            self.pause_ramp.ramp_to(0.0, self.pause_fade, self.now);
+            self.state = PlayerState::Pausing;
+        }
+    }
+
+    pub(crate) fn resume(&mut self) -> VerboseResult<()> {
+        let now = self.now;
+
+        match self.state {
+            PlayerState::Paused => {
+                for voice in self.voices.iter_mut() {
+                    voice.music.play(false)?;
+                    voice.playing_since = Some(now);
+                }
+            }
+            PlayerState::Pausing => (),
+            _ => return Ok(()),
+        }
+
+        self.pause_ramp.ramp_to(1.0, self.pause_fade, now);
+        self.state = PlayerState::Playing;
+
+        Ok(())
+    }
+
+    /// true, if the music is played by this player
+    pub(crate) fn manages(&self, music: &Arc<Music>) -> bool {
+        self.voices
+            .iter()
+            .any(|voice| Arc::ptr_eq(&voice.music, music))
+    }
+
+    /// Volume factor of a track, 1.0 for music, that isn't played by the player
+    pub(crate) fn fade(&self, music: &Arc<Music>, now: Duration) -> f32 {
+        self.voices
+            .iter()
+            .filter(|voice| Arc::ptr_eq(&voice.music, music))
+            .map(|voice| voice.fade.value(now) * self.pause_ramp.value(now))
+            .fold(1.0, f32::min)
+    }
+
+    pub(crate) fn voices(&self) -> impl Iterator<Item = &Arc<Music>> {
+        self.voices.iter().map(|voice| &voice.music)
+    }
+
+    pub(crate) fn update(&mut self, now: Duration) -> VerboseResult<()> {
+        let frame_time = now.checked_sub(self.now).unwrap_or_default();
+
+        self.now = now;
+
+        if self.state == PlayerState::Pausing && !self.pause_ramp.is_active(now) {
+            for voice in self.voices.iter_mut() {
+                voice.music.pause()?;
+
+                voice.elapsed = voice.position(now);
+                voice.playing_since = None;
+            }
+
+            self.state = PlayerState::Paused;
+        }
+
+        if self.state == PlayerState::Paused {
+            return Ok(());
+        }
+
+        // drop tracks, that faded out or ended
+        let mut index = 0;
+
+        while index < self.voices.len() {
+            let voice = &self.voices[index];
+
+            let faded_out =
+                voice.retired && !voice.fade.is_active(now) && voice.fade.target() == 0.0;
+
+            if faded_out || !voice.music.is_playing()? {
+                voice.music.stop()?;
+                self.voices.remove(index);
+            } else {
+                index += 1;
+            }
+        }
+
+        if self.state != PlayerState::Playing {
+            return Ok(());
+        }
+
+        let current_index = self.voices.iter().rposition(|voice| !voice.retired);
+
+        match current_index {
+            Some(current_index) => {
+                let remaining = self.voices[current_index].remaining(now);
+
+                match self.transition {
+                    MusicTransition::Gap(_) => (),
+                    MusicTransition::Gapless => {
+                        if remaining <= frame_time {
+                            self.voices[current_index].retired = true;
+                            self.advance(1, None, now)?;
+                        }
+                    }
+                    MusicTransition::Crossfade { duration, curve } => {
+                        if remaining <= duration {
+                            let same_track = self
+                                .next_music(1)
+                                .map(|music| Arc::ptr_eq(&music, &self.voices[current_index].music))
+                                .unwrap_or(false);
+
+                            // a single track can't fade into itself, so it restarts without a gap
+                            if same_track {
+                                if remaining <= frame_time {
+                                    self.voices[current_index].retired = true;
+                                    self.advance(1, None, now)?;
+                                }
+                            } else {
+                                let voice = &mut self.voices[current_index];
+
+                                voice.retired = true;
+                                voice.fade.ramp_with(0.0, remaining, curve, now);
+
+                                self.advance(1, Some((remaining, curve)), now)?;
+                            }
+                        }
+                    }
+                }
+            }
+            None => match (self.transition, self.next_start) {
+                (MusicTransition::Gap(gap), None) => {
+                    self.next_start = Some(now + gap);
+                }
+                (MusicTransition::Gap(_), Some(next_start)) => {
+                    if now >= next_start {
+                        self.next_start = None;
+                        self.advance(1, None, now)?;
+                    }
+                }
+                _ => self.advance(1, None, now)?,
+            },
+        }
+
+        Ok(())
+    }
+
+    fn skip(&mut self, step: isize) -> VerboseResult<()> {
+        if self.playlist.is_empty() {
+            return Ok(());
+        }
+
+        let now = self.now;
+
+        let fade = match self.transition {
+            MusicTransition::Crossfade { duration, curve } => Some((duration, curve)),
+            _ => None,
+        };
+
+        for voice in self.voices.iter_mut() {
+            voice.retired = true;
+
+            match fade {
+                Some((duration, curve)) => voice.fade.ramp_with(0.0, duration, curve, now),
+                None => voice.fade.ramp_to(0.0, Duration::default(), now),
+            }
+        }
+
+        self.next_start = None;
+
+        // skipping ignores repeating a single track
+        self.position = self.step_position(step);
+
+        if self.state == PlayerState::Playing {
+            self.start_track(fade, now)?;
+        }
+
+        Ok(())
+    }
+
+    // moves to the next track according to the repeat mode, stops at the end of the playlist
+    fn advance(
+        &mut self,
+        step: isize,
+        fade_in: Option<(Duration, FadeCurve)>,
+        now: Duration,
+    ) -> VerboseResult<()> {
+        match self.repeat {
+            RepeatMode::One => (),
+            RepeatMode::All => {
+                let position = self.step_position(step);
+
+                // a new round of a shuffled playlist gets a new order
+                if self.shuffle && position == 0 {
+                    self.reorder();
+                }
+
+                self.position = position;
+            }
+            RepeatMode::Off => {
+                if self.position + 1 >= self.order.len() {
+                    self.state = PlayerState::Stopped;
+                    return Ok(());
+                }
+
+                self.position += 1;
+            }
+        }
+
+        self.start_track(fade_in, now)
+    }
+
+    // music, that `advance` would start
+    fn next_music(&self, step: isize) -> Option<Arc<Music>> {
+        let position = match self.repeat {
+            RepeatMode::One => self.position,
+            RepeatMode::All => self.step_position(step),
+            RepeatMode::Off => {
+                if self.position + 1 >= self.order.len() {
+                    return None;
+                }
+
+                self.position + 1
+            }
+        };
+
+        self.order
+            .get(position)
+            .map(|index| self.playlist[*index].clone())
+    }
+
+    fn step_position(&self, step: isize) -> usize {
+        let count = self.order.len() as isize;
+
+        if count == 0 {
+            return 0;
+        }
+
+        ((self.position as isize + step) % count + count) as usize % count as usize
+    }
+
+    fn start_track(
+        &mut self,
+        fade_in: Option<(Duration, FadeCurve)>,
+        now: Duration,
+    ) -> VerboseResult<()> {
+        let music = match self.order.get(self.position) {
+            Some(index) => self.playlist[*index].clone(),
+            None => return Ok(()),
+        };
+
+        // the same music can't play twice at once
+        if let Some(index) = self
+            .voices
+            .iter()
+            .position(|voice| Arc::ptr_eq(&voice.music, &music))
+        {
+            self.voices.remove(index);
+        }
+
+        music.stop()?;
+        music.play(false)?;
+
+        let mut fade = VolumeRamp::new(1.0);
+
+        if let Some((duration, curve)) = fade_in {
+            fade = VolumeRamp::new(0.0);
+            fade.ramp_with(1.0, duration, curve, now);
+        }
+
+        self.voices.push(MusicVoice {
+            music,
+            fade,
+
+            elapsed: Duration::default(),
+            playing_since: Some(now),
+
+            retired: false,
+        });
+
+        Ok(())
+    }
+
+    fn stop_voices(&mut self) -> VerboseResult<()> {
+        for voice in self.voices.drain(..) {
+            voice.music.stop()?;
+        }
+
+        self.next_start = None;
+
+        Ok(())
+    }
+
+    fn reorder(&mut self) {
+        self.order = (0..self.playlist.len()).collect();
+
+        if self.shuffle {
+            self.order.shuffle(&mut rand::thread_rng());
+        }
+
+        self.position = self.position.min(self.order.len().saturating_sub(1));
+    }
+}
@@ -1,37 +1,42 @@
-use ears::{
-    init,
-    listener::{
-        get_orientation, get_position, get_volume, set_orientation, set_position, set_volume,
-    },
-    AudioController, Music as ALMusic, ReverbEffect, Sound as ALSound, SoundData, State,
-};
-
 use presentation::prelude::*;
 
+#[cfg(feature = "audio")]
+use super::audiobackend::{nullbackend::NullBackend, openalbackend::OpenALBackend};
+use super::audiobackend::{
+    wavbuffer::WavBuffer, AudioBackend, Reverb, SharedBackend, SourceId, SourceState,
+};
+use super::mixer::{Ducking, Mixer, MixerBus, MUSIC_BUS};
+use super::musicplayer::MusicPlayer;
+use super::voicemanager::{Admission, PlayInfo, VoiceLimit, VoiceManager};
+
 use std::cell::RefCell;
 use std::collections::HashMap;
-use std::rc::Rc;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 macro_rules! sound_ctor {
-    ( $(($struct_name:ident, $type_name:ident, $member:ident)),+ ) => {
+    ( $($struct_name:ident),+ ) => {
         $(
             pub struct $struct_name {
-                $member: Mutex<$type_name>,
+                backend: SharedBackend,
+                source: SourceId,
                 path: String,
                 sound_type: String,
                 duration: Duration,
-                reverb: RefCell<Option<ReverbEffect>>,
+                reverb: RefCell<Option<Reverb>>,
             }
 
             impl $struct_name {
-                fn new(path: &str, sound_type: &str) -> VerboseResult<Arc<Self>> {
-                    let $member = $type_name::new(path)?;
-
+                fn new(
+                    backend: &SharedBackend,
+                    source: SourceId,
+                    path: &str,
+                    sound_type: &str,
+                ) -> VerboseResult<Arc<Self>> {
                     Ok(Arc::new($struct_name {
-                        duration: $member.get_duration(),
-                        $member: Mutex::new($member),
+                        duration: backend.lock()?.duration(source)?,
+                        backend: backend.clone(),
+                        source,
                         path: path.to_string(),
                         sound_type: sound_type.to_string(),
                         reverb: RefCell::new(None),
@@ -39,80 +44,58 @@ macro_rules! sound_ctor {
                 }
 
                 pub fn play(&self, enable_looping: bool) -> VerboseResult<()> {
-                    let mut $member = self.$member.lock()?;
+                    let mut backend = self.backend.lock()?;
 
-                    if !$member.is_playing() {
-                        $member.play();
-                        $member.set_looping(enable_looping);
+                    if backend.state(self.source)? != SourceState::Playing {
+                        backend.play(self.source)?;
+                        backend.set_looping(self.source, enable_looping)?;
                     }
 
                     Ok(())
                 }
 
                 pub fn stop_looping(&self) -> VerboseResult<()> {
-                    self.$member.lock()?.set_looping(false);
-
-                    Ok(())
+                    self.backend.lock()?.set_looping(self.source, false)
                 }
 
                 pub fn stop(&self) -> VerboseResult<()> {
-                    self.$member.lock()?.stop();
-
-                    Ok(())
+                    self.backend.lock()?.stop(self.source)
                 }
 
                 pub fn set_position(&self, position: impl Into<[f32; 3]>) -> VerboseResult<()> {
-                    self.$member.lock()?.set_position(position.into());
-
-                    Ok(())
+                    self.backend.lock()?.set_position(self.source, position.into())
                 }
 
                 pub fn set_direction(&self, direction: impl Into<[f32; 3]>) -> VerboseResult<()> {
-                    self.$member.lock()?.set_direction(direction.into());
-
-                    Ok(())
+                    self.backend.lock()?.set_direction(self.source, direction.into())
                 }
 
                 pub fn set_attenuation(&self, attenuation: f32) -> VerboseResult<()> {
-                    self.$member.lock()?.set_attenuation(attenuation);
-
-                    Ok(())
+                    self.backend.lock()?.set_attenuation(self.source, attenuation)
                 }
 
                 pub fn set_max_volume(&self, max_volume: f32) -> VerboseResult<()> {
-                    self.$member.lock()?.set_max_volume(max_volume);
-
-                    Ok(())
+                    self.backend.lock()?.set_max_volume(self.source, max_volume)
                 }
 
                 pub fn set_min_volume(&self, min_volume: f32) -> VerboseResult<()> {
-                    self.$member.lock()?.set_min_volume(min_volume);
-
-                    Ok(())
+                    self.backend.lock()?.set_min_volume(self.source, min_volume)
                 }
 
                 pub fn set_max_distance(&self, max_distance: f32) -> VerboseResult<()> {
-                    self.$member.lock()?.set_max_distance(max_distance);
-
-                    Ok(())
+                    self.backend.lock()?.set_max_distance(self.source, max_distance)
                 }
 
                 pub fn set_min_distance(&self, min_distance: f32) -> VerboseResult<()> {
-                    self.$member.lock()?.set_reference_distance(min_distance);
-
-                    Ok(())
+                    self.backend.lock()?.set_reference_distance(self.source, min_distance)
                 }
 
                 pub fn set_relative(&self, s: bool) -> VerboseResult<()> {
-                    self.$member.lock()?.set_relative(s);
-
-                    Ok(())
+                    self.backend.lock()?.set_relative(self.source, s)
                 }
 
                 pub fn set_pitch(&self, pitch: f32) -> VerboseResult<()> {
-                    self.$member.lock()?.set_pitch(pitch);
-
-                    Ok(())
+                    self.backend.lock()?.set_pitch(self.source, pitch)
                 }
 
                 pub fn file_path(&self) -> &str {
@@ -127,13 +110,43 @@ macro_rules! sound_ctor {
                     self.duration
                 }
 
-                pub fn set_reverb(&self, reverb_effect: Option<ReverbEffect>) -> VerboseResult<()> {
+                pub fn set_reverb(&self, reverb_effect: Option<Reverb>) -> VerboseResult<()> {
                     let mut reverb = self.reverb.try_borrow_mut()?;
                     *reverb = reverb_effect;
 
-                    self.$member.lock()?.connect(&reverb);
+                    self.backend.lock()?.connect(self.source, &reverb)
+                }
 
-                    Ok(())
+                pub(crate) fn state(&self) -> VerboseResult<SourceState> {
+                    self.backend.lock()?.state(self.source)
+                }
+
+                pub(crate) fn is_playing(&self) -> VerboseResult<bool> {
+                    Ok(self.state()? == SourceState::Playing)
+                }
+
+                pub(crate) fn pause(&self) -> VerboseResult<()> {
+                    self.backend.lock()?.pause(self.source)
+                }
+
+                // continues a paused source, in contrast to `play`
+                pub(crate) fn resume(&self) -> VerboseResult<()> {
+                    self.backend.lock()?.play(self.source)
+                }
+
+                pub(crate) fn set_volume(&self, volume: f32) -> VerboseResult<()> {
+                    self.backend.lock()?.set_volume(self.source, volume)
+                }
+
+                // applies the reverb and filters of its bus, an own reverb is kept
+                fn apply_bus(&self, mixer: &Mixer, bus: &str) -> VerboseResult<()> {
+                    let mut backend = self.backend.lock()?;
+
+                    if self.reverb.try_borrow()?.is_none() {
+                        backend.connect(self.source, mixer.reverb(bus))?;
+                    }
+
+                    backend.set_filters(self.source, mixer.low_pass(bus), mixer.high_pass(bus))
                 }
             }
 
@@ -143,18 +156,26 @@ macro_rules! sound_ctor {
                 }
 
                 fn is_playing(&self) -> VerboseResult<bool> {
-                    Ok(self.$member.lock()?.is_playing())
+                    $struct_name::is_playing(self)
                 }
             }
 
-            // safe since, OpenAL is thread safe
+            impl Drop for $struct_name {
+                fn drop(&mut self) {
+                    if let Ok(mut backend) = self.backend.lock() {
+                        backend.remove_source(self.source);
+                    }
+                }
+            }
+
+            // safe since, the backend is behind a mutex
             unsafe impl Send for $struct_name {}
             unsafe impl Sync for $struct_name {}
         )*
     }
 }
 
-sound_ctor!((Sound, ALSound, sound), (Music, ALMusic, music));
+sound_ctor!(Sound, Music);
 
 trait ClearQueueObject {
     fn end_looping(&self) -> VerboseResult<()>;
@@ -162,36 +183,30 @@ trait ClearQueueObject {
 }
 
 impl Sound {
-    fn from_data(
-        path: &str,
-        sound_type: &str,
-        data: Rc<RefCell<SoundData>>,
-    ) -> VerboseResult<Arc<Self>> {
-        let sound = ALSound::new_with_data(data)?;
-
-        Ok(Arc::new(Sound {
-            duration: sound.get_duration(),
-            sound: Mutex::new(sound),
-            path: path.to_string(),
-            sound_type: sound_type.to_string(),
-            reverb: RefCell::new(None),
-        }))
-    }
-
+    /// Waits until the sound stopped, offline backends only advance in `SoundHandler::update`,
+    /// so it must not be called on the thread, that updates them
     pub fn block(&self) -> VerboseResult<()> {
-        let sound = self.sound.lock()?;
-        while sound.is_playing() {}
+        while self.is_playing()? {}
 
         Ok(())
     }
 
     pub fn set_air_absorption_factor(&self, factor: f32) -> VerboseResult<()> {
-        self.sound.lock()?.set_air_absorption_factor(factor);
+        self.backend
+            .lock()?
+            .set_air_absorption_factor(self.source, factor)
+    }
 
-        Ok(())
+    // paused sounds still occupy their voice
+    pub(crate) fn is_stopped(&self) -> VerboseResult<bool> {
+        match self.state()? {
+            SourceState::Initial | SourceState::Stopped => Ok(true),
+            SourceState::Playing | SourceState::Paused => Ok(false),
+        }
     }
 }
 
+/// Initial volumes of the master and music bus, `other_volume` is used for every other bus
 #[derive(Copy, Clone, Debug, Default)]
 pub struct VolumeInfo {
     pub master_volume: f32,
@@ -200,46 +215,80 @@ pub struct VolumeInfo {
 }
 
 pub struct SoundHandler {
-    volume_info: VolumeInfo,
+    mixer: Mixer,
+    // volumes have to be applied again, because bus settings changed or a ramp ended
+    mixer_dirty: bool,
+    mixer_ramping: bool,
 
-    // sound handling
-    sound_volumes: HashMap<String, f32>,
+    // sound handling, sounds are grouped by their bus
     sounds: HashMap<String, Vec<Arc<Sound>>>,
 
+    // sources of play_sound, they are part of `sounds` as well
+    sound_pools: HashMap<String, Vec<Arc<Sound>>>,
+    voice_manager: VoiceManager,
+
     // music handling
     music: Vec<Arc<Music>>,
+    music_player: MusicPlayer,
 
-    // 'clever' data handling
-    data: HashMap<String, Rc<RefCell<SoundData>>>,
+    backend: SharedBackend,
+    // audio time, that `update` advanced, ramps and the music player run on it
+    clock: Duration,
 
     clear_queue: Vec<Arc<dyn ClearQueueObject>>,
 }
 
 impl SoundHandler {
-    pub(crate) fn new(volume_info: VolumeInfo) -> VerboseResult<SoundHandler> {
-        init()?;
-        set_volume(volume_info.master_volume);
-
+    /// Mixes into `backend`, e.g. a `SoftwareBackend` for tests without an audio device
+    pub fn new(
+        volume_info: VolumeInfo,
+        backend: Box<dyn AudioBackend>,
+    ) -> VerboseResult<SoundHandler> {
         Ok(SoundHandler {
-            volume_info,
+            mixer: Mixer::new(&volume_info),
+            mixer_dirty: false,
+            mixer_ramping: false,
 
-            sound_volumes: HashMap::new(),
             sounds: HashMap::new(),
 
+            sound_pools: HashMap::new(),
+            voice_manager: VoiceManager::new(),
+
             music: Vec::new(),
+            music_player: MusicPlayer::new(),
 
-            data: HashMap::new(),
+            backend: Arc::new(Mutex::new(backend)),
+            clock: Duration::default(),
 
             clear_queue: Vec::new(),
         })
     }
 
+    /// Uses OpenAL, falls back to the `NullBackend`, if there is no audio device
+    #[cfg(feature = "audio")]
+    pub(crate) fn with_default_backend(volume_info: VolumeInfo) -> VerboseResult<SoundHandler> {
+        let backend: Box<dyn AudioBackend> = match OpenALBackend::new() {
+            Ok(backend) => Box::new(backend),
+            Err(err) => {
+                println!("failed initializing OpenAL, audio is disabled: {}", err);
+
+                Box::new(NullBackend::new())
+            }
+        };
+
+        Self::new(volume_info, backend)
+    }
+
     pub fn set_position(&self, position: impl Into<[f32; 3]>) {
         let pos = position.into();
-        let current_position = get_position();
 
-        if pos != current_position {
-            set_position(pos);
+        match self.backend.lock() {
+            Ok(mut backend) => {
+                if pos != backend.listener_position() {
+                    backend.set_listener_position(pos);
+                }
+            }
+            Err(err) => println!("{}", err),
         }
     }
 
@@ -247,44 +296,47 @@ impl SoundHandler {
         let dir = direction.into();
         let up = up.into();
 
-        let (current_direction, current_up) = get_orientation();
+        match self.backend.lock() {
+            Ok(mut backend) => {
+                let (current_direction, current_up) = backend.listener_orientation();
 
-        if dir != current_direction || up != current_up {
-            set_orientation(dir, up);
+                if dir != current_direction || up != current_up {
+                    backend.set_listener_orientation(dir, up);
+                }
+            }
+            Err(err) => println!("{}", err),
         }
     }
 
     pub fn set_global_volume(&self, volume: f32) {
-        let current_volume = get_volume();
-
-        if volume != current_volume {
-            set_volume(current_volume);
+        match self.backend.lock() {
+            Ok(mut backend) => {
+                if volume != backend.listener_volume() {
+                    backend.set_listener_volume(volume);
+                }
+            }
+            Err(err) => println!("{}", err),
         }
     }
 
+    /// Everything, that was rendered since the last call, `None` if the backend doesn't render
+    pub fn take_output(&self) -> VerboseResult<Option<WavBuffer>> {
+        self.backend.lock()?.take_output()
+    }
+
+    /// Loads a sound and routes it to the bus `sound_type`
+    ///
+    /// If the bus doesn't exist, it is created as a child of the master bus
     pub fn load_sound(&mut self, path: &str, sound_type: &str) -> VerboseResult<Arc<Sound>> {
         // create sound
-        let sound = match self.data.get(path) {
-            Some(data) => Sound::from_data(path, sound_type, data.clone())?,
-            None => {
-                let sound = Sound::new(path, sound_type)?;
-                self.data
-                    .insert(path.to_string(), sound.sound.lock()?.get_datas());
+        let source = self.backend.lock()?.load_sound(path)?;
+        let sound = Sound::new(&self.backend, source, path, sound_type)?;
 
-                sound
-            }
-        };
+        self.mixer.ensure_bus(sound_type);
 
-        // query volume for given sound type
-        let volume = match self.sound_volumes.get(sound_type) {
-            Some(volume) => *volume,
-            None => self.volume_info.other_volume,
-        };
-
-        // set volume
-        if let Ok(mut internal_sound) = sound.sound.lock() {
-            internal_sound.set_volume(volume);
-        }
+        // apply the settings of the bus
+        sound.set_volume(self.mixer.gain(sound_type, self.clock))?;
+        sound.apply_bus(&self.mixer, sound_type)?;
 
         // add sound to internal map
         match self.sounds.get_mut(sound_type) {
@@ -298,14 +350,116 @@ impl SoundHandler {
         Ok(sound)
     }
 
+    /// Plays a sound with a source from a pool, instead of loading a new one
+    ///
+    /// Returns `None`, if the voice limit of the sound type or the distance to the listener
+    /// prevent playback. The sound goes back to the pool after it stopped, so it shouldn't
+    /// be used anymore afterwards.
+    pub fn play_sound(
+        &mut self,
+        path: &str,
+        sound_type: &str,
+        info: PlayInfo,
+    ) -> VerboseResult<Option<Arc<Sound>>> {
+        let now = self.clock;
+        let mixer = &self.mixer;
+
+        let admission =
+            self.voice_manager
+                .admit(sound_type, &info, self.listener_position()?, |bus| {
+                    mixer.gain(bus, now)
+                });
+
+        match admission {
+            Admission::Play => (),
+            Admission::Steal(sound) => {
+                sound.stop()?;
+                self.voice_manager.remove(&sound);
+            }
+            Admission::Reject => return Ok(None),
+        }
+
+        let sound = self.pooled_sound(path, sound_type)?;
+
+        sound.set_pitch(info.pitch)?;
+
+        match info.position {
+            Some(position) => {
+                sound.set_relative(false)?;
+                sound.set_position(position)?;
+            }
+            None => {
+                sound.set_relative(true)?;
+                sound.set_position([0.0, 0.0, 0.0])?;
+            }
+        }
+
+        sound.play(info.looping)?;
+
+        self.voice_manager
+            .add(sound.clone(), sound_type, &info, self.clock);
+
+        Ok(Some(sound))
+    }
+
+    /// Limits the voices of `play_sound` for a sound type, `None` removes the limit
+    pub fn set_voice_limit(&mut self, sound_type: &str, limit: Option<VoiceLimit>) {
+        self.voice_manager.set_limit(sound_type, limit);
+    }
+
+    pub fn voice_limit(&self, sound_type: &str) -> Option<VoiceLimit> {
+        self.voice_manager.limit(sound_type)
+    }
+
+    /// Limit of all voices of `play_sound` together, the steal policy of the new sound's
+    /// type is used
+    pub fn set_max_voices(&mut self, max_voices: usize) {
+        self.voice_manager.set_max_voices(max_voices);
+    }
+
+    pub fn max_voices(&self) -> usize {
+        self.voice_manager.max_voices()
+    }
+
+    /// Active voices of `play_sound`, either of a sound type or of all
+    pub fn voice_count(&self, sound_type: Option<&str>) -> usize {
+        self.voice_manager.voice_count(sound_type)
+    }
+
+    fn pooled_sound(&mut self, path: &str, sound_type: &str) -> VerboseResult<Arc<Sound>> {
+        if let Some(pool) = self.sound_pools.get(sound_type) {
+            for sound in pool {
+                if sound.file_path() == path && sound.is_stopped()? {
+                    // the voice manager only notices stopped sounds in update
+                    self.voice_manager.remove(sound);
+
+                    return Ok(sound.clone());
+                }
+            }
+        }
+
+        let sound = self.load_sound(path, sound_type)?;
+
+        match self.sound_pools.get_mut(sound_type) {
+            Some(pool) => pool.push(sound.clone()),
+            None => {
+                self.sound_pools
+                    .insert(sound_type.to_string(), vec![sound.clone()]);
+            }
+        }
+
+        Ok(sound)
+    }
+
+    /// Loads music and routes it to the music bus
     pub fn load_music(&mut self, path: &str) -> VerboseResult<Arc<Music>> {
         // create music
-        let music = Music::new(path, "music")?;
+        let source = self.backend.lock()?.load_music(path)?;
+        let music = Music::new(&self.backend, source, path, MUSIC_BUS)?;
 
-        // set volume
-        if let Ok(mut internal_music) = music.music.lock() {
-            internal_music.set_volume(self.volume_info.music_volume);
-        }
+        // apply the settings of the bus
+        music.set_volume(self.mixer.gain(MUSIC_BUS, self.clock))?;
+        music.apply_bus(&self.mixer, MUSIC_BUS)?;
 
         // add music to internal vector
         self.music.push(music.clone());
@@ -313,96 +467,260 @@ impl SoundHandler {
         Ok(music)
     }
 
+    /// Playlist playback of music, that was loaded with `load_music`
+    pub fn music_player(&mut self) -> &mut MusicPlayer {
+        &mut self.music_player
+    }
+
+    /// Sets the volume of a bus immediately, `"master"` and `"music"` are buses as well
     pub fn set_volume(&mut self, sound_type: &str, volume: f32) {
-        // check for master volume
-        if sound_type == "master" {
-            if get_volume() != volume {
-                self.volume_info.master_volume = volume;
+        self.mixer.ensure_bus(sound_type);
+
+        if let Err(err) = self.ramp_volume(sound_type, volume, Duration::default()) {
+            println!("{}", err);
+        }
+    }
+
+    /// Moves the volume of a bus smoothly to `volume` over `duration`
+    pub fn ramp_volume(&mut self, bus: &str, volume: f32, duration: Duration) -> VerboseResult<()> {
+        self.mixer.set_volume(bus, volume, duration, self.clock)?;
+        self.mixer_dirty = true;
+
+        Ok(())
+    }
+
+    /// Creates a bus below `parent`, e.g. `"dialogue"` below `"master"`
+    pub fn create_bus(&mut self, name: &str, parent: &str) -> VerboseResult<()> {
+        self.mixer.create_bus(name, parent)
+    }
+
+    pub fn bus(&self, name: &str) -> Option<&MixerBus> {
+        self.mixer.bus(name)
+    }
+
+    pub fn buses(&self) -> &[MixerBus] {
+        self.mixer.buses()
+    }
+
+    pub fn set_muted(&mut self, bus: &str, muted: bool) -> VerboseResult<()> {
+        self.mixer.set_muted(bus, muted)?;
+        self.mixer_dirty = true;
+
+        Ok(())
+    }
+
+    /// While any bus is soloed, only buses below a soloed bus are audible
+    pub fn set_solo(&mut self, bus: &str, solo: bool) -> VerboseResult<()> {
+        self.mixer.set_solo(bus, solo)?;
+        self.mixer_dirty = true;
+
+        Ok(())
+    }
+
+    /// Cutoff frequency in Hz, the lowest cutoff of a bus and its parents is used
+    ///
+    /// Filters are only rendered by the offline `SoftwareBackend`, the real time
+    /// `OpenALBackend` has none. Cutoffs fail, unless `supports_filters` is true.
+    pub fn set_low_pass(&mut self, bus: &str, cutoff: Option<f32>) -> VerboseResult<()> {
+        self.check_filter_support(cutoff)?;
+        self.mixer.set_low_pass(bus, cutoff)?;
+
+        self.apply_bus_settings(bus)
+    }
 
-                set_volume(volume);
+    /// Cutoff frequency in Hz, the highest cutoff of a bus and its parents is used
+    ///
+    /// Like `set_low_pass`, cutoffs fail, unless `supports_filters` is true
+    pub fn set_high_pass(&mut self, bus: &str, cutoff: Option<f32>) -> VerboseResult<()> {
+        self.check_filter_support(cutoff)?;
+        self.mixer.set_high_pass(bus, cutoff)?;
+
+        self.apply_bus_settings(bus)
+    }
+
+    /// false for `OpenALBackend`, low and high pass filters can't be used then
+    pub fn supports_filters(&self) -> VerboseResult<bool> {
+        Ok(self.backend.lock()?.supports_filters())
+    }
+
+    fn check_filter_support(&self, cutoff: Option<f32>) -> VerboseResult<()> {
+        if cutoff.is_some() && !self.backend.lock()?.supports_filters() {
+            create_error!("audio backend doesn't support filters");
+        }
+
+        Ok(())
+    }
+
+    /// Reverb send of all sounds on the bus and its children, that don't have their own
+    pub fn set_bus_reverb(&mut self, bus: &str, reverb: Option<Reverb>) -> VerboseResult<()> {
+        self.mixer.set_reverb(bus, reverb)?;
+
+        self.apply_bus_settings(bus)
+    }
+
+    // reverb and filters of all sounds on the bus and its children
+    fn apply_bus_settings(&self, bus: &str) -> VerboseResult<()> {
+        for (sound_type, sounds) in self.sounds.iter() {
+            if !self.mixer.is_in_chain(sound_type, bus) {
+                continue;
+            }
+
+            for sound in sounds {
+                sound.apply_bus(&self.mixer, sound_type)?;
             }
         }
-        // check if sound type is music, since we separate it
-        else if sound_type == "music" {
-            if self.volume_info.master_volume != volume {
-                // set volume
-                self.volume_info.music_volume = volume;
-
-                // iterate every music to set its volume
-                for music in self.music.iter() {
-                    if let Ok(mut internal_music) = music.music.lock() {
-                        internal_music.set_volume(volume);
-                    }
-                }
+
+        if self.mixer.is_in_chain(MUSIC_BUS, bus) {
+            for music in self.music.iter() {
+                music.apply_bus(&self.mixer, MUSIC_BUS)?;
             }
         }
-        // check all other sound types
-        else {
-            // check map if sound type is already present
-            match self.sound_volumes.get_mut(sound_type) {
-                Some(stype) => {
-                    // set volume
-                    *stype = volume;
-
-                    // check if sounds map has requested sound type
-                    if let Some(sounds) = self.sounds.get(sound_type) {
-                        // iterate every sound and set its volume
-                        for sound in sounds.iter() {
-                            if let Ok(mut internal_sound) = sound.sound.lock() {
-                                internal_sound.set_volume(volume);
-                            }
-                        }
-                    }
-                }
-                None => {
-                    // insert volume for requested sound type
-                    self.sound_volumes.insert(sound_type.to_string(), volume);
+
+        Ok(())
+    }
+
+    /// Lowers the volume of a bus, while sounds of another bus are playing
+    pub fn add_ducking(&mut self, ducking: Ducking) -> VerboseResult<()> {
+        self.mixer.add_ducking(ducking)
+    }
+
+    pub fn remove_ducking(&mut self, bus: &str, trigger: &str) {
+        self.mixer.remove_ducking(bus, trigger);
+    }
+
+    /// Renders `elapsed` with offline backends and advances the music player, volume ramps
+    /// and ducking by it
+    ///
+    /// Called every frame by the `Context`, without the `audio` feature it has to be called
+    /// manually.
+    pub fn update(&mut self, elapsed: Duration) -> VerboseResult<()> {
+        self.backend.lock()?.render(elapsed)?;
+
+        self.clock += elapsed;
+        let now = self.clock;
+
+        self.music_player.update(now)?;
+
+        // voices, that moved out of range of the listener
+        for sound in self.voice_manager.update(self.listener_position()?)? {
+            sound.stop()?;
+        }
+
+        let mut playing_buses = Vec::new();
+
+        for (sound_type, sounds) in self.sounds.iter() {
+            for sound in sounds {
+                if sound.is_playing()? {
+                    playing_buses.push(sound_type.as_str());
+                    break;
                 }
             }
         }
+
+        for music in self.music.iter() {
+            if music.is_playing()? {
+                playing_buses.push(MUSIC_BUS);
+                break;
+            }
+        }
+
+        self.mixer
+            .update_ducking(|bus| playing_buses.contains(&bus), now);
+
+        let ramping = self.mixer.is_ramping(now);
+
+        // apply once more after a ramp ended, so the target volume is reached exactly
+        if self.mixer_dirty || ramping || self.mixer_ramping {
+            self.apply_volumes(now)?;
+            self.mixer_dirty = false;
+        } else {
+            // the fades of the music player change every frame
+            let gain = self.mixer.gain(MUSIC_BUS, now);
+
+            for music in self.music_player.voices() {
+                music.set_volume(gain * self.music_player.fade(music, now))?;
+            }
+        }
+
+        self.mixer_ramping = ramping;
+
+        Ok(())
+    }
+
+    fn apply_volumes(&self, now: Duration) -> VerboseResult<()> {
+        for (sound_type, sounds) in self.sounds.iter() {
+            let gain = self.mixer.gain(sound_type, now);
+
+            for sound in sounds {
+                sound.set_volume(gain)?;
+            }
+        }
+
+        let gain = self.mixer.gain(MUSIC_BUS, now);
+
+        for music in self.music.iter() {
+            music.set_volume(gain * self.music_player.fade(music, now))?;
+        }
+
+        Ok(())
     }
 
     pub fn pause(&mut self) {
         // check if sounds are playing
         for sounds in self.sounds.values() {
             for sound in sounds {
-                if let Ok(mut internal_sound) = sound.sound.lock() {
-                    // if sound is playing, pause it
-                    if let State::Playing = internal_sound.get_state() {
-                        internal_sound.pause();
+                // if sound is playing, pause it
+                if let Ok(SourceState::Playing) = sound.state() {
+                    if let Err(err) = sound.pause() {
+                        println!("{}", err);
                     }
                 }
             }
         }
 
+        // the music player fades out instead
+        self.music_player.pause();
+
         for music in self.music.iter() {
-            if let Ok(mut internal_music) = music.music.lock() {
-                // if music is playing, pause it
-                if let State::Playing = internal_music.get_state() {
-                    internal_music.pause();
+            if self.music_player.manages(music) {
+                continue;
+            }
+
+            // if music is playing, pause it
+            if let Ok(SourceState::Playing) = music.state() {
+                if let Err(err) = music.pause() {
+                    println!("{}", err);
                 }
             }
         }
     }
 
-    pub fn resume(&self) {
+    pub fn resume(&mut self) {
         // check if sounds are paused
         for sounds in self.sounds.values() {
             for sound in sounds {
-                if let Ok(mut internal_sound) = sound.sound.lock() {
-                    // if sound is paused, resume it
-                    if let State::Paused = internal_sound.get_state() {
-                        internal_sound.play();
+                // if sound is paused, resume it
+                if let Ok(SourceState::Paused) = sound.state() {
+                    if let Err(err) = sound.resume() {
+                        println!("{}", err);
                     }
                 }
             }
         }
 
+        if let Err(err) = self.music_player.resume() {
+            println!("{}", err);
+        }
+
         for music in self.music.iter() {
-            if let Ok(mut internal_music) = music.music.lock() {
-                // if music is paused, resume it
-                if let State::Paused = internal_music.get_state() {
-                    internal_music.play();
+            if self.music_player.manages(music) {
+                continue;
+            }
+
+            // if music is paused, resume it
+            if let Ok(SourceState::Paused) = music.state() {
+                if let Err(err) = music.resume() {
+                    println!("{}", err);
                 }
             }
         }
@@ -410,6 +728,11 @@ impl SoundHandler {
 
     pub fn remove_sound(&mut self, sound: &Arc<Sound>) -> VerboseResult<()> {
         if let Some(sounds) = self.sounds.get_mut(sound.sound_type()) {
+            if let Some(pool) = self.sound_pools.get_mut(sound.sound_type()) {
+                erase_arc(pool, sound);
+                self.voice_manager.remove(sound);
+            }
+
             if let Some(old_sound) = erase_arc(sounds, sound) {
                 old_sound.end_looping()?;
                 self.clear_queue.push(old_sound);
@@ -419,7 +742,8 @@ impl SoundHandler {
         Ok(())
     }
 
-    pub(crate) fn check_clear_queue(&mut self) -> VerboseResult<()> {
+    /// Drops removed sounds, that finished playing, called once a second by the `Context`
+    pub fn check_clear_queue(&mut self) -> VerboseResult<()> {
         let mut new_queue = Vec::new();
 
         for sound in self.clear_queue.iter() {
@@ -434,12 +758,60 @@ impl SoundHandler {
     }
 
     pub fn clear(&mut self) {
+        if let Err(err) = self.music_player.set_playlist(Vec::new()) {
+            println!("{}", err);
+        }
+
         self.sounds.clear();
+        self.sound_pools.clear();
+        self.voice_manager.clear();
         self.music.clear();
-        self.data.clear();
+        match self.backend.lock() {
+            Ok(mut backend) => backend.clear_cache(),
+            Err(err) => println!("{}", err),
+        }
+    }
+
+    fn listener_position(&self) -> VerboseResult<[f32; 3]> {
+        Ok(self.backend.lock()?.listener_position())
     }
 }
 
 // should be safe
 unsafe impl Send for SoundHandler {}
 unsafe impl Sync for SoundHandler {}
+
+#[test]
+fn sound_handler_ramps_follow_the_update_clock() {
+    use super::audiobackend::softwarebackend::SoftwareBackend;
+
+    let mut backend = SoftwareBackend::new(100).unwrap();
+    backend.insert_data("constant", WavBuffer::new(100, 1, vec![0.5; 100]).unwrap());
+
+    let volume_info = VolumeInfo {
+        master_volume: 1.0,
+        music_volume: 1.0,
+        other_volume: 1.0,
+    };
+
+    let mut sound_handler = SoundHandler::new(volume_info, Box::new(backend)).unwrap();
+
+    let sound = sound_handler.load_sound("constant", "effects").unwrap();
+    sound.play(false).unwrap();
+
+    sound_handler
+        .ramp_volume("effects", 0.0, Duration::from_millis(100))
+        .unwrap();
+
+    // every update renders with the volume of the previous one, before the clock advances
+    for _ in 0..3 {
+        sound_handler.update(Duration::from_millis(50)).unwrap();
+    }
+
+    let left = sound_handler.take_output().unwrap().unwrap().channel(0);
+
+    assert_eq!(left.len(), 15);
+    assert_eq!(&left[0..5], &[0.5; 5]);
+    assert_eq!(&left[5..10], &[0.25; 5]);
+    assert_eq!(&left[10..], &[0.0; 5]);
+}
@@ -0,0 +1,402 @@
+use presentation::prelude::*;
+
+use super::soundhandler::Sound;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+// OpenAL implementations usually provide 256 sources, music and loaded sounds need some as well
+const DEFAULT_MAX_VOICES: usize = 64;
+
+/// Which voice is stopped, if a new one exceeds a limit
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StealPolicy {
+    /// the new voice isn't played
+    Reject,
+    StealOldest,
+    /// the voice, that is the least audible at the listener position
+    StealQuietest,
+}
+
+/// Limits of the pooled voices of a sound type
+#[derive(Debug, Clone, Copy)]
+pub struct VoiceLimit {
+    pub max_voices: usize,
+    pub policy: StealPolicy,
+
+    /// voices further away from the listener aren't played
+    pub max_distance: Option<f32>,
+}
+
+/// Parameters of `SoundHandler::play_sound`
+#[derive(Debug, Clone, Copy)]
+pub struct PlayInfo {
+    /// `None` plays the sound at the listener
+    pub position: Option<[f32; 3]>,
+
+    /// only voices with the same or a lower priority can be stolen
+    pub priority: u32,
+
+    pub looping: bool,
+    pub pitch: f32,
+}
+
+impl Default for PlayInfo {
+    fn default() -> Self {
+        PlayInfo {
+            position: None,
+            priority: 0,
+            looping: false,
+            pitch: 1.0,
+        }
+    }
+}
+
+pub(crate) enum Admission {
+    Play,
+    /// the voice has to be stopped, before the new one is played
+    Steal(Arc<Sound>),
+    Reject,
+}
+
+struct Voice {
+    sound: Arc<Sound>,
+    sound_type: String,
+
+    priority: u32,
+    // clock of the `SoundHandler`
+    started: Duration,
+    position: Option<[f32; 3]>,
+}
+
+/// Keeps track of the pooled voices and decides, which ones may play
+pub(crate) struct VoiceManager {
+    limits: HashMap<String, VoiceLimit>,
+    max_voices: usize,
+
+    voices: Vec<Voice>,
+}
+
+impl VoiceManager {
+    pub(crate) fn new() -> Self {
+        VoiceManager {
+            limits: HashMap::new(),
+            max_voices: DEFAULT_MAX_VOICES,
+
+            voices: Vec::new(),
+        }
+    }
+
+    pub(crate) fn set_limit(&mut self, sound_type: &str, limit: Option<VoiceLimit>) {
+        match limit {
+            Some(limit) => {
+                self.limits.insert(sound_type.to_string(), limit);
+            }
+            None => {
+                self.limits.remove(sound_type);
+            }
+        }
+    }
+
+    pub(crate) fn limit(&self, sound_type: &str) -> Option<VoiceLimit> {
+        self.limits.get(sound_type).cloned()
+    }
+
+    pub(crate) fn set_max_voices(&mut self, max_voices: usize) {
+        self.max_voices = max_voices;
+    }
+
+    pub(crate) fn max_voices(&self) -> usize {
+        self.max_voices
+    }
+
+    pub(crate) fn voice_count(&self, sound_type: Option<&str>) -> usize {
+        match sound_type {
+            Some(sound_type) => self
+                .voices
+                .iter()
+                .filter(|voice| voice.sound_type == sound_type)
+                .count(),
+            None => self.voices.len(),
+        }
+    }
+
+    /// `gain` is the volume of the bus of a sound type
+    pub(crate) fn admit(
+        &self,
+        sound_type: &str,
+        info: &PlayInfo,
+        listener: [f32; 3],
+        gain: impl Fn(&str) -> f32,
+    ) -> Admission {
+        let limit = self.limits.get(sound_type);
+
+        if let (Some(max_distance), Some(position)) =
+            (limit.and_then(|limit| limit.max_distance), info.position)
+        {
+            if Self::distance(position, listener) > max_distance {
+                return Admission::Reject;
+            }
+        }
+
+        let policy = limit
+            .map(|limit| limit.policy)
+            .unwrap_or(StealPolicy::StealOldest);
+
+        // the limit of the sound type is checked first, then the overall limit
+        if let Some(limit) = limit {
+            if self.voice_count(Some(sound_type)) >= limit.max_voices {
+                return self.steal(Some(sound_type), info.priority, policy, listener, &gain);
+            }
+        }
+
+        if self.voices.len() >= self.max_voices {
+            return self.steal(None, info.priority, policy, listener, &gain);
+        }
+
+        Admission::Play
+    }
+
+    pub(crate) fn add(
+        &mut self,
+        sound: Arc<Sound>,
+        sound_type: &str,
+        info: &PlayInfo,
+        now: Duration,
+    ) {
+        self.voices.push(Voice {
+            sound,
+            sound_type: sound_type.to_string(),
+
+            priority: info.priority,
+            started: now,
+            position: info.position,
+        });
+    }
+
+    pub(crate) fn remove(&mut self, sound: &Arc<Sound>) {
+        self.voices
+            .retain(|voice| !Arc::ptr_eq(&voice.sound, sound));
+    }
+
+    /// Forgets voices, that finished playing, and returns the ones, that moved out of range
+    /// of the listener
+    pub(crate) fn update(&mut self, listener: [f32; 3]) -> VerboseResult<Vec<Arc<Sound>>> {
+        let mut finished = Vec::new();
+        let mut culled = Vec::new();
+
+        for (index, voice) in self.voices.iter().enumerate() {
+            if voice.sound.is_stopped()? {
+                finished.push(index);
+                continue;
+            }
+
+            let max_distance = self
+                .limits
+                .get(&voice.sound_type)
+                .and_then(|limit| limit.max_distance);
+
+            if let (Some(max_distance), Some(position)) = (max_distance, voice.position) {
+                if Self::distance(position, listener) > max_distance {
+                    finished.push(index);
+                    culled.push(voice.sound.clone());
+                }
+            }
+        }
+
+        for index in finished.into_iter().rev() {
+            self.voices.remove(index);
+        }
+
+        Ok(culled)
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.voices.clear();
+    }
+
+    fn steal(
+        &self,
+        sound_type: Option<&str>,
+        priority: u32,
+        policy: StealPolicy,
+        listener: [f32; 3],
+        gain: &impl Fn(&str) -> f32,
+    ) -> Admission {
+        let candidates: Vec<&Voice> = self
+            .voices
+            .iter()
+            .filter(|voice| match sound_type {
+                Some(sound_type) => voice.sound_type == sound_type,
+                None => true,
+            })
+            .filter(|voice| voice.priority <= priority)
+            .collect();
+
+        // voices with the lowest priority are stolen first
+        let lowest_priority = match candidates.iter().map(|voice| voice.priority).min() {
+            Some(lowest_priority) => lowest_priority,
+            None => return Admission::Reject,
+        };
+
+        let candidates = candidates
+            .into_iter()
+            .filter(|voice| voice.priority == lowest_priority);
+
+        let victim = match policy {
+            StealPolicy::Reject => None,
+            StealPolicy::StealOldest => candidates.min_by_key(|voice| voice.started),
+            StealPolicy::StealQuietest => candidates.min_by(|lhs, rhs| {
+                let lhs_volume = Self::audibility(lhs, listener, gain);
+                let rhs_volume = Self::audibility(rhs, listener, gain);
+
+                lhs_volume
+                    .partial_cmp(&rhs_volume)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            }),
+        };
+
+        match victim {
+            Some(voice) => Admission::Steal(voice.sound.clone()),
+            None => Admission::Reject,
+        }
+    }
+
+    // estimated volume at the listener, OpenAL attenuates by the inverse distance by default
+    fn audibility(voice: &Voice, listener: [f32; 3], gain: &impl Fn(&str) -> f32) -> f32 {
+        let attenuation = match voice.position {
+            Some(position) => 1.0 / Self::distance(position, listener).max(1.0),
+            None => 1.0,
+        };
+
+        gain(&voice.sound_type) * attenuation
+    }
+
+    fn distance(lhs: [f32; 3], rhs: [f32; 3]) -> f32 {
+        let x = lhs[0] - rhs[0];
+        let y = lhs[1] - rhs[1];
+        let z = lhs[2] - rhs[2];
+
+        (x * x + y * y + z * z).sqrt()
+    }
+}
+
+#[test]
+fn voice_manager_admits_steals_and_culls() {
+    use super::audiobackend::{softwarebackend::SoftwareBackend, wavbuffer::WavBuffer};
+    use super::soundhandler::{SoundHandler, VolumeInfo};
+
+    fn stolen(admission: Admission) -> Option<Arc<Sound>> {
+        match admission {
+            Admission::Steal(sound) => Some(sound),
+            _ => None,
+        }
+    }
+
+    fn at(position: [f32; 3], priority: u32) -> PlayInfo {
+        PlayInfo {
+            position: Some(position),
+            priority,
+            ..PlayInfo::default()
+        }
+    }
+
+    let mut backend = SoftwareBackend::new(100).unwrap();
+    backend.insert_data("sound", WavBuffer::new(100, 1, vec![0.5; 100]).unwrap());
+
+    let mut sound_handler = SoundHandler::new(VolumeInfo::default(), Box::new(backend)).unwrap();
+
+    let sounds: Vec<Arc<Sound>> = (0..4)
+        .map(|_| sound_handler.load_sound("sound", "effects").unwrap())
+        .collect();
+
+    for sound in sounds.iter() {
+        sound.play(false).unwrap();
+    }
+
+    let listener = [0.0, 0.0, 0.0];
+    let gain = |_: &str| 1.0;
+
+    let mut voice_manager = VoiceManager::new();
+    voice_manager.set_limit(
+        "effects",
+        Some(VoiceLimit {
+            max_voices: 2,
+            policy: StealPolicy::StealOldest,
+            max_distance: Some(10.0),
+        }),
+    );
+
+    let info = at([5.0, 0.0, 0.0], 1);
+    assert!(matches!(
+        voice_manager.admit("effects", &info, listener, gain),
+        Admission::Play
+    ));
+    voice_manager.add(sounds[0].clone(), "effects", &info, Duration::from_secs(0));
+
+    let info = at([1.0, 0.0, 0.0], 0);
+    voice_manager.add(sounds[1].clone(), "effects", &info, Duration::from_secs(1));
+
+    // voices out of range are rejected, even if the limit isn't reached
+    assert!(matches!(
+        voice_manager.admit("effects", &at([20.0, 0.0, 0.0], 1), listener, gain),
+        Admission::Reject
+    ));
+
+    // the lowest priority is stolen first, even if it is younger
+    let victim = stolen(voice_manager.admit("effects", &at([0.0; 3], 1), listener, gain));
+    assert!(Arc::ptr_eq(&victim.unwrap(), &sounds[1]));
+
+    voice_manager.remove(&sounds[1]);
+    voice_manager.add(
+        sounds[2].clone(),
+        "effects",
+        &at([1.0, 0.0, 0.0], 1),
+        Duration::from_secs(2),
+    );
+
+    // only voices with the same or a lower priority can be stolen
+    assert!(matches!(
+        voice_manager.admit("effects", &at([0.0; 3], 0), listener, gain),
+        Admission::Reject
+    ));
+
+    let victim = stolen(voice_manager.admit("effects", &at([0.0; 3], 1), listener, gain));
+    assert!(Arc::ptr_eq(&victim.unwrap(), &sounds[0]));
+
+    // the voice further away is quieter
+    voice_manager.set_limit(
+        "effects",
+        Some(VoiceLimit {
+            max_voices: 2,
+            policy: StealPolicy::StealQuietest,
+            max_distance: Some(10.0),
+        }),
+    );
+
+    let victim = stolen(voice_manager.admit("effects", &at([0.0; 3], 1), listener, gain));
+    assert!(Arc::ptr_eq(&victim.unwrap(), &sounds[0]));
+
+    // the overall limit applies to sound types without a limit, across all sound types
+    let info = PlayInfo {
+        priority: 1,
+        ..PlayInfo::default()
+    };
+
+    voice_manager.set_max_voices(3);
+    voice_manager.add(sounds[3].clone(), "other", &info, Duration::from_secs(3));
+
+    let victim = stolen(voice_manager.admit("ui", &info, listener, gain));
+    assert!(Arc::ptr_eq(&victim.unwrap(), &sounds[0]));
+
+    // stopped voices are forgotten, voices out of range are returned to be stopped
+    sounds[3].stop().unwrap();
+
+    let culled = voice_manager.update([0.0, 0.0, 9.0]).unwrap();
+
+    assert_eq!(culled.len(), 1);
+    assert!(Arc::ptr_eq(&culled[0], &sounds[0]));
+    assert_eq!(voice_manager.voice_count(None), 1);
+    assert_eq!(voice_manager.voice_count(Some("effects")), 1);
+}
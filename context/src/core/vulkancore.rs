@@ -32,6 +32,7 @@ impl VulkanCore {
         let mut instance_extensions = InstanceExtensions::default();
         presentation_core.activate_vulkan_instance_extensions(&mut instance_extensions)?;
         instance_extensions.physical_device_properties2 = true;
+        instance_extensions.swapchain_colorspace = true;
 
         // create instance
         let instance = Instance::new(info, *vulkan_debug_info, instance_extensions)?;
@@ -77,6 +78,16 @@ impl VulkanCore {
                     VK_QUEUE_GRAPHICS_BIT | VK_QUEUE_COMPUTE_BIT,
                 )?;
 
+                (queue_info, physical_device)
+            }
+            PresentationBackend::Headless(_headless) => {
+                let physical_device = PhysicalDevice::new(instance.clone())?;
+
+                let queue_info = Queue::create_non_presentable_request_info(
+                    &physical_device,
+                    VK_QUEUE_GRAPHICS_BIT | VK_QUEUE_COMPUTE_BIT,
+                )?;
+
                 (queue_info, physical_device)
             }
         };
@@ -85,10 +96,13 @@ impl VulkanCore {
         let mut dev_exts = DeviceExtensions::default();
         presentation_core.activate_vulkan_device_extensions(&mut dev_exts, &physical_device)?;
         dev_exts.memory_requirements2 = true;
+        dev_exts.dedicated_allocation = true;
+        dev_exts.timeline_semaphore = true;
         dev_exts.nv_ray_tracing = true;
         dev_exts.amd_rasterization_order = true;
         dev_exts.descriptor_indexing = true;
         dev_exts.maintenance3 = true;
+        dev_exts.hdr_metadata = true;
 
         if vulkan_debug_info.renderdoc {
             dev_exts.debug_marker = true;
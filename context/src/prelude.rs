@@ -1,14 +1,30 @@
 // structures
 pub use crate::core::{
-    configs::WindowConfig, context::Context, osspecific::osspecific::OsSpecificConfig,
+    configs::WindowConfig,
+    context::Context,
+    framestats::{FrameStats, FrameTimeSummary, FrameTiming},
+    gameloop::{FrameTime, LoopMode},
+    osspecific::osspecific::OsSpecificConfig,
+};
+
+// audio
+pub use crate::core::{
+    audiobackend::{
+        nullbackend::NullBackend, softwarebackend::SoftwareBackend, wavbuffer::WavBuffer,
+        AudioBackend, Reverb, SourceId, SourceState,
+    },
+    mixer::{Ducking, FadeCurve, MixerBus, MASTER_BUS, MUSIC_BUS},
+    musicplayer::{MusicPlayer, MusicTransition, RepeatMode},
+    soundhandler::{Music, Sound, SoundHandler, VolumeInfo},
+    voicemanager::{PlayInfo, StealPolicy, VoiceLimit},
 };
 
 #[cfg(feature = "audio")]
-pub use crate::core::soundhandler::{Music, Sound, SoundHandler, VolumeInfo};
+pub use crate::core::audiobackend::openalbackend::OpenALBackend;
 
-// reexport ears presets (OpenAL)
+// reexport ears presets (OpenAL), they convert into `Reverb`
 #[cfg(feature = "audio")]
-pub use ears::{ReverbEffect, ReverbPreset};
+pub use ears::ReverbPreset;
 
 // render target
 pub use crate::utils::{
@@ -0,0 +1,78 @@
+use utilities::prelude::*;
+use vulkan_rs::prelude::*;
+
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy)]
+pub struct HeadlessCreateInfo {
+    pub width: u32,
+    pub height: u32,
+    pub image_count: usize,
+}
+
+impl Default for HeadlessCreateInfo {
+    fn default() -> Self {
+        HeadlessCreateInfo {
+            width: 800,
+            height: 600,
+            image_count: 2,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct HeadlessIntegration {
+    width: u32,
+    height: u32,
+    image_count: usize,
+}
+
+impl HeadlessIntegration {
+    pub fn new(create_info: &HeadlessCreateInfo) -> VerboseResult<HeadlessIntegration> {
+        if create_info.width == 0 || create_info.height == 0 {
+            create_error!(format!(
+                "invalid headless extent: {}x{}",
+                create_info.width, create_info.height
+            ));
+        }
+
+        if create_info.image_count == 0 {
+            create_error!("headless backend requires at least one image");
+        }
+
+        Ok(HeadlessIntegration {
+            width: create_info.width,
+            height: create_info.height,
+            image_count: create_info.image_count,
+        })
+    }
+
+    pub fn activate_vulkan_instance_extensions(
+        &self,
+        _extensions: &mut InstanceExtensions,
+    ) -> VerboseResult<()> {
+        // no surface, no additional instance extensions
+        Ok(())
+    }
+
+    pub fn activate_vulkan_device_extensions(
+        &self,
+        _extensions: &mut DeviceExtensions,
+        _physical_device: &Arc<PhysicalDevice>,
+    ) -> VerboseResult<()> {
+        // no swapchain, no additional device extensions
+        Ok(())
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn image_count(&self) -> usize {
+        self.image_count
+    }
+}
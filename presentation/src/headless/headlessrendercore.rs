@@ -0,0 +1,242 @@
+use crate::{prelude::*, renderbackend::RenderBackend, RenderCoreCreateInfo};
+
+use super::headlessintegration::HeadlessIntegration;
+
+use utilities::prelude::*;
+use vulkan_rs::prelude::*;
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering::SeqCst},
+    Arc, Mutex,
+};
+use std::time::Duration;
+
+pub struct HeadlessRenderCore {
+    format: VkFormat,
+
+    render_fence: Arc<Fence>,
+
+    render_backend: RenderBackend,
+
+    // index of the image, which was rendered most recently
+    current_image_index: AtomicUsize,
+
+    width: u32,
+    height: u32,
+}
+
+impl HeadlessRenderCore {
+    pub fn new(
+        headless: &HeadlessIntegration,
+        device: &Arc<Device>,
+        queue: &Arc<Mutex<Queue>>,
+        create_info: RenderCoreCreateInfo,
+    ) -> VerboseResult<(Self, TargetMode<()>)> {
+        let width = headless.width();
+        let height = headless.height();
+
+        // target images need to be transfer source, so that they can be read back
+        let usage = create_info.usage
+            | RenderBackend::required_image_usage()
+            | VK_IMAGE_USAGE_TRANSFER_SRC_BIT
+            | VK_IMAGE_USAGE_SAMPLED_BIT;
+
+        let images = Self::create_target_images(
+            width,
+            height,
+            headless.image_count(),
+            usage,
+            create_info.format,
+            device,
+            queue,
+        )?;
+
+        let render_backend = RenderBackend::new(
+            device,
+            queue,
+            TargetMode::Single(images),
+            VK_IMAGE_LAYOUT_TRANSFER_SRC_OPTIMAL,
+            1,
+        )?;
+
+        let headless_render_core = HeadlessRenderCore {
+            format: create_info.format,
+
+            render_fence: Fence::builder().build(device.clone())?,
+
+            render_backend,
+
+            current_image_index: AtomicUsize::new(headless.image_count() - 1),
+
+            width,
+            height,
+        };
+
+        Ok((headless_render_core, TargetMode::Single(())))
+    }
+
+    /// Returns the index of the image, that was rendered in the last call to `next_frame`
+    pub fn current_image_index(&self) -> usize {
+        self.current_image_index.load(SeqCst)
+    }
+
+    /// Returns the image, that was rendered in the last call to `next_frame`
+    pub fn current_image(&self) -> VerboseResult<Arc<Image>> {
+        let images = self.render_backend.images()?;
+
+        Ok(images.single()?[self.current_image_index()].clone())
+    }
+
+    #[inline]
+    fn create_target_images(
+        width: u32,
+        height: u32,
+        image_count: usize,
+        usage: VkImageUsageFlagBits,
+        format: VkFormat,
+        device: &Arc<Device>,
+        queue: &Arc<Mutex<Queue>>,
+    ) -> VerboseResult<Vec<Arc<Image>>> {
+        if !Image::check_configuration(device, VK_IMAGE_TILING_OPTIMAL, format, usage) {
+            create_error!(format!(
+                "wrong config: {:?}, {:?}, {:?}",
+                VK_IMAGE_TILING_OPTIMAL, format, usage
+            ));
+        }
+
+        let mut images = Vec::with_capacity(image_count);
+
+        for _ in 0..image_count {
+            let image = Image::empty(width, height, usage, VK_SAMPLE_COUNT_1_BIT)
+                .attach_sampler(Sampler::nearest_sampler().build(device)?)
+                .format(format)
+                .build(device, queue)?;
+
+            image.convert_layout(VK_IMAGE_LAYOUT_TRANSFER_SRC_OPTIMAL)?;
+
+            images.push(image);
+        }
+
+        Ok(images)
+    }
+}
+
+impl RenderCore for HeadlessRenderCore {
+    fn format(&self) -> VkFormat {
+        self.format
+    }
+
+    fn image_layout(&self) -> VkImageLayout {
+        self.render_backend.image_layout()
+    }
+
+    fn next_frame(&self) -> VerboseResult<bool> {
+        let image_index = (self.current_image_index() + 1) % self.render_backend.image_count();
+
+        let command_buffer = self
+            .render_backend
+            .render(TargetMode::Single(image_index), 0)?;
+
+        let submits = &[SubmitInfo::default().add_command_buffer(&command_buffer)];
+
+        {
+            let queue_lock = self.render_backend.queue().lock()?;
+
+            queue_lock.submit(Some(&self.render_fence), submits)?;
+        }
+
+        // make sure the image is completely rendered before handing it out
+        self.render_backend.device().wait_for_fences(
+            &[&self.render_fence],
+            true,
+            Duration::from_secs(10),
+        )?;
+        self.render_fence.reset();
+
+        self.current_image_index.store(image_index, SeqCst);
+
+        Ok(true)
+    }
+
+    fn set_clear_color(&self, color: [f32; 4]) -> VerboseResult<()> {
+        self.render_backend.set_clear_color(color)
+    }
+
+    // scene handling
+    fn add_scene(&self, scene: Arc<dyn TScene + Sync + Send>) -> VerboseResult<()> {
+        self.render_backend.add_scene(scene)
+    }
+
+    fn remove_scene(&self, scene: &Arc<dyn TScene + Sync + Send>) -> VerboseResult<()> {
+        self.render_backend.remove_scene(scene)
+    }
+
+    fn clear_scenes(&self) -> VerboseResult<()> {
+        self.render_backend.clear_scenes()
+    }
+
+    // post process handling
+    fn add_post_processing_routine(
+        &self,
+        post_process: Arc<dyn PostProcess + Sync + Send>,
+    ) -> VerboseResult<()> {
+        self.render_backend
+            .add_post_processing_routine(post_process)
+    }
+
+    fn remove_post_processing_routine(
+        &self,
+        post_process: &Arc<dyn PostProcess + Sync + Send>,
+    ) -> VerboseResult<()> {
+        self.render_backend
+            .remove_post_processing_routine(post_process)
+    }
+
+    fn clear_post_processing_routines(&self) -> VerboseResult<()> {
+        self.render_backend.clear_post_processing_routines()
+    }
+
+    // getter
+    fn image_count(&self) -> usize {
+        self.render_backend.image_count()
+    }
+
+    fn images(&self) -> VerboseResult<TargetMode<Vec<Arc<Image>>>> {
+        self.render_backend.images()
+    }
+
+    fn allocate_primary_buffer(&self) -> VerboseResult<Arc<CommandBuffer>> {
+        self.render_backend.allocate_primary_buffer()
+    }
+
+    fn allocate_secondary_buffer(&self) -> VerboseResult<Arc<CommandBuffer>> {
+        self.render_backend.allocate_secondary_buffer()
+    }
+
+    fn width(&self) -> u32 {
+        self.width
+    }
+
+    fn height(&self) -> u32 {
+        self.height
+    }
+
+    fn transformations(&self) -> VerboseResult<Option<(VRTransformations, VRTransformations)>> {
+        Ok(None)
+    }
+
+    fn gpu_profiler(&self) -> &Arc<GpuProfiler> {
+        self.render_backend.gpu_profiler()
+    }
+
+    fn capture_frame(&self) -> VerboseResult<TargetMode<image::RgbaImage>> {
+        self.render_backend
+            .capture_images(&TargetMode::Single(self.current_image_index()))
+    }
+}
+
+impl std::fmt::Debug for HeadlessRenderCore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HeadlessRenderCore {{ }}")
+    }
+}
@@ -0,0 +1,2 @@
+pub mod headlessintegration;
+pub mod headlessrendercore;
@@ -1,61 +1,450 @@
 use crate::input::guidirection::GuiDirection;
 
 use super::controlleraxis::ControllerAxis;
+use super::sdl2ext::*;
 
 use sdl2;
+use sdl2::sys::{SDL_GameController, SDL_GameControllerFromInstanceID, SDL_SensorType, SDL_bool};
 use utilities::prelude::*;
 
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControllerSensor {
+    /// angular velocity around x, y and z in radians per second
+    Gyroscope,
+    /// acceleration along x, y and z in meters per second squared, including gravity
+    Accelerometer,
+}
+
+impl ControllerSensor {
+    fn sdl_type(self) -> SDL_SensorType {
+        match self {
+            ControllerSensor::Gyroscope => SDL_SensorType::SDL_SENSOR_GYRO,
+            ControllerSensor::Accelerometer => SDL_SensorType::SDL_SENSOR_ACCEL,
+        }
+    }
+}
+
+/// Features of a controller, that not every device supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ControllerCapabilities {
+    pub rumble: bool,
+    pub trigger_rumble: bool,
+    pub led: bool,
+    pub gyroscope: bool,
+    pub accelerometer: bool,
+    pub touchpads: u32,
+}
+
+/// A finger on a controller touchpad, positions are normalized to [0, 1]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TouchpadFinger {
+    pub touchpad: u32,
+    pub finger: u32,
+    pub x: f32,
+    pub y: f32,
+    pub pressure: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum TouchpadChange {
+    Down(TouchpadFinger),
+    Motion(TouchpadFinger),
+    Up(TouchpadFinger),
+}
+
 pub struct Controller {
-    _sdl2_controller: sdl2::controller::GameController,
+    // `None` for controllers of an input replay, `raw` is null then
+    sdl2_controller: Option<sdl2::controller::GameController>,
+    raw: *mut SDL_GameController,
+    capabilities: ControllerCapabilities,
+
     deadzone: f32,
     name: String,
+    guid: String,
     id: u32,
+    player_slot: usize,
 
     controller_axis: ControllerAxis,
 
     last_direction: GuiDirection,
+
+    touchpad_fingers: Vec<TouchpadFinger>,
+    sensor_data: Vec<(ControllerSensor, [f32; 3])>,
 }
 
 impl Controller {
+    /// `device_index` is the index SDL reports when the device is added, it differs from
+    /// the instance id, that is used in all further events
     pub fn new(
         controller_subsystem: &sdl2::GameControllerSubsystem,
-        id: u32,
+        device_index: u32,
         deadzone: f32,
     ) -> VerboseResult<Controller> {
-        if controller_subsystem.is_game_controller(id) {
-            let test = controller_subsystem.open(id);
+        if controller_subsystem.is_game_controller(device_index) {
+            let test = controller_subsystem.open(device_index);
 
-            let sdl2_controller = match test {
+            let mut sdl2_controller = match test {
                 Ok(controller) => controller,
                 Err(error) => create_error!(error.to_string()),
             };
 
+            let raw =
+                unsafe { SDL_GameControllerFromInstanceID(sdl2_controller.instance_id() as i32) };
+
+            if raw.is_null() {
+                create_error!("failed getting raw game controller handle");
+            }
+
+            // there are no queries for rumble support, but stopping succeeds only if supported
+            let capabilities = unsafe {
+                ControllerCapabilities {
+                    rumble: sdl2_controller.set_rumble(0, 0, 0).is_ok(),
+                    trigger_rumble: SDL_GameControllerRumbleTriggers(raw, 0, 0, 0) == 0,
+                    led: SDL_GameControllerHasLED(raw) == SDL_bool::SDL_TRUE,
+                    gyroscope: SDL_GameControllerHasSensor(raw, SDL_SensorType::SDL_SENSOR_GYRO)
+                        == SDL_bool::SDL_TRUE,
+                    accelerometer: SDL_GameControllerHasSensor(
+                        raw,
+                        SDL_SensorType::SDL_SENSOR_ACCEL,
+                    ) == SDL_bool::SDL_TRUE,
+                    touchpads: SDL_GameControllerGetNumTouchpads(raw).max(0) as u32,
+                }
+            };
+
+            let guid = match controller_subsystem
+                .sdl()
+                .joystick()?
+                .device_guid(device_index)
+            {
+                Ok(guid) => guid.string(),
+                Err(error) => create_error!(error.to_string()),
+            };
+
             let controller_axis = ControllerAxis::default();
 
             Ok(Controller {
                 name: sdl2_controller.name(),
-                id,
+                guid,
+                id: sdl2_controller.instance_id(),
+                player_slot: 0,
                 deadzone,
 
-                _sdl2_controller: sdl2_controller,
+                sdl2_controller: Some(sdl2_controller),
+                raw,
+                capabilities,
 
                 controller_axis,
 
                 last_direction: GuiDirection::None,
+
+                touchpad_fingers: Vec::new(),
+                sensor_data: Vec::new(),
             })
         } else {
             create_error!("controller is not a game pad")
         }
     }
 
+    /// Controller without a device, that is driven by an input replay
+    ///
+    /// It has no capabilities, so rumble, LED and sensors are unavailable
+    pub(crate) fn replayed(
+        id: u32,
+        name: String,
+        guid: String,
+        player_slot: usize,
+        deadzone: f32,
+    ) -> Controller {
+        Controller {
+            sdl2_controller: None,
+            raw: std::ptr::null_mut(),
+            capabilities: ControllerCapabilities::default(),
+
+            deadzone,
+            name,
+            guid,
+            id,
+            player_slot,
+
+            controller_axis: ControllerAxis::default(),
+
+            last_direction: GuiDirection::None,
+
+            touchpad_fingers: Vec::new(),
+            sensor_data: Vec::new(),
+        }
+    }
+
     pub fn name(&self) -> &String {
         &self.name
     }
 
+    /// Identifies the type of the device, it is the same for every controller of the same
+    /// model and stays the same across reconnects, e.g. to remember bindings
+    pub fn guid(&self) -> &str {
+        &self.guid
+    }
+
+    /// Instance id of the controller, only valid as long as the controller is connected
     pub fn id(&self) -> u32 {
         self.id
     }
 
+    /// Player slot, starting at 0
+    ///
+    /// A reconnected controller gets its previous slot back, if that slot is still free
+    pub fn player_slot(&self) -> usize {
+        self.player_slot
+    }
+
+    pub(crate) fn set_player_slot(&mut self, player_slot: usize) {
+        self.player_slot = player_slot;
+    }
+
+    pub fn capabilities(&self) -> ControllerCapabilities {
+        self.capabilities
+    }
+
+    /// Strength of the low and high frequency motors in [0, 1], zero stops the rumble
+    pub fn rumble(
+        &mut self,
+        low_frequency: f32,
+        high_frequency: f32,
+        duration: Duration,
+    ) -> VerboseResult<()> {
+        if !self.capabilities.rumble {
+            create_error!(format!("controller ({}) has no rumble support", self.name));
+        }
+
+        let sdl2_controller = match self.sdl2_controller.as_mut() {
+            Some(sdl2_controller) => sdl2_controller,
+            None => create_error!(format!("controller ({}) has no device", self.name)),
+        };
+
+        if let Err(err) = sdl2_controller.set_rumble(
+            Self::motor_strength(low_frequency),
+            Self::motor_strength(high_frequency),
+            Self::duration_ms(duration),
+        ) {
+            create_error!(err.to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Strength of the motors in the left and right trigger in [0, 1]
+    pub fn rumble_triggers(
+        &mut self,
+        left: f32,
+        right: f32,
+        duration: Duration,
+    ) -> VerboseResult<()> {
+        if !self.capabilities.trigger_rumble {
+            create_error!(format!(
+                "controller ({}) has no trigger rumble support",
+                self.name
+            ));
+        }
+
+        let result = unsafe {
+            SDL_GameControllerRumbleTriggers(
+                self.raw,
+                Self::motor_strength(left),
+                Self::motor_strength(right),
+                Self::duration_ms(duration),
+            )
+        };
+
+        if result != 0 {
+            create_error!(format!(
+                "failed rumbling triggers of controller ({})",
+                self.name
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn set_led(&mut self, red: u8, green: u8, blue: u8) -> VerboseResult<()> {
+        if !self.capabilities.led {
+            create_error!(format!("controller ({}) has no LED", self.name));
+        }
+
+        if unsafe { SDL_GameControllerSetLED(self.raw, red, green, blue) } != 0 {
+            create_error!(format!("failed setting LED of controller ({})", self.name));
+        }
+
+        Ok(())
+    }
+
+    pub fn has_sensor(&self, sensor: ControllerSensor) -> bool {
+        match sensor {
+            ControllerSensor::Gyroscope => self.capabilities.gyroscope,
+            ControllerSensor::Accelerometer => self.capabilities.accelerometer,
+        }
+    }
+
+    /// Sensors are disabled by default, enabled sensors are reported with
+    /// `PresentationEventType::ControllerSensor`
+    pub fn set_sensor_enabled(
+        &mut self,
+        sensor: ControllerSensor,
+        enabled: bool,
+    ) -> VerboseResult<()> {
+        if !self.has_sensor(sensor) {
+            create_error!(format!("controller ({}) has no {:?}", self.name, sensor));
+        }
+
+        let sdl_enabled = if enabled {
+            SDL_bool::SDL_TRUE
+        } else {
+            SDL_bool::SDL_FALSE
+        };
+
+        if unsafe { SDL_GameControllerSetSensorEnabled(self.raw, sensor.sdl_type(), sdl_enabled) }
+            != 0
+        {
+            create_error!(format!(
+                "failed enabling {:?} of controller ({})",
+                sensor, self.name
+            ));
+        }
+
+        self.sensor_data.retain(|(s, _)| *s != sensor);
+
+        if enabled {
+            self.sensor_data.push((sensor, [0.0; 3]));
+        }
+
+        Ok(())
+    }
+
+    pub fn is_sensor_enabled(&self, sensor: ControllerSensor) -> bool {
+        self.sensor_data.iter().any(|(s, _)| *s == sensor)
+    }
+
+    /// Latest reading of an enabled sensor
+    pub fn sensor_data(&self, sensor: ControllerSensor) -> Option<[f32; 3]> {
+        self.sensor_data
+            .iter()
+            .find(|(s, _)| *s == sensor)
+            .map(|(_, data)| *data)
+    }
+
+    /// Fingers, that currently touch a touchpad
+    pub fn touchpad_fingers(&self) -> &[TouchpadFinger] {
+        &self.touchpad_fingers
+    }
+
+    /// Reads the enabled sensors, returns the ones that changed
+    pub(crate) fn update_sensors(&mut self) -> Vec<(ControllerSensor, [f32; 3])> {
+        let mut changed = Vec::new();
+
+        for (sensor, data) in self.sensor_data.iter_mut() {
+            let mut new_data = [0.0; 3];
+
+            let result = unsafe {
+                SDL_GameControllerGetSensorData(
+                    self.raw,
+                    sensor.sdl_type(),
+                    new_data.as_mut_ptr(),
+                    3,
+                )
+            };
+
+            if result == 0 && new_data != *data {
+                *data = new_data;
+                changed.push((*sensor, new_data));
+            }
+        }
+
+        changed
+    }
+
+    /// Compares the touchpad state with the previous one
+    pub(crate) fn update_touchpads(&mut self) -> Vec<TouchpadChange> {
+        if self.capabilities.touchpads == 0 {
+            return Vec::new();
+        }
+
+        let mut fingers = Vec::new();
+
+        for touchpad in 0..self.capabilities.touchpads as i32 {
+            let finger_count =
+                unsafe { SDL_GameControllerGetNumTouchpadFingers(self.raw, touchpad) };
+
+            for finger in 0..finger_count {
+                let mut state = 0;
+                let mut x = 0.0;
+                let mut y = 0.0;
+                let mut pressure = 0.0;
+
+                let result = unsafe {
+                    SDL_GameControllerGetTouchpadFinger(
+                        self.raw,
+                        touchpad,
+                        finger,
+                        &mut state,
+                        &mut x,
+                        &mut y,
+                        &mut pressure,
+                    )
+                };
+
+                if result == 0 && state != 0 {
+                    fingers.push(TouchpadFinger {
+                        touchpad: touchpad as u32,
+                        finger: finger as u32,
+                        x,
+                        y,
+                        pressure,
+                    });
+                }
+            }
+        }
+
+        let same_finger = |lhs: &TouchpadFinger, rhs: &TouchpadFinger| {
+            lhs.touchpad == rhs.touchpad && lhs.finger == rhs.finger
+        };
+
+        let mut changes = Vec::new();
+
+        for old_finger in self.touchpad_fingers.iter() {
+            if !fingers.iter().any(|finger| same_finger(finger, old_finger)) {
+                changes.push(TouchpadChange::Up(*old_finger));
+            }
+        }
+
+        for finger in fingers.iter() {
+            match self
+                .touchpad_fingers
+                .iter()
+                .find(|old_finger| same_finger(finger, old_finger))
+            {
+                Some(old_finger) => {
+                    if old_finger != finger {
+                        changes.push(TouchpadChange::Motion(*finger));
+                    }
+                }
+                None => changes.push(TouchpadChange::Down(*finger)),
+            }
+        }
+
+        self.touchpad_fingers = fingers;
+
+        changes
+    }
+
+    fn motor_strength(strength: f32) -> u16 {
+        (strength.clamp(0.0, 1.0) * u16::MAX as f32) as u16
+    }
+
+    fn duration_ms(duration: Duration) -> u32 {
+        duration.as_millis().min(u32::MAX as u128) as u32
+    }
+
     fn check_direction(x: f32, y: f32, deadzone: f32) -> GuiDirection {
         let thresh_x = if x.abs() < deadzone { 0.0 } else { x };
         let thresh_y = if y.abs() < deadzone { 0.0 } else { y };
@@ -143,6 +532,10 @@ impl Controller {
         self.controller_axis
     }
 
+    pub fn deadzone(&self) -> f32 {
+        self.deadzone
+    }
+
     pub fn direction(&self) -> GuiDirection {
         self.last_direction
     }
@@ -152,8 +545,8 @@ impl std::fmt::Debug for Controller {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "Point {{ name: {}, deadzone: {}, id: {} }}",
-            self.name, self.deadzone, self.id
+            "Controller {{ name: {}, guid: {}, deadzone: {}, id: {}, player_slot: {} }}",
+            self.name, self.guid, self.deadzone, self.id, self.player_slot
         )
     }
 }
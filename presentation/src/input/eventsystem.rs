@@ -1,19 +1,26 @@
 use sdl2;
 use sdl2::controller::Button;
 use sdl2::event::{Event, EventType as SdlEventType};
-use sdl2::keyboard::Keycode;
-use sdl2::mouse::{MouseButton as SdlMouseButton, MouseUtil};
+use sdl2::keyboard::{Keycode, Mod, Scancode, TextInputUtil};
+use sdl2::mouse::{MouseButton as SdlMouseButton, MouseUtil, MouseWheelDirection};
+use sdl2::rect::Rect;
 use sdl2::EventPump;
 use sdl2::EventSubsystem;
 use sdl2::GameControllerSubsystem;
 use sdl2::Sdl;
+use sdl2::VideoSubsystem;
 
 use utilities::prelude::*;
 
 use std::ops::Deref;
-use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard};
-
-use super::controller::Controller;
+use std::sync::{
+    atomic::{AtomicBool, Ordering::SeqCst},
+    Arc, Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard,
+};
+
+use super::controller::{Controller, ControllerSensor, TouchpadChange, TouchpadFinger};
+use super::inputmap::{ActionState, InputMap};
+use super::inputrecording::{InputRecorder, InputReplay, RecordedEvent};
 use super::mousebutton::MouseButton;
 
 #[derive(Debug)]
@@ -22,46 +29,106 @@ pub enum PresentationEventType {
     MouseMotion(u32, u32),
     MouseButtonDown(MouseButton),
     MouseButtonUp(MouseButton),
-    MouseWheel(),
+    /// positive `y` scrolls away from the user, positive `x` to the right
+    MouseWheel {
+        x: i32,
+        y: i32,
+    },
 
     // keyboard events
-    KeyDown(Keycode),
-    KeyUp(Keycode),
+    /// `repeat` is only set, if key repeat is enabled via `EventSystem::set_key_repeat`
+    KeyDown {
+        keycode: Keycode,
+        scancode: Option<Scancode>,
+        modifiers: Mod,
+        repeat: bool,
+    },
+    KeyUp {
+        keycode: Keycode,
+        scancode: Option<Scancode>,
+        modifiers: Mod,
+    },
+
+    // text events, only sent while text input is active
+    /// committed text, possibly composed by an IME
+    TextInput(String),
+    /// text that is currently being composed by an IME, `start` and `length` describe the cursor
+    TextEditing {
+        text: String,
+        start: i32,
+        length: i32,
+    },
 
     // controller events
     ControllerAxis(Arc<RwLock<Controller>>),
-    ControllerButtonDown(Button),
-    ControllerButtonUp(Button),
+    ControllerButtonDown(Arc<RwLock<Controller>>, Button),
+    ControllerButtonUp(Arc<RwLock<Controller>>, Button),
     ControllerAdded(Arc<RwLock<Controller>>),
     ControllerRemoved(Arc<RwLock<Controller>>),
+    ControllerTouchpadDown(Arc<RwLock<Controller>>, TouchpadFinger),
+    ControllerTouchpadMotion(Arc<RwLock<Controller>>, TouchpadFinger),
+    ControllerTouchpadUp(Arc<RwLock<Controller>>, TouchpadFinger),
+    /// only sent for sensors, that were enabled with `Controller::set_sensor_enabled`
+    ControllerSensor(Arc<RwLock<Controller>>, ControllerSensor, [f32; 3]),
+
+    // input map events
+    /// sent after the raw event, that changed the state of an action or axis of the `InputMap`
+    Action(String, ActionState),
 }
 
 pub struct EventSystem {
+    // only present, if a window was created
+    video_subsystem: Option<Mutex<VideoSubsystem>>,
     event_pump: RwLock<EventPump>,
     mouse: Mutex<MouseUtil>,
     controller_subsystem: Mutex<GameControllerSubsystem>,
     event_subsystem: Mutex<EventSubsystem>,
 
     controller_axis_deadzone: RwLock<f32>,
+    key_repeat: AtomicBool,
 
     selected_controller: RwLock<Option<Arc<RwLock<Controller>>>>,
     connected_controllers: RwLock<Vec<Arc<RwLock<Controller>>>>,
+    player_slots: RwLock<PlayerSlots>,
+
+    // forward events of every controller instead of only the selected one
+    all_controller_events: AtomicBool,
+    controller_enabled: AtomicBool,
+
+    input_map: RwLock<InputMap>,
+
+    input_recorder: Mutex<Option<InputRecorder>>,
+    input_replay: Mutex<Option<InputReplay>>,
 
     event_callback: RwLock<Box<dyn Fn(PresentationEventType) -> VerboseResult<()> + Send + Sync>>,
 }
 
 impl EventSystem {
-    pub fn new(sdl2_context: &Sdl) -> VerboseResult<EventSystem> {
+    pub fn new(
+        sdl2_context: &Sdl,
+        video_subsystem: Option<VideoSubsystem>,
+    ) -> VerboseResult<EventSystem> {
         let event_system = EventSystem {
+            video_subsystem: video_subsystem.map(Mutex::new),
             event_pump: RwLock::new(sdl2_context.event_pump()?),
             mouse: Mutex::new(sdl2_context.mouse()),
             controller_subsystem: Mutex::new(sdl2_context.game_controller()?),
             event_subsystem: Mutex::new(sdl2_context.event()?),
 
             controller_axis_deadzone: RwLock::new(0.25),
+            key_repeat: AtomicBool::new(false),
 
             selected_controller: RwLock::new(None),
             connected_controllers: RwLock::new(Vec::new()),
+            player_slots: RwLock::new(PlayerSlots::default()),
+
+            all_controller_events: AtomicBool::new(false),
+            controller_enabled: AtomicBool::new(false),
+
+            input_map: RwLock::new(InputMap::new()),
+
+            input_recorder: Mutex::new(None),
+            input_replay: Mutex::new(None),
 
             event_callback: RwLock::new(Box::new(move |_| Ok(()))),
         };
@@ -82,12 +149,71 @@ impl EventSystem {
         Ok(())
     }
 
+    pub fn input_map(&self) -> VerboseResult<RwLockWriteGuard<'_, InputMap>> {
+        Ok(self.input_map.write()?)
+    }
+
+    /// Replaces the input map, e.g. with one loaded by `InputMap::load`
+    pub fn set_input_map(&self, input_map: InputMap) -> VerboseResult<()> {
+        *self.input_map.write()? = input_map;
+
+        Ok(())
+    }
+
+    /// Writes every event with its frame index and timestamp to `file_name`, until
+    /// `stop_recording` is called or the event system is dropped
+    pub fn start_recording(&self, file_name: &str) -> VerboseResult<()> {
+        let mut recorder = InputRecorder::new(file_name)?;
+
+        // already connected controllers are needed to replay their events
+        for controller in self.connected_controllers.read()?.iter() {
+            recorder.record(&RecordedEvent::from_event(
+                &PresentationEventType::ControllerAdded(controller.clone()),
+            )?)?;
+        }
+
+        *self.input_recorder.lock()? = Some(recorder);
+
+        Ok(())
+    }
+
+    pub fn stop_recording(&self) -> VerboseResult<()> {
+        *self.input_recorder.lock()? = None;
+
+        Ok(())
+    }
+
+    pub fn is_recording(&self) -> VerboseResult<bool> {
+        Ok(self.input_recorder.lock()?.is_some())
+    }
+
+    /// Feeds the events of a recording into the callback instead of the ones from SDL
+    ///
+    /// Connected controllers are replaced by the recorded ones. `poll_events` returns false,
+    /// once the replay is finished.
+    pub fn start_replay(&self, file_name: &str) -> VerboseResult<()> {
+        let replay = InputReplay::new(file_name)?;
+
+        self.connected_controllers.write()?.clear();
+        *self.selected_controller.write()? = None;
+
+        *self.input_replay.lock()? = Some(replay);
+
+        Ok(())
+    }
+
+    /// false once the replay is finished
+    pub fn is_replaying(&self) -> VerboseResult<bool> {
+        Ok(self.input_replay.lock()?.is_some())
+    }
+
     pub fn enable_mouse(&self) -> VerboseResult<()> {
         let mut event_pump = self.event_pump.write()?;
 
         event_pump.enable_event(SdlEventType::MouseMotion);
         event_pump.enable_event(SdlEventType::MouseButtonDown);
         event_pump.enable_event(SdlEventType::MouseButtonUp);
+        event_pump.enable_event(SdlEventType::MouseWheel);
 
         self.mouse.lock()?.show_cursor(true);
 
@@ -100,6 +226,7 @@ impl EventSystem {
         event_pump.disable_event(SdlEventType::MouseMotion);
         event_pump.disable_event(SdlEventType::MouseButtonDown);
         event_pump.disable_event(SdlEventType::MouseButtonUp);
+        event_pump.disable_event(SdlEventType::MouseWheel);
 
         self.mouse.lock()?.show_cursor(false);
 
@@ -111,6 +238,8 @@ impl EventSystem {
 
         event_pump.enable_event(SdlEventType::KeyUp);
         event_pump.enable_event(SdlEventType::KeyDown);
+        event_pump.enable_event(SdlEventType::TextInput);
+        event_pump.enable_event(SdlEventType::TextEditing);
 
         Ok(())
     }
@@ -121,10 +250,61 @@ impl EventSystem {
         event_pump.disable_event(SdlEventType::KeyUp);
 
         event_pump.disable_event(SdlEventType::KeyDown);
+        event_pump.disable_event(SdlEventType::TextInput);
+        event_pump.disable_event(SdlEventType::TextEditing);
 
         Ok(())
     }
 
+    /// Repeated key down events are sent while a key is held, disabled by default
+    pub fn set_key_repeat(&self, key_repeat: bool) {
+        self.key_repeat.store(key_repeat, SeqCst);
+    }
+
+    pub fn key_repeat(&self) -> bool {
+        self.key_repeat.load(SeqCst)
+    }
+
+    /// Starts sending `TextInput` and `TextEditing` events, e.g. when a text field gains focus
+    ///
+    /// On some platforms this shows an on-screen keyboard or an IME. Text input needs a window,
+    /// without one an error is returned.
+    pub fn start_text_input(&self) -> VerboseResult<()> {
+        self.text_input()?.start();
+
+        Ok(())
+    }
+
+    pub fn stop_text_input(&self) -> VerboseResult<()> {
+        self.text_input()?.stop();
+
+        Ok(())
+    }
+
+    pub fn is_text_input_active(&self) -> VerboseResult<bool> {
+        Ok(self.text_input()?.is_active())
+    }
+
+    /// Area of the text field in window coordinates, used to place the IME candidate list
+    pub fn set_text_input_rect(
+        &self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+    ) -> VerboseResult<()> {
+        self.text_input()?.set_rect(Rect::new(x, y, width, height));
+
+        Ok(())
+    }
+
+    fn text_input(&self) -> VerboseResult<TextInputUtil> {
+        match &self.video_subsystem {
+            Some(video_subsystem) => Ok(video_subsystem.lock()?.text_input()),
+            None => create_error!("text input requires a window"),
+        }
+    }
+
     pub fn enable_controller(&self) -> VerboseResult<()> {
         let mut event_pump = self.event_pump.write()?;
 
@@ -132,6 +312,8 @@ impl EventSystem {
         event_pump.enable_event(SdlEventType::ControllerButtonDown);
         event_pump.enable_event(SdlEventType::ControllerButtonUp);
 
+        self.controller_enabled.store(true, SeqCst);
+
         Ok(())
     }
 
@@ -142,6 +324,8 @@ impl EventSystem {
         event_pump.disable_event(SdlEventType::ControllerButtonDown);
         event_pump.disable_event(SdlEventType::ControllerButtonUp);
 
+        self.controller_enabled.store(false, SeqCst);
+
         Ok(())
     }
 
@@ -159,7 +343,21 @@ impl EventSystem {
     }
 
     pub fn poll_events(&self) -> VerboseResult<bool> {
-        let mut controller_axis_changed = false;
+        let running = if self.is_replaying()? {
+            self.poll_replay()?
+        } else {
+            self.poll_sdl_events()?
+        };
+
+        if let Some(recorder) = self.input_recorder.lock()?.as_mut() {
+            recorder.next_frame()?;
+        }
+
+        Ok(running)
+    }
+
+    fn poll_sdl_events(&self) -> VerboseResult<bool> {
+        let mut changed_controllers: Vec<Arc<RwLock<Controller>>> = Vec::new();
         let mut event_pump = self.event_pump.write()?;
 
         for event in event_pump.poll_iter() {
@@ -167,9 +365,7 @@ impl EventSystem {
                 Event::Quit { .. } => return Ok(false),
                 // ----------------- Mouse Events ---------------------
                 Event::MouseMotion { x, y, .. } => {
-                    self.event_callback.read()?(PresentationEventType::MouseMotion(
-                        x as u32, y as u32,
-                    ))?;
+                    self.dispatch(PresentationEventType::MouseMotion(x as u32, y as u32))?;
                 }
                 Event::MouseButtonDown { mouse_btn, .. } => {
                     let mouse_button = match mouse_btn {
@@ -181,9 +377,7 @@ impl EventSystem {
                         SdlMouseButton::Unknown => continue,
                     };
 
-                    self.event_callback.read()?(PresentationEventType::MouseButtonDown(
-                        mouse_button,
-                    ))?;
+                    self.dispatch(PresentationEventType::MouseButtonDown(mouse_button))?;
                 }
                 Event::MouseButtonUp { mouse_btn, .. } => {
                     let mouse_button = match mouse_btn {
@@ -195,40 +389,79 @@ impl EventSystem {
                         SdlMouseButton::Unknown => continue,
                     };
 
-                    self.event_callback.read()?(PresentationEventType::MouseButtonUp(
-                        mouse_button,
-                    ))?;
+                    self.dispatch(PresentationEventType::MouseButtonUp(mouse_button))?;
                 }
-                Event::MouseWheel { .. } => {
-                    println!("Mouse Wheel event is currently not implemented!");
+                Event::MouseWheel {
+                    x, y, direction, ..
+                } => {
+                    // flipped devices report inverted deltas
+                    let (x, y) = match direction {
+                        MouseWheelDirection::Flipped => (-x, -y),
+                        _ => (x, y),
+                    };
+
+                    self.dispatch(PresentationEventType::MouseWheel { x, y })?;
                 }
                 // ------------------- Key Events ---------------------
                 Event::KeyDown {
-                    keycode, repeat, ..
+                    keycode,
+                    scancode,
+                    keymod,
+                    repeat,
+                    ..
                 } => {
-                    if repeat {
+                    if repeat && !self.key_repeat() {
                         continue;
                     }
 
                     if let Some(keycode) = keycode {
-                        self.event_callback.read()?(PresentationEventType::KeyDown(keycode))?;
+                        self.dispatch(PresentationEventType::KeyDown {
+                            keycode,
+                            scancode,
+                            modifiers: keymod,
+                            repeat,
+                        })?;
                     }
                 }
                 Event::KeyUp {
-                    keycode, repeat, ..
+                    keycode,
+                    scancode,
+                    keymod,
+                    repeat,
+                    ..
                 } => {
                     if repeat {
                         continue;
                     }
 
                     if let Some(keycode) = keycode {
-                        self.event_callback.read()?(PresentationEventType::KeyUp(keycode))?;
+                        self.dispatch(PresentationEventType::KeyUp {
+                            keycode,
+                            scancode,
+                            modifiers: keymod,
+                        })?;
                     }
                 }
+                // ------------------- Text Events --------------------
+                Event::TextInput { text, .. } => {
+                    self.dispatch(PresentationEventType::TextInput(text))?;
+                }
+                Event::TextEditing {
+                    text,
+                    start,
+                    length,
+                    ..
+                } => {
+                    self.dispatch(PresentationEventType::TextEditing {
+                        text,
+                        start,
+                        length,
+                    })?;
+                }
 
                 // --------------- Controller Events -------------------
                 Event::ControllerDeviceAdded { which, .. } => {
-                    if let Ok(controller) = Controller::new(
+                    if let Ok(mut controller) = Controller::new(
                         self.controller_subsystem.lock()?.deref(),
                         which as u32,
                         self.controller_axis_deadzone.read()?.clone(),
@@ -238,6 +471,10 @@ impl EventSystem {
 
                             let mut selected_controller = self.selected_controller.write()?;
 
+                            controller.set_player_slot(
+                                self.player_slots.write()?.assign(controller.guid()),
+                            );
+
                             let arc_controller = Arc::new(RwLock::new(controller));
 
                             connected_controllers.push(arc_controller.clone());
@@ -249,9 +486,7 @@ impl EventSystem {
                             arc_controller
                         };
 
-                        self.event_callback.read()?(PresentationEventType::ControllerAdded(
-                            controller,
-                        ))?;
+                        self.dispatch(PresentationEventType::ControllerAdded(controller))?;
                     }
                 }
                 Event::ControllerDeviceRemoved { which, .. } => {
@@ -267,18 +502,29 @@ impl EventSystem {
 
                         let mut connected_controllers = self.connected_controllers.write()?;
 
-                        let mut remove_index = 0;
+                        let mut remove_index = None;
 
                         for (i, controller_cell) in connected_controllers.iter().enumerate() {
                             let controller = controller_cell.read()?;
                             if controller.id() == which {
-                                remove_index = i;
+                                remove_index = Some(i);
                                 break;
                             }
                         }
 
+                        let remove_index = match remove_index {
+                            Some(index) => index,
+                            None => continue,
+                        };
+
                         let removed_controller = connected_controllers.swap_remove(remove_index);
 
+                        // the slot stays reserved for this controller, until it is reconnected
+                        // or another controller needs it
+                        self.player_slots
+                            .write()?
+                            .release(removed_controller.read()?.player_slot());
+
                         // if we removed the selected controller, take the controller at the first position if possible
                         if selected_controller.is_none() && !connected_controllers.is_empty() {
                             *selected_controller = Some(connected_controllers[0].clone());
@@ -287,93 +533,358 @@ impl EventSystem {
                         removed_controller
                     };
 
-                    self.event_callback.read()?(PresentationEventType::ControllerRemoved(
-                        removed_controller,
-                    ))?;
+                    self.dispatch(PresentationEventType::ControllerRemoved(removed_controller))?;
                 }
-                // maybe make use of `which`, for support of multiple controllers
                 Event::ControllerButtonDown { button, which, .. } => {
-                    // only call back if the selected controller pressed a button
-                    match self.selected_controller.read()?.as_ref() {
-                        Some(selected_controller) => {
-                            if selected_controller.read()?.id() != which {
-                                continue;
-                            }
-                        }
-                        None => continue,
+                    if let Some(controller) = self.event_controller(which)? {
+                        self.dispatch(PresentationEventType::ControllerButtonDown(
+                            controller, button,
+                        ))?;
                     }
-
-                    self.event_callback.read()?(PresentationEventType::ControllerButtonDown(
-                        button,
-                    ))?;
                 }
-                // maybe make use of `which`, for support of multiple controllers
                 Event::ControllerButtonUp { button, which, .. } => {
-                    // only call back if the selected controller released a button
-                    match self.selected_controller.read()?.as_ref() {
-                        Some(selected_controller) => {
-                            if selected_controller.read()?.id() != which {
-                                continue;
+                    if let Some(controller) = self.event_controller(which)? {
+                        self.dispatch(PresentationEventType::ControllerButtonUp(
+                            controller, button,
+                        ))?;
+                    }
+                }
+                Event::ControllerAxisMotion {
+                    axis, value, which, ..
+                } => {
+                    if let Some(controller_cell) = self.event_controller(which)? {
+                        {
+                            let mut controller = controller_cell.write()?;
+
+                            // 1 / 32768 = 0,000030518
+                            let normalized = value as f32 * 0.000_030_518;
+
+                            match axis {
+                                sdl2::controller::Axis::LeftX => {
+                                    controller.set_left_x(normalized);
+                                }
+                                sdl2::controller::Axis::RightX => {
+                                    controller.set_right_x(normalized);
+                                }
+                                sdl2::controller::Axis::LeftY => {
+                                    controller.set_left_y(-normalized);
+                                }
+                                sdl2::controller::Axis::RightY => {
+                                    controller.set_right_y(normalized);
+                                }
+                                sdl2::controller::Axis::TriggerLeft => {
+                                    controller.set_left_trigger(normalized);
+                                }
+                                sdl2::controller::Axis::TriggerRight => {
+                                    controller.set_right_trigger(normalized);
+                                }
                             }
                         }
-                        None => continue,
+
+                        // report every controller only once per poll
+                        if changed_controllers
+                            .iter()
+                            .find(|c| Arc::ptr_eq(c, &controller_cell))
+                            .is_none()
+                        {
+                            changed_controllers.push(controller_cell);
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        for controller in changed_controllers {
+            self.dispatch(PresentationEventType::ControllerAxis(controller))?;
+        }
+
+        if self.controller_enabled.load(SeqCst) {
+            self.poll_touchpads_and_sensors()?;
+        }
+
+        Ok(true)
+    }
+
+    /// By default only events of the active controller are forwarded, with this enabled
+    /// events of every connected controller are, e.g. for local multiplayer
+    pub fn set_all_controller_events(&self, all_controller_events: bool) {
+        self.all_controller_events
+            .store(all_controller_events, SeqCst);
+    }
+
+    pub fn all_controller_events(&self) -> bool {
+        self.all_controller_events.load(SeqCst)
+    }
+
+    /// Connected controller, that occupies `player_slot`
+    pub fn controller_by_slot(
+        &self,
+        player_slot: usize,
+    ) -> VerboseResult<Option<Arc<RwLock<Controller>>>> {
+        for controller in self.connected_controllers.read()?.iter() {
+            if controller.read()?.player_slot() == player_slot {
+                return Ok(Some(controller.clone()));
+            }
+        }
+
+        Ok(None)
+    }
+
+    // the sdl2 bindings don't translate touchpad and sensor events, so the state is polled
+    fn poll_touchpads_and_sensors(&self) -> VerboseResult<()> {
+        let controllers = if self.all_controller_events() {
+            self.connected_controllers.read()?.clone()
+        } else {
+            self.selected_controller.read()?.iter().cloned().collect()
+        };
+
+        for controller in controllers {
+            let (touchpad_changes, sensor_changes) = {
+                let mut controller = controller.write()?;
+
+                (controller.update_touchpads(), controller.update_sensors())
+            };
+
+            for change in touchpad_changes {
+                self.dispatch(match change {
+                    TouchpadChange::Down(finger) => {
+                        PresentationEventType::ControllerTouchpadDown(controller.clone(), finger)
+                    }
+                    TouchpadChange::Motion(finger) => {
+                        PresentationEventType::ControllerTouchpadMotion(controller.clone(), finger)
                     }
+                    TouchpadChange::Up(finger) => {
+                        PresentationEventType::ControllerTouchpadUp(controller.clone(), finger)
+                    }
+                })?;
+            }
+
+            for (sensor, data) in sensor_changes {
+                self.dispatch(PresentationEventType::ControllerSensor(
+                    controller.clone(),
+                    sensor,
+                    data,
+                ))?;
+            }
+        }
 
-                    self.event_callback.read()?(PresentationEventType::ControllerButtonUp(button))?;
+        Ok(())
+    }
+
+    fn poll_replay(&self) -> VerboseResult<bool> {
+        // events from SDL are dropped, only quitting is still possible
+        for event in self.event_pump.write()?.poll_iter() {
+            if let Event::Quit { .. } = event {
+                return Ok(false);
+            }
+        }
+
+        let events = match self.input_replay.lock()?.as_mut() {
+            Some(replay) => replay.next_frame(),
+            None => return Ok(true),
+        };
+
+        let events = match events {
+            Some(events) => events,
+            None => {
+                *self.input_replay.lock()? = None;
+                return Ok(false);
+            }
+        };
+
+        for event in events {
+            let event = match event {
+                RecordedEvent::MouseMotion(x, y) => PresentationEventType::MouseMotion(x, y),
+                RecordedEvent::MouseButtonDown(button) => {
+                    PresentationEventType::MouseButtonDown(button)
                 }
-                Event::ControllerAxisMotion {
-                    axis, value, which, ..
+                RecordedEvent::MouseButtonUp(button) => {
+                    PresentationEventType::MouseButtonUp(button)
+                }
+                RecordedEvent::MouseWheel { x, y } => PresentationEventType::MouseWheel { x, y },
+                RecordedEvent::KeyDown {
+                    keycode,
+                    scancode,
+                    modifiers,
+                    repeat,
+                } => PresentationEventType::KeyDown {
+                    keycode,
+                    scancode,
+                    modifiers,
+                    repeat,
+                },
+                RecordedEvent::KeyUp {
+                    keycode,
+                    scancode,
+                    modifiers,
+                } => PresentationEventType::KeyUp {
+                    keycode,
+                    scancode,
+                    modifiers,
+                },
+                RecordedEvent::TextInput(text) => PresentationEventType::TextInput(text),
+                RecordedEvent::TextEditing {
+                    text,
+                    start,
+                    length,
+                } => PresentationEventType::TextEditing {
+                    text,
+                    start,
+                    length,
+                },
+                RecordedEvent::ControllerAdded {
+                    id,
+                    name,
+                    guid,
+                    player_slot,
                 } => {
+                    let controller = Arc::new(RwLock::new(Controller::replayed(
+                        id,
+                        name,
+                        guid,
+                        player_slot,
+                        self.controller_axis_deadzone.read()?.clone(),
+                    )));
+
+                    self.connected_controllers.write()?.push(controller.clone());
+
                     let mut selected_controller = self.selected_controller.write()?;
 
-                    if let Some(controller) = selected_controller.as_mut() {
-                        let mut controller = controller.write()?;
+                    if selected_controller.is_none() {
+                        *selected_controller = Some(controller.clone());
+                    }
 
-                        // only update axis, when selected controller made the change
-                        if controller.id() != which {
-                            continue;
-                        }
+                    PresentationEventType::ControllerAdded(controller)
+                }
+                RecordedEvent::ControllerRemoved(id) => {
+                    let controller = self.replay_controller(id)?;
 
-                        // 1 / 32768 = 0,000030518
-                        let normalized = value as f32 * 0.000_030_518;
+                    let mut connected_controllers = self.connected_controllers.write()?;
+                    let mut selected_controller = self.selected_controller.write()?;
 
-                        match axis {
-                            sdl2::controller::Axis::LeftX => {
-                                controller.set_left_x(normalized);
-                            }
-                            sdl2::controller::Axis::RightX => {
-                                controller.set_right_x(normalized);
-                            }
-                            sdl2::controller::Axis::LeftY => {
-                                controller.set_left_y(-normalized);
-                            }
-                            sdl2::controller::Axis::RightY => {
-                                controller.set_right_y(normalized);
-                            }
-                            sdl2::controller::Axis::TriggerLeft => {
-                                controller.set_left_trigger(normalized);
-                            }
-                            sdl2::controller::Axis::TriggerRight => {
-                                controller.set_right_trigger(normalized);
-                            }
+                    connected_controllers.retain(|c| !Arc::ptr_eq(c, &controller));
+
+                    if let Some(selected) = selected_controller.as_ref() {
+                        if Arc::ptr_eq(selected, &controller) {
+                            *selected_controller = connected_controllers.first().cloned();
                         }
+                    }
 
-                        controller_axis_changed = true;
+                    PresentationEventType::ControllerRemoved(controller)
+                }
+                RecordedEvent::ControllerAxis(id, axis) => {
+                    let controller_cell = self.replay_controller(id)?;
+
+                    {
+                        let mut controller = controller_cell.write()?;
+
+                        controller.set_left_x(axis.left_stick.x);
+                        controller.set_left_y(axis.left_stick.y);
+                        controller.set_right_x(axis.right_stick.x);
+                        controller.set_right_y(axis.right_stick.y);
+                        controller.set_left_trigger(axis.left_trigger);
+                        controller.set_right_trigger(axis.right_trigger);
                     }
+
+                    PresentationEventType::ControllerAxis(controller_cell)
                 }
-                _ => (),
+                RecordedEvent::ControllerButtonDown(id, button) => {
+                    PresentationEventType::ControllerButtonDown(self.replay_controller(id)?, button)
+                }
+                RecordedEvent::ControllerButtonUp(id, button) => {
+                    PresentationEventType::ControllerButtonUp(self.replay_controller(id)?, button)
+                }
+                RecordedEvent::ControllerTouchpadDown(id, finger) => {
+                    PresentationEventType::ControllerTouchpadDown(
+                        self.replay_controller(id)?,
+                        finger,
+                    )
+                }
+                RecordedEvent::ControllerTouchpadMotion(id, finger) => {
+                    PresentationEventType::ControllerTouchpadMotion(
+                        self.replay_controller(id)?,
+                        finger,
+                    )
+                }
+                RecordedEvent::ControllerTouchpadUp(id, finger) => {
+                    PresentationEventType::ControllerTouchpadUp(self.replay_controller(id)?, finger)
+                }
+                RecordedEvent::ControllerSensor(id, sensor, data) => {
+                    PresentationEventType::ControllerSensor(
+                        self.replay_controller(id)?,
+                        sensor,
+                        data,
+                    )
+                }
+                // actions are created again by the input map
+                RecordedEvent::Action(..) => continue,
+            };
+
+            self.dispatch(event)?;
+        }
+
+        Ok(true)
+    }
+
+    fn replay_controller(&self, id: u32) -> VerboseResult<Arc<RwLock<Controller>>> {
+        for controller in self.connected_controllers.read()?.iter() {
+            if controller.read()?.id() == id {
+                return Ok(controller.clone());
             }
         }
 
-        if controller_axis_changed {
-            if let Some(controller) = self.selected_controller.read()?.as_ref() {
-                self.event_callback.read()?(PresentationEventType::ControllerAxis(
-                    controller.clone(),
-                ))?;
+        create_error!(format!(
+            "input replay references unknown controller ({})",
+            id
+        ))
+    }
+
+    // sends the raw event followed by the actions, that it changed
+    fn dispatch(&self, event: PresentationEventType) -> VerboseResult<()> {
+        let actions = self.input_map.write()?.process(&event);
+
+        if let Some(recorder) = self.input_recorder.lock()?.as_mut() {
+            recorder.record(&RecordedEvent::from_event(&event)?)?;
+
+            for (name, state) in actions.iter() {
+                recorder.record(&RecordedEvent::Action(name.clone(), *state))?;
             }
         }
 
-        Ok(true)
+        let event_callback = self.event_callback.read()?;
+
+        event_callback(event)?;
+
+        for (name, state) in actions {
+            event_callback(PresentationEventType::Action(name, state))?;
+        }
+
+        Ok(())
+    }
+
+    // controller, whose events are forwarded
+    fn event_controller(&self, id: u32) -> VerboseResult<Option<Arc<RwLock<Controller>>>> {
+        if self.all_controller_events() {
+            for controller in self.connected_controllers.read()?.iter() {
+                if controller.read()?.id() == id {
+                    return Ok(Some(controller.clone()));
+                }
+            }
+
+            return Ok(None);
+        }
+
+        match self.selected_controller.read()?.as_ref() {
+            Some(selected_controller) => {
+                if selected_controller.read()?.id() == id {
+                    Ok(Some(selected_controller.clone()))
+                } else {
+                    Ok(None)
+                }
+            }
+            None => Ok(None),
+        }
     }
 
     pub fn controllers(&self) -> VerboseResult<RwLockReadGuard<'_, Vec<Arc<RwLock<Controller>>>>> {
@@ -400,3 +911,83 @@ impl EventSystem {
 
 unsafe impl Send for EventSystem {}
 unsafe impl Sync for EventSystem {}
+
+#[derive(Default)]
+struct PlayerSlots {
+    // guid of the last controller, that used the slot
+    guids: Vec<String>,
+    occupied: Vec<bool>,
+}
+
+impl PlayerSlots {
+    /// Prefers the free slot, that the same kind of controller used before, then any free slot,
+    /// a new slot is only added if all slots are occupied
+    fn assign(&mut self, guid: &str) -> usize {
+        let free = |index: &usize| !self.occupied[*index];
+
+        let slot = (0..self.guids.len())
+            .filter(free)
+            .find(|index| self.guids[*index] == guid)
+            .or_else(|| (0..self.guids.len()).find(free));
+
+        let slot = match slot {
+            Some(slot) => slot,
+            None => {
+                self.guids.push(String::new());
+                self.occupied.push(false);
+
+                self.guids.len() - 1
+            }
+        };
+
+        self.guids[slot] = guid.to_string();
+        self.occupied[slot] = true;
+
+        slot
+    }
+
+    fn release(&mut self, slot: usize) {
+        if let Some(occupied) = self.occupied.get_mut(slot) {
+            *occupied = false;
+        }
+    }
+}
+
+#[test]
+fn player_slots_reconnect() {
+    let mut slots = PlayerSlots::default();
+
+    assert_eq!(slots.assign("pad_a"), 0);
+    assert_eq!(slots.assign("pad_b"), 1);
+
+    // the same controller gets its slot back after reconnecting
+    slots.release(0);
+    assert_eq!(slots.assign("pad_a"), 0);
+
+    // a different controller takes over a free slot, instead of adding a new one
+    slots.release(1);
+    assert_eq!(slots.assign("pad_c"), 1);
+    assert_eq!(slots.assign("pad_b"), 2);
+}
+
+#[test]
+fn player_slots_prefer_previous_slot() {
+    let mut slots = PlayerSlots::default();
+
+    assert_eq!(slots.assign("pad_a"), 0);
+    assert_eq!(slots.assign("pad_b"), 1);
+
+    slots.release(0);
+    slots.release(1);
+
+    // slot 0 is free as well, but pad_b used slot 1 before
+    assert_eq!(slots.assign("pad_b"), 1);
+    assert_eq!(slots.assign("pad_a"), 0);
+
+    // two controllers of the same kind
+    slots.release(0);
+    slots.release(1);
+
+    assert_eq!(slots.assign("pad_a"), 0);
+    assert_eq!(slots.assign("pad_a"), 1);
+}
@@ -0,0 +1,788 @@
+//! Maps raw input to named actions and axes
+
+use sdl2::controller::Button;
+use sdl2::keyboard::Keycode;
+
+use utilities::prelude::*;
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::controlleraxis::ControllerAxis;
+use super::eventsystem::PresentationEventType;
+use super::mousebutton::MouseButton;
+
+const ACTION_SECTION: &str = "Actions";
+const AXIS_SECTION: &str = "Axes";
+
+/// Analog input of a controller
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AnalogInput {
+    LeftStickX,
+    LeftStickY,
+    RightStickX,
+    RightStickY,
+    LeftTrigger,
+    RightTrigger,
+}
+
+impl AnalogInput {
+    fn value(self, controller_axis: &ControllerAxis) -> f32 {
+        match self {
+            AnalogInput::LeftStickX => controller_axis.left_stick.x,
+            AnalogInput::LeftStickY => controller_axis.left_stick.y,
+            AnalogInput::RightStickX => controller_axis.right_stick.x,
+            AnalogInput::RightStickY => controller_axis.right_stick.y,
+            AnalogInput::LeftTrigger => controller_axis.left_trigger,
+            AnalogInput::RightTrigger => controller_axis.right_trigger,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            AnalogInput::LeftStickX => "LeftStickX",
+            AnalogInput::LeftStickY => "LeftStickY",
+            AnalogInput::RightStickX => "RightStickX",
+            AnalogInput::RightStickY => "RightStickY",
+            AnalogInput::LeftTrigger => "LeftTrigger",
+            AnalogInput::RightTrigger => "RightTrigger",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<AnalogInput> {
+        match name {
+            "LeftStickX" => Some(AnalogInput::LeftStickX),
+            "LeftStickY" => Some(AnalogInput::LeftStickY),
+            "RightStickX" => Some(AnalogInput::RightStickX),
+            "RightStickY" => Some(AnalogInput::RightStickY),
+            "LeftTrigger" => Some(AnalogInput::LeftTrigger),
+            "RightTrigger" => Some(AnalogInput::RightTrigger),
+            _ => None,
+        }
+    }
+}
+
+/// A single input or a combination of inputs, that triggers an action or drives an axis
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputBinding {
+    Key(Keycode),
+    MouseButton(MouseButton),
+    ControllerButton(Button),
+
+    /// active while `input` exceeds `deadzone` in the given direction, the value is
+    /// rescaled to [0, 1] beyond the dead zone
+    ///
+    /// Without a `deadzone` the dead zone of each controller is used
+    ControllerAxis {
+        input: AnalogInput,
+        positive: bool,
+        deadzone: Option<f32>,
+    },
+
+    /// active while all bindings are active, e.g. Ctrl + S
+    Chord(Vec<InputBinding>),
+}
+
+impl InputBinding {
+    pub fn chord(bindings: &[InputBinding]) -> Self {
+        InputBinding::Chord(bindings.to_vec())
+    }
+
+    fn value(&self, state: &InputState) -> f32 {
+        match self {
+            InputBinding::Key(keycode) => Self::digital(state.keys.contains(keycode)),
+            InputBinding::MouseButton(button) => {
+                Self::digital(state.mouse_buttons.contains(button))
+            }
+            InputBinding::ControllerButton(button) => Self::digital(
+                state
+                    .controller_buttons
+                    .iter()
+                    .any(|(_, pressed_button)| pressed_button == button),
+            ),
+            InputBinding::ControllerAxis {
+                input,
+                positive,
+                deadzone,
+            } => {
+                let sign = if *positive { 1.0 } else { -1.0 };
+
+                // the strongest deflection of all controllers wins
+                state
+                    .controller_axes
+                    .iter()
+                    .map(|(_, controller_axis, controller_deadzone)| {
+                        let deadzone = deadzone.unwrap_or(*controller_deadzone).clamp(0.0, 0.99);
+                        let value = input.value(controller_axis) * sign;
+
+                        if value <= deadzone {
+                            0.0
+                        } else {
+                            ((value - deadzone) / (1.0 - deadzone)).min(1.0)
+                        }
+                    })
+                    .fold(0.0, f32::max)
+            }
+            InputBinding::Chord(bindings) => {
+                if bindings.is_empty() {
+                    return 0.0;
+                }
+
+                bindings
+                    .iter()
+                    .map(|binding| binding.value(state))
+                    .fold(1.0, f32::min)
+            }
+        }
+    }
+
+    fn digital(active: bool) -> f32 {
+        if active {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+/// Format inside of the config file:
+/// `Key:Space`, `Mouse:Left`, `Button:a`, `Axis:LeftStickX+:0.25` (`Axis:LeftStickX+` with
+/// the dead zone of the controller) and `Key:Left Ctrl&Key:S` for chords
+impl fmt::Display for InputBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputBinding::Key(keycode) => {
+                let name = keycode.name();
+
+                // `&` separates the bindings of a chord, so that key is written by its key code
+                if name.is_empty() || name.contains('&') {
+                    write!(f, "Key:{}", *keycode as i32)
+                } else {
+                    write!(f, "Key:{}", name)
+                }
+            }
+            InputBinding::MouseButton(button) => {
+                write!(f, "Mouse:{}", button.name())
+            }
+            InputBinding::ControllerButton(button) => write!(f, "Button:{}", button.string()),
+            InputBinding::ControllerAxis {
+                input,
+                positive,
+                deadzone,
+            } => {
+                write!(
+                    f,
+                    "Axis:{}{}",
+                    input.name(),
+                    if *positive { "+" } else { "-" }
+                )?;
+
+                match deadzone {
+                    Some(deadzone) => write!(f, ":{}", deadzone),
+                    None => Ok(()),
+                }
+            }
+            InputBinding::Chord(bindings) => {
+                for (i, binding) in bindings.iter().enumerate() {
+                    if i != 0 {
+                        write!(f, "&")?;
+                    }
+
+                    write!(f, "{}", binding)?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+impl ConfigType for InputBinding {
+    fn from_item(item: &ConfigItem) -> Result<Self, String> {
+        String::from_item(item)?
+            .parse()
+            .map_err(|err: UtilError| err.message())
+    }
+
+    fn to_item(&self) -> ConfigItem {
+        ConfigItem::Value(self.to_string())
+    }
+}
+
+impl FromStr for InputBinding {
+    type Err = UtilError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        if s.contains('&') {
+            return Ok(InputBinding::Chord(
+                s.split('&')
+                    .map(|binding| binding.parse())
+                    .collect::<VerboseResult<Vec<InputBinding>>>()?,
+            ));
+        }
+
+        let mut split = s.splitn(2, ':');
+
+        let (kind, value) = match (split.next(), split.next()) {
+            (Some(kind), Some(value)) => (kind, value),
+            _ => create_error!(format!("input binding has wrong format: {}", s)),
+        };
+
+        match kind {
+            "Key" => {
+                let keycode = Keycode::from_name(value).or_else(|| match value.parse::<i32>() {
+                    Ok(code) => Keycode::from_i32(code),
+                    Err(_) => None,
+                });
+
+                match keycode {
+                    Some(keycode) => Ok(InputBinding::Key(keycode)),
+                    None => create_error!(format!("unknown key: {}", value)),
+                }
+            }
+            "Mouse" => match MouseButton::from_name(value) {
+                Some(button) => Ok(InputBinding::MouseButton(button)),
+                None => create_error!(format!("unknown mouse button: {}", value)),
+            },
+            "Button" => match Button::from_string(value) {
+                Some(button) => Ok(InputBinding::ControllerButton(button)),
+                None => create_error!(format!("unknown controller button: {}", value)),
+            },
+            "Axis" => {
+                let mut axis_split = value.splitn(2, ':');
+
+                let input = match axis_split.next() {
+                    Some(input) => input,
+                    None => create_error!(format!("controller axis has wrong format: {}", value)),
+                };
+
+                let (input, positive) = if input.ends_with('+') {
+                    (input.trim_end_matches('+'), true)
+                } else if input.ends_with('-') {
+                    (input.trim_end_matches('-'), false)
+                } else {
+                    create_error!(format!("controller axis is missing a direction: {}", input))
+                };
+
+                let input = match AnalogInput::from_name(input) {
+                    Some(input) => input,
+                    None => create_error!(format!("unknown controller axis: {}", input)),
+                };
+
+                // without a dead zone the one of the controller is used
+                let deadzone = match axis_split.next() {
+                    Some(deadzone) => match deadzone.parse::<f32>() {
+                        Ok(deadzone) if deadzone.is_finite() => Some(deadzone),
+                        _ => create_error!(format!("error parsing dead zone: {}", deadzone)),
+                    },
+                    None => None,
+                };
+
+                Ok(InputBinding::ControllerAxis {
+                    input,
+                    positive,
+                    deadzone,
+                })
+            }
+            _ => create_error!(format!("unknown input binding: {}", s)),
+        }
+    }
+}
+
+/// Change of an action, that is sent as `PresentationEventType::Action`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ActionState {
+    Pressed,
+    Released,
+    /// new value of an axis in [-1, 1]
+    Axis(f32),
+}
+
+struct Action {
+    name: String,
+    bindings: Vec<InputBinding>,
+    pressed: bool,
+}
+
+struct Axis {
+    name: String,
+    // each binding is multiplied with its scale, e.g. -1.0 for the negative direction
+    bindings: Vec<(InputBinding, f32)>,
+    value: f32,
+}
+
+#[derive(Default)]
+struct InputState {
+    keys: Vec<Keycode>,
+    mouse_buttons: Vec<MouseButton>,
+
+    // controller instance id with its state, axes together with the dead zone of the controller
+    controller_buttons: Vec<(u32, Button)>,
+    controller_axes: Vec<(u32, ControllerAxis, f32)>,
+}
+
+/// Named actions and axes with their bindings
+///
+/// Input of all forwarded controllers is combined
+#[derive(Default)]
+pub struct InputMap {
+    actions: Vec<Action>,
+    axes: Vec<Axis>,
+
+    state: InputState,
+}
+
+impl InputMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads actions and axes from a config file, written by `InputMap::save`
+    pub fn load(file_name: &str) -> VerboseResult<Self> {
+        let document = ConfigDocument::read(file_name)?;
+        let mut input_map = Self::new();
+
+        for name in document.keys(ACTION_SECTION) {
+            let bindings: Vec<InputBinding> =
+                document.value(ACTION_SECTION, name)?.unwrap_or_default();
+
+            input_map.set_action_bindings(name, &bindings);
+        }
+
+        for name in document.keys(AXIS_SECTION) {
+            let mut bindings = Vec::new();
+
+            for binding in document
+                .value::<Vec<String>>(AXIS_SECTION, name)?
+                .unwrap_or_default()
+            {
+                // the scale is appended to the binding, e.g. `Key:A*-1`
+                let mut split = binding.rsplitn(2, '*');
+
+                let (scale, binding) = match (split.next(), split.next()) {
+                    (Some(scale), Some(binding)) => (scale, binding),
+                    _ => create_error!(format!("axis binding has wrong format: {}", binding)),
+                };
+
+                let scale = match scale.parse::<f32>() {
+                    Ok(scale) if scale.is_finite() => scale,
+                    _ => create_error!(format!("error parsing axis scale: {}", scale)),
+                };
+
+                bindings.push((binding.parse::<InputBinding>()?, scale));
+            }
+
+            input_map.set_axis_bindings(name, &bindings);
+        }
+
+        Ok(input_map)
+    }
+
+    pub fn save(&self, file_name: &str) -> VerboseResult<()> {
+        let mut document = ConfigDocument::new();
+
+        for action in self.actions.iter() {
+            document.set(ACTION_SECTION, &action.name, action.bindings.to_item())?;
+        }
+
+        for axis in self.axes.iter() {
+            let bindings: Vec<String> = axis
+                .bindings
+                .iter()
+                .map(|(binding, scale)| format!("{}*{}", binding, scale))
+                .collect();
+
+            document.set(AXIS_SECTION, &axis.name, bindings.to_item())?;
+        }
+
+        document.write(file_name)
+    }
+
+    /// Adds a binding to an action, the action is created if it doesn't exist yet
+    pub fn bind_action(&mut self, name: &str, binding: InputBinding) {
+        let action = self.action_mut(name);
+
+        if !action.bindings.contains(&binding) {
+            action.bindings.push(binding);
+        }
+    }
+
+    pub fn unbind_action(&mut self, name: &str, binding: &InputBinding) {
+        if let Some(action) = self.actions.iter_mut().find(|action| action.name == name) {
+            action.bindings.retain(|b| b != binding);
+        }
+    }
+
+    /// Replaces all bindings of an action, e.g. when the player rebinds it
+    pub fn set_action_bindings(&mut self, name: &str, bindings: &[InputBinding]) {
+        self.action_mut(name).bindings = bindings.to_vec();
+    }
+
+    pub fn action_bindings(&self, name: &str) -> Option<&[InputBinding]> {
+        self.actions
+            .iter()
+            .find(|action| action.name == name)
+            .map(|action| action.bindings.as_slice())
+    }
+
+    pub fn remove_action(&mut self, name: &str) {
+        self.actions.retain(|action| action.name != name);
+    }
+
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.actions.iter().map(|action| action.name.as_str())
+    }
+
+    /// Adds a binding to an axis, its value is multiplied with `scale`
+    pub fn bind_axis(&mut self, name: &str, binding: InputBinding, scale: f32) {
+        let axis = self.axis_mut(name);
+
+        match axis.bindings.iter_mut().find(|(b, _)| *b == binding) {
+            Some((_, old_scale)) => *old_scale = scale,
+            None => axis.bindings.push((binding, scale)),
+        }
+    }
+
+    pub fn unbind_axis(&mut self, name: &str, binding: &InputBinding) {
+        if let Some(axis) = self.axes.iter_mut().find(|axis| axis.name == name) {
+            axis.bindings.retain(|(b, _)| b != binding);
+        }
+    }
+
+    pub fn set_axis_bindings(&mut self, name: &str, bindings: &[(InputBinding, f32)]) {
+        self.axis_mut(name).bindings = bindings.to_vec();
+    }
+
+    pub fn axis_bindings(&self, name: &str) -> Option<&[(InputBinding, f32)]> {
+        self.axes
+            .iter()
+            .find(|axis| axis.name == name)
+            .map(|axis| axis.bindings.as_slice())
+    }
+
+    pub fn remove_axis(&mut self, name: &str) {
+        self.axes.retain(|axis| axis.name != name);
+    }
+
+    pub fn axes(&self) -> impl Iterator<Item = &str> {
+        self.axes.iter().map(|axis| axis.name.as_str())
+    }
+
+    pub fn is_pressed(&self, name: &str) -> bool {
+        self.actions
+            .iter()
+            .find(|action| action.name == name)
+            .map(|action| action.pressed)
+            .unwrap_or(false)
+    }
+
+    /// Current value in [-1, 1], 0.0 for unknown axes
+    pub fn axis_value(&self, name: &str) -> f32 {
+        self.axes
+            .iter()
+            .find(|axis| axis.name == name)
+            .map(|axis| axis.value)
+            .unwrap_or(0.0)
+    }
+
+    /// Updates the input state with a raw event and returns the actions and axes, that changed
+    pub(crate) fn process(&mut self, event: &PresentationEventType) -> Vec<(String, ActionState)> {
+        let state = &mut self.state;
+
+        match event {
+            PresentationEventType::KeyDown { keycode, .. } => {
+                if !state.keys.contains(keycode) {
+                    state.keys.push(*keycode);
+                }
+            }
+            PresentationEventType::KeyUp { keycode, .. } => {
+                state.keys.retain(|key| key != keycode);
+            }
+            PresentationEventType::MouseButtonDown(button) => {
+                if !state.mouse_buttons.contains(button) {
+                    state.mouse_buttons.push(*button);
+                }
+            }
+            PresentationEventType::MouseButtonUp(button) => {
+                state.mouse_buttons.retain(|b| b != button);
+            }
+            PresentationEventType::ControllerButtonDown(controller, button) => {
+                let id = match controller.read() {
+                    Ok(controller) => controller.id(),
+                    Err(_) => return Vec::new(),
+                };
+
+                if !state.controller_buttons.contains(&(id, *button)) {
+                    state.controller_buttons.push((id, *button));
+                }
+            }
+            PresentationEventType::ControllerButtonUp(controller, button) => {
+                let id = match controller.read() {
+                    Ok(controller) => controller.id(),
+                    Err(_) => return Vec::new(),
+                };
+
+                state
+                    .controller_buttons
+                    .retain(|(controller_id, b)| *controller_id != id || b != button);
+            }
+            PresentationEventType::ControllerAxis(controller) => {
+                let (id, controller_axis, deadzone) = match controller.read() {
+                    Ok(controller) => (
+                        controller.id(),
+                        controller.controller_axis(),
+                        controller.deadzone(),
+                    ),
+                    Err(_) => return Vec::new(),
+                };
+
+                match state.controller_axes.iter_mut().find(|(c, _, _)| *c == id) {
+                    Some((_, axis, _)) => *axis = controller_axis,
+                    None => state.controller_axes.push((id, controller_axis, deadzone)),
+                }
+            }
+            PresentationEventType::ControllerRemoved(controller) => {
+                let id = match controller.read() {
+                    Ok(controller) => controller.id(),
+                    Err(_) => return Vec::new(),
+                };
+
+                state.controller_buttons.retain(|(c, _)| *c != id);
+                state.controller_axes.retain(|(c, _, _)| *c != id);
+            }
+            _ => return Vec::new(),
+        }
+
+        let state = &self.state;
+        let mut changes = Vec::new();
+
+        for action in self.actions.iter_mut() {
+            let pressed = action
+                .bindings
+                .iter()
+                .any(|binding| binding.value(state) > 0.0);
+
+            if pressed != action.pressed {
+                action.pressed = pressed;
+
+                changes.push((
+                    action.name.clone(),
+                    if pressed {
+                        ActionState::Pressed
+                    } else {
+                        ActionState::Released
+                    },
+                ));
+            }
+        }
+
+        for axis in self.axes.iter_mut() {
+            let value: f32 = axis
+                .bindings
+                .iter()
+                .map(|(binding, scale)| binding.value(state) * scale)
+                .sum();
+            let value = value.clamp(-1.0, 1.0);
+
+            if value != axis.value {
+                axis.value = value;
+
+                changes.push((axis.name.clone(), ActionState::Axis(value)));
+            }
+        }
+
+        changes
+    }
+
+    fn action_mut(&mut self, name: &str) -> &mut Action {
+        match self.actions.iter().position(|action| action.name == name) {
+            Some(index) => &mut self.actions[index],
+            None => {
+                self.actions.push(Action {
+                    name: name.to_string(),
+                    bindings: Vec::new(),
+                    pressed: false,
+                });
+
+                // unwrap is save since we just pushed an action
+                self.actions.last_mut().unwrap()
+            }
+        }
+    }
+
+    fn axis_mut(&mut self, name: &str) -> &mut Axis {
+        match self.axes.iter().position(|axis| axis.name == name) {
+            Some(index) => &mut self.axes[index],
+            None => {
+                self.axes.push(Axis {
+                    name: name.to_string(),
+                    bindings: Vec::new(),
+                    value: 0.0,
+                });
+
+                // unwrap is save since we just pushed an axis
+                self.axes.last_mut().unwrap()
+            }
+        }
+    }
+}
+
+#[test]
+fn input_binding_round_trip() {
+    let bindings = [
+        InputBinding::Key(Keycode::Space),
+        InputBinding::Key(Keycode::Equals),
+        InputBinding::MouseButton(MouseButton::Forward),
+        InputBinding::ControllerButton(Button::A),
+        InputBinding::ControllerAxis {
+            input: AnalogInput::LeftStickX,
+            positive: false,
+            deadzone: Some(0.25),
+        },
+        InputBinding::chord(&[
+            InputBinding::Key(Keycode::LCtrl),
+            InputBinding::Key(Keycode::S),
+        ]),
+        InputBinding::Key(Keycode::Ampersand),
+        InputBinding::ControllerAxis {
+            input: AnalogInput::RightTrigger,
+            positive: true,
+            deadzone: None,
+        },
+    ];
+
+    for binding in bindings.iter() {
+        let text = binding.to_string();
+
+        assert_eq!(&text.parse::<InputBinding>().unwrap(), binding, "{}", text);
+    }
+
+    assert_eq!(bindings[1].to_string(), "Key:=");
+    assert_eq!(bindings[4].to_string(), "Axis:LeftStickX-:0.25");
+    assert_eq!(bindings[5].to_string(), "Key:Left Ctrl&Key:S");
+
+    // `&` separates the bindings of a chord
+    assert_eq!(bindings[6].to_string(), "Key:38");
+    assert_eq!(bindings[7].to_string(), "Axis:RightTrigger+");
+
+    for text in [
+        "Space",
+        "Key:NoSuchKey",
+        "Mouse:Up",
+        "Axis:LeftStickX:0.25",
+        "Axis:LeftStick+:0.25",
+        "Axis:LeftStickX+:NaN",
+        "Axis:LeftStickX+:",
+        "Wheel:Up",
+    ]
+    .iter()
+    {
+        assert!(text.parse::<InputBinding>().is_err(), "{}", text);
+    }
+}
+
+#[test]
+fn input_map_save_and_load() {
+    let file_name = std::env::temp_dir().join(format!("inputmap_{}.conf", std::process::id()));
+    let file_name = file_name.to_str().unwrap();
+
+    let jump = [
+        InputBinding::Key(Keycode::Space),
+        InputBinding::ControllerButton(Button::A),
+    ];
+    let save = InputBinding::chord(&[
+        InputBinding::Key(Keycode::LCtrl),
+        InputBinding::Key(Keycode::S),
+    ]);
+    let forward = [
+        (InputBinding::Key(Keycode::W), 1.0),
+        (InputBinding::Key(Keycode::Comma), -1.0),
+        (
+            InputBinding::ControllerAxis {
+                input: AnalogInput::LeftStickY,
+                positive: true,
+                deadzone: Some(0.2),
+            },
+            0.5,
+        ),
+    ];
+
+    let mut input_map = InputMap::new();
+    input_map.set_action_bindings("jump", &jump);
+    input_map.bind_action("save", save.clone());
+    input_map.set_axis_bindings("forward", &forward);
+
+    input_map.save(file_name).unwrap();
+    let loaded = InputMap::load(file_name);
+    std::fs::remove_file(file_name).unwrap();
+    let loaded = loaded.unwrap();
+
+    assert_eq!(loaded.action_bindings("jump").unwrap(), &jump);
+    assert_eq!(loaded.action_bindings("save").unwrap(), &[save]);
+    assert_eq!(loaded.axis_bindings("forward").unwrap(), &forward);
+
+    for scale in ["NaN", "inf"].iter() {
+        std::fs::write(file_name, format!("[Axes]\nforward = [Key:W*{}]\n", scale)).unwrap();
+        let loaded = InputMap::load(file_name);
+        std::fs::remove_file(file_name).unwrap();
+
+        assert!(loaded.is_err(), "{}", scale);
+    }
+}
+
+#[test]
+fn input_map_uses_the_controller_deadzone() {
+    use super::controller::Controller;
+    use std::sync::{Arc, RwLock};
+
+    let controller = Arc::new(RwLock::new(Controller::replayed(
+        0,
+        "replayed".to_string(),
+        String::new(),
+        0,
+        0.5,
+    )));
+
+    let mut input_map = InputMap::new();
+    input_map.set_axis_bindings(
+        "accelerate",
+        &[(
+            InputBinding::ControllerAxis {
+                input: AnalogInput::RightTrigger,
+                positive: true,
+                deadzone: None,
+            },
+            1.0,
+        )],
+    );
+    input_map.set_axis_bindings(
+        "brake",
+        &[(
+            InputBinding::ControllerAxis {
+                input: AnalogInput::RightTrigger,
+                positive: true,
+                deadzone: Some(0.0),
+            },
+            1.0,
+        )],
+    );
+
+    controller.write().unwrap().set_right_trigger(0.4);
+    let changes = input_map.process(&PresentationEventType::ControllerAxis(controller.clone()));
+
+    // inside of the dead zone of the controller
+    assert_eq!(changes, [("brake".to_string(), ActionState::Axis(0.4))]);
+
+    controller.write().unwrap().set_right_trigger(0.75);
+    let changes = input_map.process(&PresentationEventType::ControllerAxis(controller));
+
+    assert_eq!(
+        changes,
+        [
+            ("accelerate".to_string(), ActionState::Axis(0.5)),
+            ("brake".to_string(), ActionState::Axis(0.75))
+        ]
+    );
+}
@@ -0,0 +1,756 @@
+//! Recording and replay of input events
+//!
+//! Every event is written as one line: `<frame index> <microseconds> <event> <arguments>`
+
+use sdl2::controller::Button;
+use sdl2::keyboard::{Keycode, Mod, Scancode};
+
+use utilities::prelude::*;
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::str::FromStr;
+use std::time::Instant;
+
+use super::controller::{ControllerSensor, TouchpadFinger};
+use super::controlleraxis::ControllerAxis;
+use super::eventsystem::PresentationEventType;
+use super::inputmap::ActionState;
+use super::mousebutton::MouseButton;
+
+const HEADER: &str = "# input recording";
+const END: &str = "End";
+
+/// `PresentationEventType`, that references controllers by their id and carries their state
+#[derive(Debug, Clone)]
+pub(crate) enum RecordedEvent {
+    MouseMotion(u32, u32),
+    MouseButtonDown(MouseButton),
+    MouseButtonUp(MouseButton),
+    MouseWheel {
+        x: i32,
+        y: i32,
+    },
+
+    KeyDown {
+        keycode: Keycode,
+        scancode: Option<Scancode>,
+        modifiers: Mod,
+        repeat: bool,
+    },
+    KeyUp {
+        keycode: Keycode,
+        scancode: Option<Scancode>,
+        modifiers: Mod,
+    },
+
+    TextInput(String),
+    TextEditing {
+        text: String,
+        start: i32,
+        length: i32,
+    },
+
+    ControllerAdded {
+        id: u32,
+        name: String,
+        guid: String,
+        player_slot: usize,
+    },
+    ControllerRemoved(u32),
+    ControllerAxis(u32, ControllerAxis),
+    ControllerButtonDown(u32, Button),
+    ControllerButtonUp(u32, Button),
+    ControllerTouchpadDown(u32, TouchpadFinger),
+    ControllerTouchpadMotion(u32, TouchpadFinger),
+    ControllerTouchpadUp(u32, TouchpadFinger),
+    ControllerSensor(u32, ControllerSensor, [f32; 3]),
+
+    Action(String, ActionState),
+}
+
+impl RecordedEvent {
+    pub(crate) fn from_event(event: &PresentationEventType) -> VerboseResult<Self> {
+        Ok(match event {
+            PresentationEventType::MouseMotion(x, y) => RecordedEvent::MouseMotion(*x, *y),
+            PresentationEventType::MouseButtonDown(button) => {
+                RecordedEvent::MouseButtonDown(*button)
+            }
+            PresentationEventType::MouseButtonUp(button) => RecordedEvent::MouseButtonUp(*button),
+            PresentationEventType::MouseWheel { x, y } => {
+                RecordedEvent::MouseWheel { x: *x, y: *y }
+            }
+            PresentationEventType::KeyDown {
+                keycode,
+                scancode,
+                modifiers,
+                repeat,
+            } => RecordedEvent::KeyDown {
+                keycode: *keycode,
+                scancode: *scancode,
+                modifiers: *modifiers,
+                repeat: *repeat,
+            },
+            PresentationEventType::KeyUp {
+                keycode,
+                scancode,
+                modifiers,
+            } => RecordedEvent::KeyUp {
+                keycode: *keycode,
+                scancode: *scancode,
+                modifiers: *modifiers,
+            },
+            PresentationEventType::TextInput(text) => RecordedEvent::TextInput(text.clone()),
+            PresentationEventType::TextEditing {
+                text,
+                start,
+                length,
+            } => RecordedEvent::TextEditing {
+                text: text.clone(),
+                start: *start,
+                length: *length,
+            },
+            PresentationEventType::ControllerAdded(controller) => {
+                let controller = controller.read()?;
+
+                RecordedEvent::ControllerAdded {
+                    id: controller.id(),
+                    name: controller.name().clone(),
+                    guid: controller.guid().to_string(),
+                    player_slot: controller.player_slot(),
+                }
+            }
+            PresentationEventType::ControllerRemoved(controller) => {
+                RecordedEvent::ControllerRemoved(controller.read()?.id())
+            }
+            PresentationEventType::ControllerAxis(controller) => {
+                let controller = controller.read()?;
+
+                RecordedEvent::ControllerAxis(controller.id(), controller.controller_axis())
+            }
+            PresentationEventType::ControllerButtonDown(controller, button) => {
+                RecordedEvent::ControllerButtonDown(controller.read()?.id(), *button)
+            }
+            PresentationEventType::ControllerButtonUp(controller, button) => {
+                RecordedEvent::ControllerButtonUp(controller.read()?.id(), *button)
+            }
+            PresentationEventType::ControllerTouchpadDown(controller, finger) => {
+                RecordedEvent::ControllerTouchpadDown(controller.read()?.id(), *finger)
+            }
+            PresentationEventType::ControllerTouchpadMotion(controller, finger) => {
+                RecordedEvent::ControllerTouchpadMotion(controller.read()?.id(), *finger)
+            }
+            PresentationEventType::ControllerTouchpadUp(controller, finger) => {
+                RecordedEvent::ControllerTouchpadUp(controller.read()?.id(), *finger)
+            }
+            PresentationEventType::ControllerSensor(controller, sensor, data) => {
+                RecordedEvent::ControllerSensor(controller.read()?.id(), *sensor, *data)
+            }
+            PresentationEventType::Action(name, state) => {
+                RecordedEvent::Action(name.clone(), *state)
+            }
+        })
+    }
+
+    fn write(&self) -> String {
+        match self {
+            RecordedEvent::MouseMotion(x, y) => format!("MouseMotion {} {}", x, y),
+            RecordedEvent::MouseButtonDown(button) => {
+                format!("MouseButtonDown {}", button.name())
+            }
+            RecordedEvent::MouseButtonUp(button) => format!("MouseButtonUp {}", button.name()),
+            RecordedEvent::MouseWheel { x, y } => format!("MouseWheel {} {}", x, y),
+            RecordedEvent::KeyDown {
+                keycode,
+                scancode,
+                modifiers,
+                repeat,
+            } => format!(
+                "KeyDown {} {} {} {}",
+                *keycode as i32,
+                Self::write_scancode(*scancode),
+                modifiers.bits(),
+                repeat
+            ),
+            RecordedEvent::KeyUp {
+                keycode,
+                scancode,
+                modifiers,
+            } => format!(
+                "KeyUp {} {} {}",
+                *keycode as i32,
+                Self::write_scancode(*scancode),
+                modifiers.bits()
+            ),
+            RecordedEvent::TextInput(text) => format!("TextInput {}", escape(text)),
+            RecordedEvent::TextEditing {
+                text,
+                start,
+                length,
+            } => format!("TextEditing {} {} {}", escape(text), start, length),
+            RecordedEvent::ControllerAdded {
+                id,
+                name,
+                guid,
+                player_slot,
+            } => format!(
+                "ControllerAdded {} {} {} {}",
+                id,
+                escape(name),
+                escape(guid),
+                player_slot
+            ),
+            RecordedEvent::ControllerRemoved(id) => format!("ControllerRemoved {}", id),
+            RecordedEvent::ControllerAxis(id, axis) => format!(
+                "ControllerAxis {} {} {} {} {} {} {}",
+                id,
+                axis.left_stick.x,
+                axis.left_stick.y,
+                axis.right_stick.x,
+                axis.right_stick.y,
+                axis.left_trigger,
+                axis.right_trigger
+            ),
+            RecordedEvent::ControllerButtonDown(id, button) => {
+                format!("ControllerButtonDown {} {}", id, button.string())
+            }
+            RecordedEvent::ControllerButtonUp(id, button) => {
+                format!("ControllerButtonUp {} {}", id, button.string())
+            }
+            RecordedEvent::ControllerTouchpadDown(id, finger) => {
+                format!(
+                    "ControllerTouchpadDown {} {}",
+                    id,
+                    Self::write_finger(finger)
+                )
+            }
+            RecordedEvent::ControllerTouchpadMotion(id, finger) => {
+                format!(
+                    "ControllerTouchpadMotion {} {}",
+                    id,
+                    Self::write_finger(finger)
+                )
+            }
+            RecordedEvent::ControllerTouchpadUp(id, finger) => {
+                format!("ControllerTouchpadUp {} {}", id, Self::write_finger(finger))
+            }
+            RecordedEvent::ControllerSensor(id, sensor, data) => format!(
+                "ControllerSensor {} {:?} {} {} {}",
+                id, sensor, data[0], data[1], data[2]
+            ),
+            RecordedEvent::Action(name, state) => match state {
+                ActionState::Pressed => format!("Action {} Pressed", escape(name)),
+                ActionState::Released => format!("Action {} Released", escape(name)),
+                ActionState::Axis(value) => format!("Action {} Axis {}", escape(name), value),
+            },
+        }
+    }
+
+    fn parse(tokens: &mut Tokens<'_>) -> VerboseResult<Self> {
+        Ok(match tokens.next_str()? {
+            "MouseMotion" => RecordedEvent::MouseMotion(tokens.next_value()?, tokens.next_value()?),
+            "MouseButtonDown" => RecordedEvent::MouseButtonDown(tokens.next_mouse_button()?),
+            "MouseButtonUp" => RecordedEvent::MouseButtonUp(tokens.next_mouse_button()?),
+            "MouseWheel" => RecordedEvent::MouseWheel {
+                x: tokens.next_value()?,
+                y: tokens.next_value()?,
+            },
+            "KeyDown" => RecordedEvent::KeyDown {
+                keycode: tokens.next_keycode()?,
+                scancode: tokens.next_scancode()?,
+                modifiers: Mod::from_bits_truncate(tokens.next_value()?),
+                repeat: tokens.next_value()?,
+            },
+            "KeyUp" => RecordedEvent::KeyUp {
+                keycode: tokens.next_keycode()?,
+                scancode: tokens.next_scancode()?,
+                modifiers: Mod::from_bits_truncate(tokens.next_value()?),
+            },
+            "TextInput" => RecordedEvent::TextInput(tokens.next_string()?),
+            "TextEditing" => RecordedEvent::TextEditing {
+                text: tokens.next_string()?,
+                start: tokens.next_value()?,
+                length: tokens.next_value()?,
+            },
+            "ControllerAdded" => RecordedEvent::ControllerAdded {
+                id: tokens.next_value()?,
+                name: tokens.next_string()?,
+                guid: tokens.next_string()?,
+                player_slot: tokens.next_value()?,
+            },
+            "ControllerRemoved" => RecordedEvent::ControllerRemoved(tokens.next_value()?),
+            "ControllerAxis" => {
+                let id = tokens.next_value()?;
+                let mut axis = ControllerAxis::default();
+
+                axis.left_stick.x = tokens.next_value()?;
+                axis.left_stick.y = tokens.next_value()?;
+                axis.right_stick.x = tokens.next_value()?;
+                axis.right_stick.y = tokens.next_value()?;
+                axis.left_trigger = tokens.next_value()?;
+                axis.right_trigger = tokens.next_value()?;
+
+                RecordedEvent::ControllerAxis(id, axis)
+            }
+            "ControllerButtonDown" => {
+                RecordedEvent::ControllerButtonDown(tokens.next_value()?, tokens.next_button()?)
+            }
+            "ControllerButtonUp" => {
+                RecordedEvent::ControllerButtonUp(tokens.next_value()?, tokens.next_button()?)
+            }
+            "ControllerTouchpadDown" => {
+                RecordedEvent::ControllerTouchpadDown(tokens.next_value()?, tokens.next_finger()?)
+            }
+            "ControllerTouchpadMotion" => {
+                RecordedEvent::ControllerTouchpadMotion(tokens.next_value()?, tokens.next_finger()?)
+            }
+            "ControllerTouchpadUp" => {
+                RecordedEvent::ControllerTouchpadUp(tokens.next_value()?, tokens.next_finger()?)
+            }
+            "ControllerSensor" => {
+                let id = tokens.next_value()?;
+
+                let sensor = match tokens.next_str()? {
+                    "Gyroscope" => ControllerSensor::Gyroscope,
+                    "Accelerometer" => ControllerSensor::Accelerometer,
+                    sensor => create_error!(format!("unknown controller sensor: {}", sensor)),
+                };
+
+                RecordedEvent::ControllerSensor(
+                    id,
+                    sensor,
+                    [
+                        tokens.next_value()?,
+                        tokens.next_value()?,
+                        tokens.next_value()?,
+                    ],
+                )
+            }
+            "Action" => {
+                let name = tokens.next_string()?;
+
+                let state = match tokens.next_str()? {
+                    "Pressed" => ActionState::Pressed,
+                    "Released" => ActionState::Released,
+                    "Axis" => ActionState::Axis(tokens.next_value()?),
+                    state => create_error!(format!("unknown action state: {}", state)),
+                };
+
+                RecordedEvent::Action(name, state)
+            }
+            event => create_error!(format!("unknown event: {}", event)),
+        })
+    }
+
+    fn write_scancode(scancode: Option<Scancode>) -> String {
+        match scancode {
+            Some(scancode) => format!("{}", scancode as i32),
+            None => "-".to_string(),
+        }
+    }
+
+    fn write_finger(finger: &TouchpadFinger) -> String {
+        format!(
+            "{} {} {} {} {}",
+            finger.touchpad, finger.finger, finger.x, finger.y, finger.pressure
+        )
+    }
+}
+
+/// Writes events to a file, flushed once per frame
+pub(crate) struct InputRecorder {
+    writer: BufWriter<File>,
+    start: Instant,
+    frame_index: u64,
+}
+
+impl InputRecorder {
+    pub(crate) fn new(file_name: &str) -> VerboseResult<Self> {
+        let file = match File::create(file_name) {
+            Ok(file) => file,
+            Err(msg) => create_error!(format!(
+                "error creating input recording({}): {}",
+                file_name, msg
+            )),
+        };
+
+        let mut recorder = InputRecorder {
+            writer: BufWriter::new(file),
+            start: Instant::now(),
+            frame_index: 0,
+        };
+
+        recorder.write_line(HEADER)?;
+
+        Ok(recorder)
+    }
+
+    pub(crate) fn record(&mut self, event: &RecordedEvent) -> VerboseResult<()> {
+        let line = format!(
+            "{} {} {}",
+            self.frame_index,
+            self.start.elapsed().as_micros(),
+            event.write()
+        );
+
+        self.write_line(&line)
+    }
+
+    pub(crate) fn next_frame(&mut self) -> VerboseResult<()> {
+        self.frame_index += 1;
+
+        // flush every frame, so a crash doesn't lose the events that led to it
+        if let Err(msg) = self.writer.flush() {
+            create_error!(format!("failed flushing input recording: {}", msg));
+        }
+
+        Ok(())
+    }
+
+    fn write_line(&mut self, line: &str) -> VerboseResult<()> {
+        if let Err(msg) = writeln!(self.writer, "{}", line) {
+            create_error!(format!("failed writing input recording: {}", msg));
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for InputRecorder {
+    fn drop(&mut self) {
+        let line = format!(
+            "{} {} {}",
+            self.frame_index,
+            self.start.elapsed().as_micros(),
+            END
+        );
+
+        if let Err(err) = self.write_line(&line) {
+            println!("{}", err);
+        }
+
+        if let Err(msg) = self.writer.flush() {
+            println!("failed flushing input recording: {}", msg);
+        }
+    }
+}
+
+/// Events of a recording, handed out frame by frame
+pub(crate) struct InputReplay {
+    events: VecDeque<(u64, RecordedEvent)>,
+
+    // frame of the `End` marker, recordings of crashed applications don't have one
+    last_frame: Option<u64>,
+    frame_index: u64,
+}
+
+impl InputReplay {
+    pub(crate) fn new(file_name: &str) -> VerboseResult<Self> {
+        let file = match File::open(file_name) {
+            Ok(file) => file,
+            Err(msg) => create_error!(format!(
+                "error opening input recording({}): {}",
+                file_name, msg
+            )),
+        };
+
+        let mut events = VecDeque::new();
+        let mut last_frame = None;
+
+        for (line_number, line_res) in BufReader::new(file).lines().enumerate() {
+            let line = match line_res {
+                Ok(line) => line,
+                Err(msg) => create_error!(format!(
+                    "error reading input recording({}): {}",
+                    file_name, msg
+                )),
+            };
+
+            // only line endings are stripped, escaped tokens can end with unicode whitespace
+            let trimmed = line.trim_end_matches(&['\r', '\n'][..]);
+
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let mut tokens = Tokens::new(trimmed, line_number + 1);
+
+            let frame_index: u64 = tokens.next_value()?;
+
+            // the timestamp is only informative, replays are driven by the frame index
+            let _timestamp: u64 = tokens.next_value()?;
+
+            if tokens.peek() == Some(END) {
+                last_frame = Some(frame_index);
+                break;
+            }
+
+            events.push_back((frame_index, RecordedEvent::parse(&mut tokens)?));
+        }
+
+        Ok(InputReplay {
+            events,
+            last_frame,
+            frame_index: 0,
+        })
+    }
+
+    /// Events of the next frame, `None` once the recording is finished
+    pub(crate) fn next_frame(&mut self) -> Option<Vec<RecordedEvent>> {
+        let last_frame = self
+            .last_frame
+            .or_else(|| self.events.back().map(|(frame_index, _)| *frame_index))
+            .unwrap_or(0);
+
+        if self.frame_index > last_frame {
+            return None;
+        }
+
+        let mut frame_events = Vec::new();
+
+        while let Some((frame_index, _)) = self.events.front() {
+            if *frame_index > self.frame_index {
+                break;
+            }
+
+            // unwrap is save since we just checked the front
+            frame_events.push(self.events.pop_front().unwrap().1);
+        }
+
+        self.frame_index += 1;
+
+        Some(frame_events)
+    }
+}
+
+struct Tokens<'a> {
+    split: std::str::Split<'a, char>,
+    peeked: Option<&'a str>,
+    line_number: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(line: &'a str, line_number: usize) -> Self {
+        Tokens {
+            split: line.split(' '),
+            peeked: None,
+            line_number,
+        }
+    }
+
+    fn peek(&mut self) -> Option<&'a str> {
+        if self.peeked.is_none() {
+            self.peeked = self.split.next();
+        }
+
+        self.peeked
+    }
+
+    fn next_str(&mut self) -> VerboseResult<&'a str> {
+        match self.peeked.take().or_else(|| self.split.next()) {
+            Some(token) => Ok(token),
+            None => create_error!(format!(
+                "input recording line {}: missing argument",
+                self.line_number
+            )),
+        }
+    }
+
+    fn next_value<T: FromStr>(&mut self) -> VerboseResult<T> {
+        let token = self.next_str()?;
+
+        match token.parse::<T>() {
+            Ok(value) => Ok(value),
+            Err(_) => create_error!(format!(
+                "input recording line {}: error parsing {}",
+                self.line_number, token
+            )),
+        }
+    }
+
+    fn next_string(&mut self) -> VerboseResult<String> {
+        Ok(unescape(self.next_str()?))
+    }
+
+    fn next_keycode(&mut self) -> VerboseResult<Keycode> {
+        let code = self.next_value()?;
+
+        match Keycode::from_i32(code) {
+            Some(keycode) => Ok(keycode),
+            None => create_error!(format!(
+                "input recording line {}: unknown key code {}",
+                self.line_number, code
+            )),
+        }
+    }
+
+    fn next_scancode(&mut self) -> VerboseResult<Option<Scancode>> {
+        let token = self.next_str()?;
+
+        if token == "-" {
+            return Ok(None);
+        }
+
+        match token.parse::<i32>().ok().and_then(Scancode::from_i32) {
+            Some(scancode) => Ok(Some(scancode)),
+            None => create_error!(format!(
+                "input recording line {}: unknown scan code {}",
+                self.line_number, token
+            )),
+        }
+    }
+
+    fn next_mouse_button(&mut self) -> VerboseResult<MouseButton> {
+        let token = self.next_str()?;
+
+        match MouseButton::from_name(token) {
+            Some(button) => Ok(button),
+            None => create_error!(format!(
+                "input recording line {}: unknown mouse button {}",
+                self.line_number, token
+            )),
+        }
+    }
+
+    fn next_button(&mut self) -> VerboseResult<Button> {
+        let token = self.next_str()?;
+
+        match Button::from_string(token) {
+            Some(button) => Ok(button),
+            None => create_error!(format!(
+                "input recording line {}: unknown controller button {}",
+                self.line_number, token
+            )),
+        }
+    }
+
+    fn next_finger(&mut self) -> VerboseResult<TouchpadFinger> {
+        Ok(TouchpadFinger {
+            touchpad: self.next_value()?,
+            finger: self.next_value()?,
+            x: self.next_value()?,
+            y: self.next_value()?,
+            pressure: self.next_value()?,
+        })
+    }
+}
+
+// strings are written as a single token, so spaces and line breaks are escaped
+fn escape(text: &str) -> String {
+    if text.is_empty() {
+        return "\\0".to_string();
+    }
+
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ' ' => escaped.push_str("\\s"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            _ => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+fn unescape(token: &str) -> String {
+    if token == "\\0" {
+        return String::new();
+    }
+
+    let mut text = String::with_capacity(token.len());
+    let mut chars = token.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            text.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('s') => text.push(' '),
+            Some('t') => text.push('\t'),
+            Some('n') => text.push('\n'),
+            Some('r') => text.push('\r'),
+            Some(c) => text.push(c),
+            None => (),
+        }
+    }
+
+    text
+}
+
+#[test]
+fn input_recording_escape_round_trip() {
+    let texts = [
+        "",
+        "plain",
+        " leading and trailing ",
+        "\u{3000}ideographic space\u{3000}",
+        "tab\tnew line\ncarriage return\r",
+        "back\\slash \\s not an escape",
+        "\\0",
+    ];
+
+    for text in texts.iter() {
+        let escaped = escape(text);
+
+        assert!(!escaped.contains(' '), "{:?} contains a space", escaped);
+        assert_eq!(unescape(&escaped), *text);
+    }
+}
+
+#[test]
+fn input_recording_record_replay_round_trip() {
+    let path = std::env::temp_dir().join(format!(
+        "input_recording_round_trip_{}.txt",
+        std::process::id()
+    ));
+    let file_name = path.to_str().unwrap();
+
+    let frames = [
+        vec![
+            RecordedEvent::TextInput("\u{3000}入力\u{3000}".to_string()),
+            RecordedEvent::MouseWheel { x: -1, y: 2 },
+        ],
+        vec![],
+        vec![
+            RecordedEvent::Action(" jump\u{3000}".to_string(), ActionState::Pressed),
+            RecordedEvent::TextInput(String::new()),
+            RecordedEvent::Action("move".to_string(), ActionState::Axis(-0.5)),
+        ],
+    ];
+
+    {
+        let mut recorder = InputRecorder::new(file_name).unwrap();
+
+        for events in frames.iter() {
+            for event in events.iter() {
+                recorder.record(event).unwrap();
+            }
+
+            recorder.next_frame().unwrap();
+        }
+    }
+
+    let mut replay = InputReplay::new(file_name).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    for events in frames.iter() {
+        let replayed = replay.next_frame().unwrap();
+
+        let expected: Vec<String> = events.iter().map(|event| event.write()).collect();
+        let replayed: Vec<String> = replayed.iter().map(|event| event.write()).collect();
+
+        assert_eq!(replayed, expected);
+    }
+
+    // the end marker is written in the frame after the last recorded one
+    assert_eq!(replay.next_frame().map(|events| events.len()), Some(0));
+    assert!(replay.next_frame().is_none());
+}
@@ -4,4 +4,8 @@ pub mod controller;
 pub mod controlleraxis;
 pub mod eventsystem;
 pub mod guidirection;
+pub mod inputmap;
 pub mod mousebutton;
+
+mod inputrecording;
+mod sdl2ext;
@@ -8,3 +8,26 @@ pub enum MouseButton {
     Forward,
     Backward,
 }
+
+impl MouseButton {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            MouseButton::Left => "Left",
+            MouseButton::Middle => "Middle",
+            MouseButton::Right => "Right",
+            MouseButton::Forward => "Forward",
+            MouseButton::Backward => "Backward",
+        }
+    }
+
+    pub(crate) fn from_name(name: &str) -> Option<MouseButton> {
+        match name {
+            "Left" => Some(MouseButton::Left),
+            "Middle" => Some(MouseButton::Middle),
+            "Right" => Some(MouseButton::Right),
+            "Forward" => Some(MouseButton::Forward),
+            "Backward" => Some(MouseButton::Backward),
+            _ => None,
+        }
+    }
+}
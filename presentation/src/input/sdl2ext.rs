@@ -0,0 +1,58 @@
+//! Game controller functions of SDL 2.0.14, which is linked, but not covered by the sdl2 bindings
+
+use sdl2::sys::{SDL_GameController, SDL_SensorType, SDL_bool};
+
+use std::os::raw::{c_float, c_int};
+
+extern "C" {
+    pub fn SDL_GameControllerRumbleTriggers(
+        gamecontroller: *mut SDL_GameController,
+        left_rumble: u16,
+        right_rumble: u16,
+        duration_ms: u32,
+    ) -> c_int;
+
+    pub fn SDL_GameControllerHasLED(gamecontroller: *mut SDL_GameController) -> SDL_bool;
+
+    pub fn SDL_GameControllerSetLED(
+        gamecontroller: *mut SDL_GameController,
+        red: u8,
+        green: u8,
+        blue: u8,
+    ) -> c_int;
+
+    pub fn SDL_GameControllerHasSensor(
+        gamecontroller: *mut SDL_GameController,
+        sensor_type: SDL_SensorType,
+    ) -> SDL_bool;
+
+    pub fn SDL_GameControllerSetSensorEnabled(
+        gamecontroller: *mut SDL_GameController,
+        sensor_type: SDL_SensorType,
+        enabled: SDL_bool,
+    ) -> c_int;
+
+    pub fn SDL_GameControllerGetSensorData(
+        gamecontroller: *mut SDL_GameController,
+        sensor_type: SDL_SensorType,
+        data: *mut c_float,
+        num_values: c_int,
+    ) -> c_int;
+
+    pub fn SDL_GameControllerGetNumTouchpads(gamecontroller: *mut SDL_GameController) -> c_int;
+
+    pub fn SDL_GameControllerGetNumTouchpadFingers(
+        gamecontroller: *mut SDL_GameController,
+        touchpad: c_int,
+    ) -> c_int;
+
+    pub fn SDL_GameControllerGetTouchpadFinger(
+        gamecontroller: *mut SDL_GameController,
+        touchpad: c_int,
+        finger: c_int,
+        state: *mut u8,
+        x: *mut c_float,
+        y: *mut c_float,
+        pressure: *mut c_float,
+    ) -> c_int;
+}
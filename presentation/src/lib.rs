@@ -1,6 +1,7 @@
 #![deny(rust_2018_idioms)]
 #![deny(unused_must_use)]
 
+pub mod headless;
 pub mod input;
 pub mod presentationcore;
 mod renderbackend;
@@ -29,11 +30,23 @@ use crate::prelude::*;
 
 use std::sync::{Arc, Mutex};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct RenderCoreCreateInfo {
     pub usage: VkImageUsageFlagBits,
     pub format: VkFormat,
-    pub vsync: bool,
+
+    /// preferred present modes, the first supported one is used, FIFO is the fallback
+    pub present_modes: Vec<VkPresentModeKHR>,
+
+    /// requested swapchain image count, clamped to the surface limits
+    pub image_count: u32,
+
+    /// preferred color spaces, the first one supported by the surface is used,
+    /// sRGB is the fallback
+    pub color_spaces: Vec<VkColorSpaceKHR>,
+
+    /// only used by the window render core, the other render cores wait for every frame
+    pub frames_in_flight: usize,
 }
 
 pub fn create_render_core(
@@ -64,6 +77,16 @@ pub fn create_render_core(
             let (render_core, target_mode) =
                 vri::openvrrendercore::OpenVRRenderCore::new(vri, device, queue, create_info)?;
 
+            Ok((Box::new(render_core), target_mode))
+        }
+        PresentationBackend::Headless(headless) => {
+            let (render_core, target_mode) = headless::headlessrendercore::HeadlessRenderCore::new(
+                headless,
+                device,
+                queue,
+                create_info,
+            )?;
+
             Ok((Box::new(render_core), target_mode))
         }
     }
@@ -8,14 +8,25 @@ pub use crate::renderbackend::{Eye, TargetMode, VRTransformations};
 
 // input
 pub use crate::input::{
-    controller::Controller, controlleraxis::ControllerAxis, guidirection::GuiDirection,
+    controller::{Controller, ControllerCapabilities, ControllerSensor, TouchpadFinger},
+    controlleraxis::ControllerAxis,
+    guidirection::GuiDirection,
+    inputmap::{ActionState, AnalogInput, InputBinding, InputMap},
     mousebutton::MouseButton,
 };
 
 // wsi
 pub use crate::wsi::windowsystemintegration::{Display, WindowCreateInfo};
 
-pub use sdl2::{controller::Button as ControllerButton, keyboard::Keycode};
+// headless
+pub use crate::headless::{
+    headlessintegration::HeadlessCreateInfo, headlessrendercore::HeadlessRenderCore,
+};
+
+pub use sdl2::{
+    controller::Button as ControllerButton,
+    keyboard::{Keycode, Mod, Scancode},
+};
 
 pub use utilities::prelude::*;
 pub use vulkan_rs::prelude::*;
@@ -1,3 +1,4 @@
+use crate::headless::headlessintegration::{HeadlessCreateInfo, HeadlessIntegration};
 use crate::input::eventsystem::EventSystem;
 use crate::prelude::*;
 use crate::vri::openvrintegration::OpenVRIntegration;
@@ -16,6 +17,7 @@ pub enum PresentationBackend {
     Window(WindowSystemIntegration),
     OpenXR(OpenXRIntegration),
     OpenVR(OpenVRIntegration),
+    Headless(HeadlessIntegration),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -91,21 +93,45 @@ impl PresentationCore {
         // create sdl2 context
         let context = sdl2::init()?;
 
-        Ok(PresentationCore {
-            event_system: EventSystem::new(&context)?,
-
-            backend: match use_vr {
-                Some(vr_mode) => match vr_mode {
-                    VRMode::OpenXR => PresentationBackend::OpenXR(OpenXRIntegration::new(
-                        appl_info.as_openxr_info(),
-                    )?),
-                    VRMode::OpenVR => PresentationBackend::OpenVR(OpenVRIntegration::new()?),
-                },
-                None => PresentationBackend::Window(WindowSystemIntegration::new(
-                    window_create_info,
-                    &context,
-                )?),
+        let backend = match use_vr {
+            Some(vr_mode) => match vr_mode {
+                VRMode::OpenXR => {
+                    PresentationBackend::OpenXR(OpenXRIntegration::new(appl_info.as_openxr_info())?)
+                }
+                VRMode::OpenVR => PresentationBackend::OpenVR(OpenVRIntegration::new()?),
             },
+            None => PresentationBackend::Window(WindowSystemIntegration::new(
+                window_create_info,
+                &context,
+            )?),
+        };
+
+        // text input is handled by the video subsystem of the window
+        let video_subsystem = match &backend {
+            PresentationBackend::Window(wsi) => Some(wsi.video_subsystem()?),
+            _ => None,
+        };
+
+        Ok(PresentationCore {
+            event_system: EventSystem::new(&context, video_subsystem)?,
+
+            backend,
+
+            _sdl_context: Mutex::new(context),
+        })
+    }
+
+    /// Creates a `PresentationCore` without any window or VR runtime,
+    /// rendering is done into device owned images instead
+    pub fn headless(create_info: &HeadlessCreateInfo) -> VerboseResult<PresentationCore> {
+        // sdl2 is only used for event handling here, the video subsystem is never initialized,
+        // therefore no display is required
+        let context = sdl2::init()?;
+
+        Ok(PresentationCore {
+            event_system: EventSystem::new(&context, None)?,
+
+            backend: PresentationBackend::Headless(HeadlessIntegration::new(create_info)?),
 
             _sdl_context: Mutex::new(context),
         })
@@ -129,6 +155,9 @@ impl PresentationCore {
             PresentationBackend::OpenVR(vri) => {
                 vri.activate_vulkan_instance_extensions(extensions)?;
             }
+            PresentationBackend::Headless(headless) => {
+                headless.activate_vulkan_instance_extensions(extensions)?;
+            }
         }
 
         Ok(())
@@ -149,6 +178,9 @@ impl PresentationCore {
             PresentationBackend::OpenVR(vri) => {
                 vri.activate_vulkan_device_extensions(extensions, physical_device)?;
             }
+            PresentationBackend::Headless(headless) => {
+                headless.activate_vulkan_device_extensions(extensions, physical_device)?;
+            }
         }
 
         Ok(())
@@ -6,7 +6,7 @@ use vulkan_rs::prelude::*;
 
 use std::ops::Deref;
 use std::sync::{
-    atomic::{AtomicUsize, Ordering::SeqCst},
+    atomic::{AtomicBool, AtomicUsize, Ordering::SeqCst},
     Arc, Mutex, RwLock,
 };
 
@@ -87,6 +87,11 @@ impl Default for VRTransformations {
     }
 }
 
+const MAX_PROFILER_SCOPES: u32 = 256;
+
+// captured images with the buffers, that they are copied into
+type FrameCapture = TargetMode<(Arc<Image>, Arc<Buffer<u8>>)>;
+
 pub struct RenderBackend {
     device: Arc<Device>,
     queue: Arc<Mutex<Queue>>,
@@ -94,11 +99,20 @@ pub struct RenderBackend {
     // driver provided images
     swapchain_images: Mutex<TargetMode<Vec<Arc<Image>>>>,
     image_count: AtomicUsize,
+    image_layout: VkImageLayout,
 
     clear_color: RwLock<VkClearColorValue>,
 
     cmd_pool: Arc<CommandPool>,
-    command_buffer: Arc<CommandBuffer>,
+    // one command buffer per frame in flight
+    command_buffers: Vec<Arc<CommandBuffer>>,
+
+    gpu_profiler: Arc<GpuProfiler>,
+
+    // the next recorded frame copies its images into host visible buffers
+    capture_requested: AtomicBool,
+    // read back buffers per frame in flight, together with their source images
+    captures: Mutex<Vec<Option<FrameCapture>>>,
 
     scenes: Mutex<Vec<Arc<dyn TScene + Sync + Send>>>,
     post_processes: Mutex<Vec<Arc<dyn PostProcess + Sync + Send>>>,
@@ -109,7 +123,13 @@ impl RenderBackend {
         device: &Arc<Device>,
         queue: &Arc<Mutex<Queue>>,
         images: TargetMode<Vec<Arc<Image>>>,
+        image_layout: VkImageLayout,
+        frames_in_flight: usize,
     ) -> VerboseResult<RenderBackend> {
+        if frames_in_flight == 0 {
+            create_error!("RenderBackend requires at least one frame in flight");
+        }
+
         let image_count = match &images {
             TargetMode::Single(images) => images.len(),
             TargetMode::Stereo(left_images, right_images) => {
@@ -127,8 +147,15 @@ impl RenderBackend {
                 .build(device.clone())?
         };
 
-        // create a new command buffer
-        let command_buffer = CommandPool::allocate_primary_buffer(&command_pool)?;
+        // create a command buffer for every frame in flight
+        let mut command_buffers = Vec::with_capacity(frames_in_flight);
+
+        for _ in 0..frames_in_flight {
+            command_buffers.push(CommandPool::allocate_primary_buffer(&command_pool)?);
+        }
+
+        // a command buffer is finished before it is recorded again
+        let gpu_profiler = GpuProfiler::new(device, frames_in_flight, MAX_PROFILER_SCOPES)?;
 
         Ok(RenderBackend {
             device: device.clone(),
@@ -136,11 +163,17 @@ impl RenderBackend {
 
             swapchain_images: Mutex::new(images),
             image_count: AtomicUsize::new(image_count),
+            image_layout,
 
             clear_color: RwLock::new(VkClearColorValue::float32([0.0, 0.0, 0.0, 1.0])),
 
             cmd_pool: command_pool,
-            command_buffer,
+            command_buffers,
+
+            gpu_profiler,
+
+            capture_requested: AtomicBool::new(false),
+            captures: Mutex::new(vec![None; frames_in_flight]),
 
             scenes: Mutex::new(Vec::new()),
             post_processes: Mutex::new(Vec::new()),
@@ -161,13 +194,35 @@ impl RenderBackend {
         &self.queue
     }
 
+    pub fn gpu_profiler(&self) -> &Arc<GpuProfiler> {
+        &self.gpu_profiler
+    }
+
+    pub fn frames_in_flight(&self) -> usize {
+        self.command_buffers.len()
+    }
+
     pub fn set_clear_color(&self, clear_color: [f32; 4]) -> VerboseResult<()> {
         *self.clear_color.write()? = VkClearColorValue::float32(clear_color);
 
         Ok(())
     }
 
-    pub fn render(&self, image_indices: TargetMode<usize>) -> VerboseResult<Arc<CommandBuffer>> {
+    /// Records the command buffer of `frame_index`, which must not be in use by the GPU anymore
+    pub fn render(
+        &self,
+        image_indices: TargetMode<usize>,
+        frame_index: usize,
+    ) -> VerboseResult<Arc<CommandBuffer>> {
+        let command_buffer = match self.command_buffers.get(frame_index) {
+            Some(command_buffer) => command_buffer,
+            None => create_error!(format!(
+                "frame index {} exceeds frames in flight ({})",
+                frame_index,
+                self.command_buffers.len()
+            )),
+        };
+
         let scenes = self.scenes.lock()?;
 
         // update scenes
@@ -176,26 +231,23 @@ impl RenderBackend {
         }
 
         // begin main command buffer
-        self.command_buffer.begin(VkCommandBufferBeginInfo::new(
+        command_buffer.begin(VkCommandBufferBeginInfo::new(
             VK_COMMAND_BUFFER_USAGE_ONE_TIME_SUBMIT_BIT,
         ))?;
 
+        self.gpu_profiler.begin_frame(command_buffer, frame_index)?;
+
         // clear the current swapchain image
         {
             let swapchain_images = self.swapchain_images.lock()?;
-            let target_layout = VK_IMAGE_LAYOUT_PRESENT_SRC_KHR;
+            let target_layout = self.image_layout;
             let clear_color = self.clear_color.read()?.clone();
 
             match (&image_indices, swapchain_images.deref()) {
                 (TargetMode::Single(image_index), TargetMode::Single(images)) => {
                     let swapchain_image = &images[*image_index];
 
-                    Self::clear_image(
-                        &self.command_buffer,
-                        swapchain_image,
-                        clear_color,
-                        target_layout,
-                    )?;
+                    Self::clear_image(command_buffer, swapchain_image, clear_color, target_layout)?;
                 }
                 (
                     TargetMode::Stereo(left_image_index, right_image_index),
@@ -205,18 +257,13 @@ impl RenderBackend {
                     let right_image = &right_images[*right_image_index];
 
                     Self::clear_image(
-                        &self.command_buffer,
+                        command_buffer,
                         left_image,
                         clear_color.clone(),
                         target_layout,
                     )?;
 
-                    Self::clear_image(
-                        &self.command_buffer,
-                        right_image,
-                        clear_color,
-                        target_layout,
-                    )?;
+                    Self::clear_image(command_buffer, right_image, clear_color, target_layout)?;
                 }
                 _ => create_error!("not fitting target modes!"),
             }
@@ -224,17 +271,27 @@ impl RenderBackend {
 
         // make a call to the connected scenes
         for scene in scenes.iter() {
-            scene.process(&self.command_buffer, &image_indices)?;
+            self.gpu_profiler.scope(command_buffer, scene.name(), || {
+                scene.process(command_buffer, &image_indices, frame_index)
+            })?;
         }
 
         // post processing
         for post_process in self.post_processes.lock()?.iter() {
-            post_process.process(&self.command_buffer, &image_indices)?;
+            self.gpu_profiler
+                .scope(command_buffer, post_process.name(), || {
+                    post_process.process(command_buffer, &image_indices)
+                })?;
         }
 
-        self.command_buffer.end()?;
+        // read back has to happen before the images are handed over to the presentation engine
+        self.record_capture(command_buffer, &image_indices, frame_index)?;
+
+        self.gpu_profiler.end_frame(command_buffer)?;
 
-        Ok(self.command_buffer.clone())
+        command_buffer.end()?;
+
+        Ok(command_buffer.clone())
     }
 
     pub fn resize(
@@ -343,16 +400,112 @@ impl RenderBackend {
         Ok(self.swapchain_images.lock()?.clone())
     }
 
+    pub fn image_layout(&self) -> VkImageLayout {
+        self.image_layout
+    }
+
     pub fn allocate_primary_buffer(&self) -> VerboseResult<Arc<CommandBuffer>> {
         CommandPool::allocate_primary_buffer(&self.cmd_pool)
     }
 
+    pub fn capture_images(
+        &self,
+        image_indices: &TargetMode<usize>,
+    ) -> VerboseResult<TargetMode<image::RgbaImage>> {
+        let swapchain_images = self.swapchain_images.lock()?;
+
+        match (image_indices, swapchain_images.deref()) {
+            (TargetMode::Single(image_index), TargetMode::Single(images)) => {
+                Ok(TargetMode::Single(Image::to_rgba(&images[*image_index])?))
+            }
+            (
+                TargetMode::Stereo(left_image_index, right_image_index),
+                TargetMode::Stereo(left_images, right_images),
+            ) => Ok(TargetMode::Stereo(
+                Image::to_rgba(&left_images[*left_image_index])?,
+                Image::to_rgba(&right_images[*right_image_index])?,
+            )),
+            _ => create_error!("not fitting target modes!"),
+        }
+    }
+
     pub fn allocate_secondary_buffer(&self) -> VerboseResult<Arc<CommandBuffer>> {
         CommandPool::allocate_secondary_buffer(&self.cmd_pool)
     }
+
+    /// The next call of `render` records a copy of its images into host visible buffers
+    pub fn request_capture(&self) {
+        self.capture_requested.store(true, SeqCst);
+    }
+
+    /// Reads back the images, that were captured by the command buffer of `frame_index`
+    ///
+    /// The command buffer has to be finished on the GPU already
+    pub fn take_capture(
+        &self,
+        frame_index: usize,
+    ) -> VerboseResult<Option<TargetMode<image::RgbaImage>>> {
+        let capture = match self.captures.lock()?.get_mut(frame_index) {
+            Some(capture) => capture.take(),
+            None => None,
+        };
+
+        Ok(match capture {
+            Some(TargetMode::Single((image, buffer))) => {
+                Some(TargetMode::Single(Image::read_back_rgba(&image, &buffer)?))
+            }
+            Some(TargetMode::Stereo((left_image, left_buffer), (right_image, right_buffer))) => {
+                Some(TargetMode::Stereo(
+                    Image::read_back_rgba(&left_image, &left_buffer)?,
+                    Image::read_back_rgba(&right_image, &right_buffer)?,
+                ))
+            }
+            None => None,
+        })
+    }
 }
 
 impl RenderBackend {
+    fn record_capture(
+        &self,
+        command_buffer: &Arc<CommandBuffer>,
+        image_indices: &TargetMode<usize>,
+        frame_index: usize,
+    ) -> VerboseResult<()> {
+        let mut captures = self.captures.lock()?;
+
+        if !self.capture_requested.swap(false, SeqCst) {
+            captures[frame_index] = None;
+
+            return Ok(());
+        }
+
+        let swapchain_images = self.swapchain_images.lock()?;
+
+        let capture_image = |image: &Arc<Image>| -> VerboseResult<(Arc<Image>, Arc<Buffer<u8>>)> {
+            let buffer = Image::create_read_back_buffer(image)?;
+            Image::record_copy_to_buffer(image, command_buffer, &buffer)?;
+
+            Ok((image.clone(), buffer))
+        };
+
+        captures[frame_index] = Some(match (image_indices, swapchain_images.deref()) {
+            (TargetMode::Single(image_index), TargetMode::Single(images)) => {
+                TargetMode::Single(capture_image(&images[*image_index])?)
+            }
+            (
+                TargetMode::Stereo(left_image_index, right_image_index),
+                TargetMode::Stereo(left_images, right_images),
+            ) => TargetMode::Stereo(
+                capture_image(&left_images[*left_image_index])?,
+                capture_image(&right_images[*right_image_index])?,
+            ),
+            _ => create_error!("not fitting target modes!"),
+        });
+
+        Ok(())
+    }
+
     #[inline]
     fn clear_image(
         command_buffer: &Arc<CommandBuffer>,
@@ -1,16 +1,27 @@
 use utilities::prelude::*;
 use vulkan_rs::prelude::*;
 
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 use crate::prelude::*;
 
 pub trait TScene {
+    /// used to label the scene in the GPU profiler
+    fn name(&self) -> &str {
+        "TScene"
+    }
+
     fn update(&self) -> VerboseResult<()>;
+
+    /// `frame_index` is in [0, frames in flight), data that is written by the CPU every
+    /// frame can be buffered per frame index, since the GPU may still read the previous ones
     fn process(
         &self,
         command_buffer: &Arc<CommandBuffer>,
         indices: &TargetMode<usize>,
+        frame_index: usize,
     ) -> VerboseResult<()>;
     fn resize(&self) -> VerboseResult<()>;
 }
@@ -19,6 +30,11 @@ pub trait PostProcess {
     /// higher priority means, it is executed earlier
     fn priority(&self) -> u32;
 
+    /// used to label the post process in the GPU profiler
+    fn name(&self) -> &str {
+        "PostProcess"
+    }
+
     fn process(
         &self,
         command_buffer: &Arc<CommandBuffer>,
@@ -55,10 +71,113 @@ pub trait RenderCore: std::fmt::Debug {
 
     // getter
     fn image_count(&self) -> usize;
+
+    /// Number of frames, that can be processed by the GPU while the CPU records the next one
+    fn frames_in_flight(&self) -> usize {
+        1
+    }
+
     fn images(&self) -> VerboseResult<TargetMode<Vec<Arc<Image>>>>;
     fn allocate_primary_buffer(&self) -> VerboseResult<Arc<CommandBuffer>>;
     fn allocate_secondary_buffer(&self) -> VerboseResult<Arc<CommandBuffer>>;
     fn width(&self) -> u32;
     fn height(&self) -> u32;
     fn transformations(&self) -> VerboseResult<Option<(VRTransformations, VRTransformations)>>;
+
+    /// Profiler that times every scene and post process, can be used to open further scopes
+    fn gpu_profiler(&self) -> &Arc<GpuProfiler>;
+
+    // presentation settings
+    /// Present mode of the swapchain, `None` if the render core doesn't present to one
+    fn present_mode(&self) -> VerboseResult<Option<VkPresentModeKHR>> {
+        Ok(None)
+    }
+
+    fn supported_present_modes(&self) -> VerboseResult<Vec<VkPresentModeKHR>> {
+        Ok(Vec::new())
+    }
+
+    /// Recreates the swapchain with the first supported mode of `present_modes`
+    /// and `image_count` images, returns the selected present mode
+    fn set_present_mode(
+        &self,
+        _present_modes: &[VkPresentModeKHR],
+        _image_count: u32,
+    ) -> VerboseResult<VkPresentModeKHR> {
+        create_error!("render core has no swapchain")
+    }
+
+    /// Color space of the swapchain, `None` if the render core doesn't present to one
+    fn color_space(&self) -> VerboseResult<Option<VkColorSpaceKHR>> {
+        Ok(None)
+    }
+
+    /// Sets the mastering metadata, that is sent to an HDR display
+    fn set_hdr_metadata(&self, _metadata: HdrMetadata) -> VerboseResult<()> {
+        create_error!("render core has no swapchain")
+    }
+
+    /// CPU time spent presenting the last frame, which is part of `next_frame`
+    fn last_present_duration(&self) -> Duration {
+        Duration::default()
+    }
+
+    // frame read back
+    /// Reads the most recently rendered frame back into host memory
+    ///
+    /// Render cores, whose images are owned by a presentation engine after present,
+    /// render and read back the next frame instead
+    fn capture_frame(&self) -> VerboseResult<TargetMode<image::RgbaImage>>;
+
+    /// Writes the most recently rendered frame into a file, the encoding is chosen by the
+    /// file extension (e.g. `png` or `ppm`)
+    ///
+    /// Stereo frames are written into two files, suffixed with `_left` and `_right`
+    fn screenshot(&self, path: &str) -> VerboseResult<()> {
+        match self.capture_frame()? {
+            TargetMode::Single(frame) => save_frame(frame, path)?,
+            TargetMode::Stereo(left_frame, right_frame) => {
+                save_frame(left_frame, &suffixed_path(path, "_left"))?;
+                save_frame(right_frame, &suffixed_path(path, "_right"))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn save_frame(frame: image::RgbaImage, path: &str) -> VerboseResult<()> {
+    let is_ppm = match Path::new(path).extension() {
+        Some(extension) => extension.eq_ignore_ascii_case("ppm"),
+        None => false,
+    };
+
+    // PPM can't store an alpha channel
+    let result = if is_ppm {
+        image::DynamicImage::ImageRgba8(frame).to_rgb().save(path)
+    } else {
+        frame.save(path)
+    };
+
+    if let Err(err) = result {
+        create_error!(format!("failed writing frame to {}: {:?}", path, err));
+    }
+
+    Ok(())
+}
+
+fn suffixed_path(path: &str, suffix: &str) -> String {
+    let path = Path::new(path);
+
+    let file_name = match path.file_stem() {
+        Some(stem) => format!("{}{}", stem.to_string_lossy(), suffix),
+        None => suffix.to_string(),
+    };
+
+    let file_name = match path.extension() {
+        Some(extension) => format!("{}.{}", file_name, extension.to_string_lossy()),
+        None => file_name,
+    };
+
+    path.with_file_name(file_name).to_string_lossy().to_string()
 }
@@ -145,6 +145,14 @@ pub mod openvrrendercore {
         fn transformations(&self) -> VerboseResult<Option<(VRTransformations, VRTransformations)>> {
             unimplemented!()
         }
+
+        fn gpu_profiler(&self) -> &Arc<GpuProfiler> {
+            unimplemented!()
+        }
+
+        fn capture_frame(&self) -> VerboseResult<TargetMode<image::RgbaImage>> {
+            unimplemented!()
+        }
     }
 
     impl std::fmt::Debug for OpenVRRenderCore {
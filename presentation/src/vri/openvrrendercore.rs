@@ -58,7 +58,13 @@ impl OpenVRRenderCore {
         )?;
 
         let images = TargetMode::Stereo(vec![left_image], vec![right_image]);
-        let render_backend = RenderBackend::new(device, queue, images.clone())?;
+        let render_backend = RenderBackend::new(
+            device,
+            queue,
+            images.clone(),
+            VK_IMAGE_LAYOUT_PRESENT_SRC_KHR,
+            1,
+        )?;
 
         let openvr_render_core = OpenVRRenderCore {
             compositor: vri.compositor().clone(),
@@ -254,7 +260,7 @@ impl RenderCore for OpenVRRenderCore {
 
         let command_buffer = self
             .render_backend
-            .render(self.current_image_indices.clone())?;
+            .render(self.current_image_indices.clone(), 0)?;
 
         let submits = &[SubmitInfo::default().add_command_buffer(&command_buffer)];
 
@@ -347,6 +353,15 @@ impl RenderCore for OpenVRRenderCore {
     fn transformations(&self) -> VerboseResult<Option<(VRTransformations, VRTransformations)>> {
         Ok(Some(self.transformations.read()?.clone()))
     }
+
+    fn gpu_profiler(&self) -> &Arc<GpuProfiler> {
+        self.render_backend.gpu_profiler()
+    }
+
+    fn capture_frame(&self) -> VerboseResult<TargetMode<image::RgbaImage>> {
+        self.render_backend
+            .capture_images(&self.current_image_indices)
+    }
 }
 
 impl std::fmt::Debug for OpenVRRenderCore {
@@ -9,24 +9,30 @@ use std::sync::{
     atomic::{AtomicUsize, Ordering::SeqCst},
     Arc, Mutex,
 };
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::u64;
 
 pub struct VulkanWindowRenderCore {
     // driver provided images
     swapchain: Arc<Swapchain>,
-    _surface: Arc<Surface>,
+    surface: Arc<Surface>,
 
     format: VkFormat,
     usage: VkImageUsageFlagBits,
 
-    image_available_sem: Arc<Semaphore>,
-    render_finished_sem: Arc<Semaphore>,
-    render_fence: Arc<Fence>,
+    // synchronization per frame in flight
+    image_available_sems: Vec<Arc<Semaphore>>,
+    render_finished_sems: Vec<Arc<Semaphore>>,
+    render_fences: Vec<Arc<Fence>>,
+
+    // frame in flight, that rendered into the swapchain image the last time
+    image_frames: Mutex<Vec<Option<usize>>>,
 
     render_backend: RenderBackend,
 
     current_image_index: AtomicUsize,
+    current_frame: AtomicUsize,
+    present_duration: Mutex<Duration>,
 }
 
 impl VulkanWindowRenderCore {
@@ -51,52 +57,80 @@ impl VulkanWindowRenderCore {
             );
         }
 
-        let usage = create_info.usage | RenderBackend::required_image_usage();
+        let mut usage = create_info.usage | RenderBackend::required_image_usage();
+
+        // swapchain images need to be transfer source to be captured
+        if (surface.capabilities(device)?.supportedUsageFlagBits & VK_IMAGE_USAGE_TRANSFER_SRC_BIT)
+            != 0
+        {
+            usage |= VK_IMAGE_USAGE_TRANSFER_SRC_BIT;
+        }
 
         // create swapchain
         let swapchain = Swapchain::new(
             device.clone(),
             &surface,
-            create_info.vsync,
-            2,
+            &create_info.present_modes,
+            create_info.image_count,
             usage,
             create_info.format,
+            &create_info.color_spaces,
             1,
         )?;
 
         let swapchain_images = Self::create_swapchain_images(&swapchain, device, queue, usage)?;
 
-        let render_sem = Semaphore::new(device.clone())?;
-        let image_sem = Semaphore::new(device.clone())?;
-        let fence = Fence::builder().build(device.clone())?;
+        let frames_in_flight = create_info.frames_in_flight;
+        let image_count = swapchain_images.len();
+
+        let mut render_sems = Vec::with_capacity(frames_in_flight);
+        let mut image_sems = Vec::with_capacity(frames_in_flight);
+        let mut fences = Vec::with_capacity(frames_in_flight);
 
-        let render_backend =
-            RenderBackend::new(device, queue, TargetMode::Single(swapchain_images))?;
+        for _ in 0..frames_in_flight {
+            render_sems.push(Semaphore::new(device.clone())?);
+            image_sems.push(Semaphore::new(device.clone())?);
+
+            // signaled, so that the first use of a frame doesn't block
+            fences.push(Fence::builder().set_signaled(true).build(device.clone())?);
+        }
+
+        let render_backend = RenderBackend::new(
+            device,
+            queue,
+            TargetMode::Single(swapchain_images),
+            VK_IMAGE_LAYOUT_PRESENT_SRC_KHR,
+            frames_in_flight,
+        )?;
 
         let window_render_core = VulkanWindowRenderCore {
             format: swapchain.format()?,
             usage: usage,
 
             swapchain,
-            _surface: surface,
+            surface,
+
+            render_finished_sems: render_sems,
+            image_available_sems: image_sems,
+            render_fences: fences,
 
-            render_finished_sem: render_sem,
-            image_available_sem: image_sem,
-            render_fence: fence,
+            image_frames: Mutex::new(vec![None; image_count]),
 
             render_backend,
 
             current_image_index: AtomicUsize::new(0),
+            current_frame: AtomicUsize::new(0),
+            present_duration: Mutex::new(Duration::default()),
         };
 
         Ok((window_render_core, TargetMode::Single(())))
     }
 
-    fn aquire_next_image_index(&self) -> VerboseResult<()> {
+    fn aquire_next_image_index(&self, frame: usize) -> VerboseResult<()> {
         loop {
             match self.swapchain.acquire_next_image(
                 u64::MAX,
-                Some(&self.image_available_sem),
+                Some(&self.image_available_sems[frame]),
                 None,
             )? {
                 OutOfDate::Ok(index) => {
@@ -110,7 +144,18 @@ impl VulkanWindowRenderCore {
         Ok(())
     }
 
+    fn wait_for_frame(&self, frame: usize) -> VerboseResult<()> {
+        self.render_backend.device().wait_for_fences(
+            &[&self.render_fences[frame]],
+            true,
+            Duration::from_secs(10),
+        )
+    }
+
     fn resize(&self) -> VerboseResult<()> {
+        // the old swapchain images might still be in use
+        self.render_backend.queue().lock()?.wait_idle()?;
+
         self.swapchain.recreate()?;
 
         let swapchain_images = Self::create_swapchain_images(
@@ -126,6 +171,8 @@ impl VulkanWindowRenderCore {
             self.swapchain.height(),
         )?;
 
+        *self.image_frames.lock()? = vec![None; self.render_backend.image_count()];
+
         Ok(())
     }
 
@@ -173,42 +220,66 @@ impl RenderCore for VulkanWindowRenderCore {
     }
 
     fn next_frame(&self) -> VerboseResult<bool> {
-        self.aquire_next_image_index()?;
+        let frame = self.current_frame.load(SeqCst);
+
+        // make sure the command buffer of this frame is ready
+        self.wait_for_frame(frame)?;
+
+        self.aquire_next_image_index(frame)?;
+
+        let image_index = self.current_image_index.load(SeqCst);
+
+        // the image can still be in use by another frame, if there are less images than
+        // frames in flight or the driver returns them out of order
+        {
+            let mut image_frames = self.image_frames.lock()?;
+
+            if let Some(image_frame) = image_frames[image_index] {
+                if image_frame != frame {
+                    self.wait_for_frame(image_frame)?;
+                }
+            }
+
+            image_frames[image_index] = Some(frame);
+        }
 
         let command_buffer = self
             .render_backend
-            .render(TargetMode::Single(self.current_image_index.load(SeqCst)))?;
+            .render(TargetMode::Single(image_index), frame)?;
 
         let submits = &[SubmitInfo::default()
-            .add_wait_semaphore(&self.image_available_sem)
+            .add_wait_semaphore(&self.image_available_sems[frame])
             .add_wait_stage(VK_PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT_BIT)
             .add_command_buffer(&command_buffer)
-            .add_signal_semaphore(&self.render_finished_sem)];
+            .add_signal_semaphore(&self.render_finished_sems[frame])];
+
+        self.render_fences[frame].reset();
 
-        if let OutOfDate::OutOfDate = {
+        let present_result = {
             let queue_lock = self.render_backend.queue().lock()?;
 
-            queue_lock.submit(Some(&self.render_fence), submits)?;
+            queue_lock.submit(Some(&self.render_fences[frame]), submits)?;
+
+            let present_start = Instant::now();
 
-            queue_lock.present(
+            let result = queue_lock.present(
                 &[&self.swapchain],
-                &[self.current_image_index.load(SeqCst) as u32],
-                &[&self.render_finished_sem],
-            )?
-        } {
+                &[image_index as u32],
+                &[&self.render_finished_sems[frame]],
+            )?;
+
+            *self.present_duration.lock()? = present_start.elapsed();
+
+            result
+        };
+
+        self.current_frame
+            .store((frame + 1) % self.render_backend.frames_in_flight(), SeqCst);
+
+        if let OutOfDate::OutOfDate = present_result {
             self.resize()?;
-            self.render_fence.reset();
-            return Ok(true);
         }
 
-        // make sure command_buffer is ready
-        self.render_backend.device().wait_for_fences(
-            &[&self.render_fence],
-            true,
-            Duration::from_secs(10),
-        )?;
-        self.render_fence.reset();
-
         Ok(true)
     }
 
@@ -255,6 +326,10 @@ impl RenderCore for VulkanWindowRenderCore {
         self.render_backend.image_count()
     }
 
+    fn frames_in_flight(&self) -> usize {
+        self.render_backend.frames_in_flight()
+    }
+
     fn images(&self) -> VerboseResult<TargetMode<Vec<Arc<Image>>>> {
         self.render_backend.images()
     }
@@ -278,6 +353,63 @@ impl RenderCore for VulkanWindowRenderCore {
     fn transformations(&self) -> VerboseResult<Option<(VRTransformations, VRTransformations)>> {
         Ok(None)
     }
+
+    fn gpu_profiler(&self) -> &Arc<GpuProfiler> {
+        self.render_backend.gpu_profiler()
+    }
+
+    fn present_mode(&self) -> VerboseResult<Option<VkPresentModeKHR>> {
+        Ok(Some(self.swapchain.present_mode()?))
+    }
+
+    fn supported_present_modes(&self) -> VerboseResult<Vec<VkPresentModeKHR>> {
+        self.surface.present_modes(self.render_backend.device())
+    }
+
+    fn set_present_mode(
+        &self,
+        present_modes: &[VkPresentModeKHR],
+        image_count: u32,
+    ) -> VerboseResult<VkPresentModeKHR> {
+        let present_mode = self.swapchain.reconfigure(present_modes, image_count)?;
+
+        self.resize()?;
+
+        Ok(present_mode)
+    }
+
+    fn color_space(&self) -> VerboseResult<Option<VkColorSpaceKHR>> {
+        Ok(Some(self.swapchain.color_space()?))
+    }
+
+    fn set_hdr_metadata(&self, metadata: HdrMetadata) -> VerboseResult<()> {
+        self.swapchain.set_hdr_metadata(metadata)
+    }
+
+    fn last_present_duration(&self) -> Duration {
+        match self.present_duration.lock() {
+            Ok(duration) => *duration,
+            Err(_) => Duration::default(),
+        }
+    }
+
+    /// Renders the next frame and reads it back
+    ///
+    /// Presented images belong to the presentation engine, so the copy has to be recorded
+    /// into the command buffer of a frame before it is presented.
+    fn capture_frame(&self) -> VerboseResult<TargetMode<image::RgbaImage>> {
+        let frame = self.current_frame.load(SeqCst);
+
+        self.render_backend.request_capture();
+        self.next_frame()?;
+
+        self.wait_for_frame(frame)?;
+
+        match self.render_backend.take_capture(frame)? {
+            Some(capture) => Ok(capture),
+            None => create_error!("frame wasn't captured"),
+        }
+    }
 }
 
 impl std::fmt::Debug for VulkanWindowRenderCore {
@@ -285,3 +417,14 @@ impl std::fmt::Debug for VulkanWindowRenderCore {
         write!(f, "VulkanWindowRenderCore {{ }}")
     }
 }
+
+impl Drop for VulkanWindowRenderCore {
+    fn drop(&mut self) {
+        // frames in flight have to be finished before their resources are destroyed
+        if let Ok(queue) = self.render_backend.queue().lock() {
+            if let Err(err) = queue.wait_idle() {
+                println!("failed waiting for frames in flight: {}", err.message());
+            }
+        }
+    }
+}
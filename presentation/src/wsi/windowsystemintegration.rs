@@ -93,7 +93,7 @@ impl CellRect {
 
 pub struct WindowSystemIntegration {
     // sdl
-    _video_subsystem: Mutex<sdl2::VideoSubsystem>,
+    video_subsystem: Mutex<sdl2::VideoSubsystem>,
     window: Mutex<sdl2::video::Window>,
 
     cursor: Mutex<Option<Cursor>>,
@@ -199,7 +199,7 @@ impl WindowSystemIntegration {
         rect.update_from_window(&window);
 
         let wsi = WindowSystemIntegration {
-            _video_subsystem: Mutex::new(video_subsystem),
+            video_subsystem: Mutex::new(video_subsystem),
             window: Mutex::new(window),
 
             cursor: Mutex::new(None),
@@ -361,6 +361,10 @@ impl WindowSystemIntegration {
         &self.displays
     }
 
+    pub(crate) fn video_subsystem(&self) -> VerboseResult<sdl2::VideoSubsystem> {
+        Ok(self.video_subsystem.lock()?.clone())
+    }
+
     pub fn create_vulkan_surface(&self, instance: &Arc<Instance>) -> VerboseResult<()> {
         let vk_surface = self
             .window
@@ -139,6 +139,8 @@ impl OpenXRRenderCore {
             device,
             queue,
             TargetMode::Stereo(left_swapchain_images, right_swapchain_images),
+            VK_IMAGE_LAYOUT_PRESENT_SRC_KHR,
+            1,
         )?;
 
         // query blend mode
@@ -441,7 +443,7 @@ impl RenderCore for OpenXRRenderCore {
         if state.should_render {
             let command_buffer = self
                 .render_backend
-                .render(self.current_image_indices.read()?.clone())?;
+                .render(self.current_image_indices.read()?.clone(), 0)?;
 
             let submits = &[SubmitInfo::default()
                 .add_command_buffer(command_buffer)
@@ -573,6 +575,15 @@ impl RenderCore for OpenXRRenderCore {
     fn transformations(&self) -> VerboseResult<Option<(VRTransformations, VRTransformations)>> {
         Ok(Some(self.transformations.read()?.clone()))
     }
+
+    fn gpu_profiler(&self) -> &Arc<GpuProfiler> {
+        self.render_backend.gpu_profiler()
+    }
+
+    fn capture_frame(&self) -> VerboseResult<TargetMode<image::RgbaImage>> {
+        // swapchain images are released to the runtime at the end of each frame
+        create_error!("OpenXR swapchain images can't be captured outside of a frame")
+    }
 }
 
 impl std::fmt::Debug for OpenXRRenderCore {
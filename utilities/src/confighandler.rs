@@ -117,9 +117,10 @@ impl ConfigHandler {
                         body: HashMap::new(),
                     });
                 } else {
-                    let mut split = trimmed.split('=');
+                    // values may contain '=' as well
+                    let mut split = trimmed.splitn(2, '=');
 
-                    let key = match split.nth(0) {
+                    let key = match split.next() {
                         Some(key) => key.trim().to_string(),
                         None => {
                             println!("cannot get key from line: {}", trimmed);
@@ -127,7 +128,7 @@ impl ConfigHandler {
                         }
                     };
 
-                    let value = match split.last() {
+                    let value = match split.next() {
                         Some(value) => value.trim().to_string(),
                         None => {
                             println!("cannot get value from line: {}", trimmed);
@@ -230,3 +231,40 @@ impl ConfigHandler {
         Ok(())
     }
 }
+
+#[test]
+fn config_handler_reads_values_as_written() {
+    let file_name = std::env::temp_dir().join(format!("confighandler_{}.conf", std::process::id()));
+    let file_name = file_name.to_str().unwrap();
+
+    std::fs::write(
+        file_name,
+        "ignored = outside of a section\n\
+         [Section]\n\
+         path = C:\\new\\temp\n\
+         title = [WIP] Game\n\
+         equation = a = b\n\
+         line without value\n\
+         array = [x, y, , z]\n",
+    )
+    .unwrap();
+
+    let config = ConfigHandler::read_config(file_name).unwrap();
+    std::fs::remove_file(file_name).unwrap();
+
+    assert_eq!(config.len(), 1);
+
+    let section = &config["Section"];
+
+    assert_eq!(section.len(), 4);
+    assert_eq!(
+        section["path"].to_value::<String>().unwrap(),
+        "C:\\new\\temp"
+    );
+    assert_eq!(section["title"].to_value::<String>().unwrap(), "[WIP] Game");
+    assert_eq!(section["equation"].to_value::<String>().unwrap(), "a = b");
+    assert_eq!(
+        section["array"].to_array::<String>().unwrap(),
+        ["x", "y", "z"]
+    );
+}
@@ -8,6 +8,7 @@ pub mod errortype;
 pub mod future;
 pub mod helperfunctions;
 pub mod prelude;
+pub mod typedconfig;
 //pub mod closures;
 
 pub mod arc_unique_vec;
@@ -25,3 +25,95 @@ macro_rules! create_error {
         return Err(UtilError::from($v));
     };
 }
+
+/// Declares a struct, that is read from and written to a section of a config file
+///
+/// Every field needs a default, that is used if its key is missing. A check and the message
+/// for a failing check can follow the default.
+///
+/// The struct is declared by the macro, an existing struct has to implement `ConfigSection`
+/// by hand instead.
+///
+/// ```ignore
+/// config_section! {
+///     pub struct AudioSettings: "Audio" {
+///         pub volume: f32 = 1.0; where |volume| *volume >= 0.0, "must not be negative",
+///         pub device: Option<String> = None,
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! config_section {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident : $section:literal {
+            $(
+                $(#[$field_meta:meta])*
+                $field_vis:vis $field:ident : $type:ty = $default:expr $(; where $check:expr, $message:expr)?
+            ),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $(
+                $(#[$field_meta])*
+                $field_vis $field: $type,
+            )*
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                $name {
+                    $( $field: $default, )*
+                }
+            }
+        }
+
+        impl $crate::typedconfig::ConfigSection for $name {
+            fn section_name() -> &'static str {
+                $section
+            }
+
+            fn from_document(
+                document: &$crate::typedconfig::ConfigDocument,
+            ) -> $crate::errortype::VerboseResult<Self> {
+                let mut section = Self::default();
+
+                document.check_keys($section, &[ $( stringify!($field) ),* ])?;
+
+                $(
+                    if let Some(value) = document.value::<$type>($section, stringify!($field))? {
+                        section.$field = value;
+                    }
+
+                    $(
+                        document.validate(
+                            $section,
+                            stringify!($field),
+                            &section.$field,
+                            $check,
+                            $message,
+                        )?;
+                    )?
+                )*
+
+                Ok(section)
+            }
+
+            fn to_document(
+                &self,
+                document: &mut $crate::typedconfig::ConfigDocument,
+            ) -> $crate::errortype::VerboseResult<()> {
+                $(
+                    document.set(
+                        $section,
+                        stringify!($field),
+                        $crate::typedconfig::ConfigType::to_item(&self.$field),
+                    )?;
+                )*
+
+                Ok(())
+            }
+        }
+    };
+}
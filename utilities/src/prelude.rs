@@ -6,10 +6,12 @@ pub use crate::helperfunctions::*;
 
 // macros
 pub use crate::check_and_return;
+pub use crate::config_section;
 pub use crate::create_error;
 
 // config file handler
 pub use crate::confighandler::*;
+pub use crate::typedconfig::*;
 
 // async thread
 pub use crate::asyncthread::AsyncThread;
@@ -0,0 +1,944 @@
+//! Typed config files with the sections and `key = value` entries of `ConfigHandler`
+//! Keeps comments and the ordering of a file, when it is written again
+
+use crate::prelude::*;
+
+use std::fmt;
+use std::fs;
+use std::iter::Peekable;
+use std::path::Path;
+use std::str::Chars;
+
+/// Parsed value of a config entry, arrays can be nested
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigItem {
+    Value(String),
+    Array(Vec<ConfigItem>),
+    /// Nothing behind the `=`, in contrast to the empty string `""`
+    Empty,
+}
+
+impl ConfigItem {
+    fn format(&self) -> String {
+        match self {
+            ConfigItem::Value(value) => {
+                if Self::needs_quotes(value) {
+                    let mut quoted = "\"".to_string();
+
+                    for c in value.chars() {
+                        match c {
+                            '"' => quoted.push_str("\\\""),
+                            '\\' => quoted.push_str("\\\\"),
+                            '\n' => quoted.push_str("\\n"),
+                            '\r' => quoted.push_str("\\r"),
+                            '\t' => quoted.push_str("\\t"),
+                            _ => quoted.push(c),
+                        }
+                    }
+
+                    quoted.push('"');
+
+                    quoted
+                } else {
+                    value.clone()
+                }
+            }
+            ConfigItem::Array(items) => {
+                let formatted: Vec<String> = items.iter().map(ConfigItem::format).collect();
+
+                // a trailing comma is ignored, so an empty last item needs one more
+                match items.last() {
+                    Some(ConfigItem::Empty) => format!("[{},]", formatted.join(", ")),
+                    _ => format!("[{}]", formatted.join(", ")),
+                }
+            }
+            ConfigItem::Empty => String::new(),
+        }
+    }
+
+    fn needs_quotes(value: &str) -> bool {
+        value.is_empty()
+            || value.starts_with(char::is_whitespace)
+            || value.ends_with(char::is_whitespace)
+            || value.starts_with('[')
+            || value.contains(&['=', ',', ']', '#', '"', '\\', '\n', '\r', '\t'][..])
+    }
+}
+
+impl fmt::Display for ConfigItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.format())
+    }
+}
+
+/// Conversion between a type and a `ConfigItem`
+pub trait ConfigType: Sized {
+    fn from_item(item: &ConfigItem) -> Result<Self, String>;
+    fn to_item(&self) -> ConfigItem;
+}
+
+macro_rules! impl_config_type {
+    ( $($type:ty),+ ) => {
+        $(
+            impl ConfigType for $type {
+                fn from_item(item: &ConfigItem) -> Result<Self, String> {
+                    let value = match item {
+                        ConfigItem::Value(value) => value.as_str(),
+                        ConfigItem::Empty => "",
+                        ConfigItem::Array(_) => {
+                            return Err(format!("expected {}, found an array", stringify!($type)))
+                        }
+                    };
+
+                    match value.parse::<$type>() {
+                        Ok(value) => Ok(value),
+                        Err(_) => Err(format!("'{}' is not a valid {}", value, stringify!($type))),
+                    }
+                }
+
+                fn to_item(&self) -> ConfigItem {
+                    ConfigItem::Value(self.to_string())
+                }
+            }
+        )*
+    }
+}
+
+impl_config_type!(bool, char, String, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+impl<T: ConfigType> ConfigType for Vec<T> {
+    fn from_item(item: &ConfigItem) -> Result<Self, String> {
+        match item {
+            ConfigItem::Array(items) => items.iter().map(T::from_item).collect(),
+            ConfigItem::Value(value) => Err(format!("expected an array, found '{}'", value)),
+            ConfigItem::Empty => Err("expected an array, found nothing".to_string()),
+        }
+    }
+
+    fn to_item(&self) -> ConfigItem {
+        ConfigItem::Array(self.iter().map(T::to_item).collect())
+    }
+}
+
+/// A missing value (`key =`) is `None`, `Some(String::new())` is written as `""`
+impl<T: ConfigType> ConfigType for Option<T> {
+    fn from_item(item: &ConfigItem) -> Result<Self, String> {
+        match item {
+            ConfigItem::Empty => Ok(None),
+            _ => T::from_item(item).map(Some),
+        }
+    }
+
+    fn to_item(&self) -> ConfigItem {
+        match self {
+            Some(value) => value.to_item(),
+            None => ConfigItem::Empty,
+        }
+    }
+}
+
+/// Struct, that is stored in a section of a config file, usually declared with `config_section!`
+pub trait ConfigSection: Default {
+    fn section_name() -> &'static str;
+    fn from_document(document: &ConfigDocument) -> VerboseResult<Self>;
+    fn to_document(&self, document: &mut ConfigDocument) -> VerboseResult<()>;
+
+    /// Reads the section from a file, a missing file results in the defaults
+    fn load(file_name: &str) -> VerboseResult<Self> {
+        if !Path::new(file_name).exists() {
+            return Ok(Self::default());
+        }
+
+        Self::from_document(&ConfigDocument::read(file_name)?)
+    }
+
+    /// Writes the section into a file, everything else in the file is kept
+    fn save(&self, file_name: &str) -> VerboseResult<()> {
+        let mut document = if Path::new(file_name).exists() {
+            ConfigDocument::read(file_name)?
+        } else {
+            ConfigDocument::new()
+        };
+
+        self.to_document(&mut document)?;
+
+        document.write(file_name)
+    }
+}
+
+#[derive(Clone, Debug)]
+enum ConfigLine {
+    // blank lines and comments
+    Text(String),
+
+    Section {
+        name: String,
+        text: String,
+        line: Option<usize>,
+    },
+
+    Entry {
+        key: String,
+        item: ConfigItem,
+        comment: Option<String>,
+        // original text, as long as the item wasn't changed
+        text: Option<String>,
+        line: Option<usize>,
+    },
+}
+
+/// Config file, that keeps comments and ordering
+///
+/// Values can be quoted (`"a, b"`) and a backslash escapes one of `= , ] # " \`, `\n`, `\r`
+/// and `\t` (`a\, b`). Before any other character the backslash is kept, e.g. `C:\Users\me`.
+/// `#` starts a comment, if it follows whitespace, and directly behind the `=` only if
+/// whitespace follows as well, so `color = #ff0000` is a value.
+///
+/// `ConfigHandler` keeps reading values as they are written. Its files are read the same here,
+/// except for values containing a `"` or a `#` behind whitespace, a backslash before one of the
+/// escaped characters (`C:\new` has to be written as `C:\\new`), a value starting with `[`
+/// that isn't an array (`[WIP] Game` has to be quoted) and lines without a `=` or outside of a
+/// section, which are errors here.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigDocument {
+    lines: Vec<ConfigLine>,
+}
+
+impl ConfigDocument {
+    pub fn new() -> Self {
+        ConfigDocument::default()
+    }
+
+    pub fn read(file_name: &str) -> VerboseResult<Self> {
+        let text = match fs::read_to_string(file_name) {
+            Ok(text) => text,
+            Err(msg) => create_error!(format!("error opening config file({}): {}", file_name, msg)),
+        };
+
+        match Self::parse_lines(&text) {
+            Ok(lines) => Ok(ConfigDocument { lines }),
+            Err(msg) => create_error!(format!("error in config file({}): {}", file_name, msg)),
+        }
+    }
+
+    /// Errors contain the line number
+    pub fn parse(text: &str) -> VerboseResult<Self> {
+        match Self::parse_lines(text) {
+            Ok(lines) => Ok(ConfigDocument { lines }),
+            Err(msg) => create_error!(msg),
+        }
+    }
+
+    pub fn write(&self, file_name: &str) -> VerboseResult<()> {
+        if let Err(msg) = fs::write(file_name, self.to_string()) {
+            create_error!(format!("error writing config file({}): {}", file_name, msg));
+        }
+
+        Ok(())
+    }
+
+    /// Names of all sections in order of the file
+    pub fn sections(&self) -> Vec<&str> {
+        let mut sections = Vec::new();
+
+        for line in self.lines.iter() {
+            if let ConfigLine::Section { name, .. } = line {
+                if !sections.contains(&name.as_str()) {
+                    sections.push(name.as_str());
+                }
+            }
+        }
+
+        sections
+    }
+
+    /// Keys of a section in order of the file
+    pub fn keys(&self, section: &str) -> Vec<&str> {
+        let mut keys = Vec::new();
+
+        for index in self.entries(section) {
+            if let ConfigLine::Entry { key, .. } = &self.lines[index] {
+                if !keys.contains(&key.as_str()) {
+                    keys.push(key.as_str());
+                }
+            }
+        }
+
+        keys
+    }
+
+    /// If a key is set multiple times, the last one is used
+    pub fn get(&self, section: &str, key: &str) -> Option<&ConfigItem> {
+        match self.find_entry(section, key) {
+            Some(index) => match &self.lines[index] {
+                ConfigLine::Entry { item, .. } => Some(item),
+                _ => None,
+            },
+            None => None,
+        }
+    }
+
+    /// Line number of an entry, `None` for entries, that were added after reading
+    pub fn line(&self, section: &str, key: &str) -> Option<usize> {
+        match self.find_entry(section, key) {
+            Some(index) => match &self.lines[index] {
+                ConfigLine::Entry { line, .. } => *line,
+                _ => None,
+            },
+            None => None,
+        }
+    }
+
+    /// Converts an entry, `None` if the key is missing
+    pub fn value<T: ConfigType>(&self, section: &str, key: &str) -> VerboseResult<Option<T>> {
+        match self.get(section, key) {
+            Some(item) => match T::from_item(item) {
+                Ok(value) => Ok(Some(value)),
+                Err(msg) => Err(self.error(section, key, &msg)),
+            },
+            None => Ok(None),
+        }
+    }
+
+    /// Changes the value of an entry, but keeps its comment
+    ///
+    /// New keys are added at the end of their section, new sections at the end of the file.
+    /// Keys and section names must not contain `= # [ ] "` or line breaks.
+    pub fn set(&mut self, section: &str, key: &str, new_item: ConfigItem) -> VerboseResult<()> {
+        Self::check_name("section", section)?;
+        Self::check_name("key", key)?;
+
+        if let Some(index) = self.find_entry(section, key) {
+            if let ConfigLine::Entry { item, text, .. } = &mut self.lines[index] {
+                // keeps the original formatting
+                if *item != new_item {
+                    *item = new_item;
+                    *text = None;
+                }
+            }
+
+            return Ok(());
+        }
+
+        let entry = ConfigLine::Entry {
+            key: key.to_string(),
+            item: new_item,
+            comment: None,
+            text: None,
+            line: None,
+        };
+
+        match self.section_end(section) {
+            Some(index) => self.lines.insert(index, entry),
+            None => {
+                if let Some(ConfigLine::Entry { .. }) = self.lines.last() {
+                    self.lines.push(ConfigLine::Text(String::new()));
+                }
+
+                self.lines.push(ConfigLine::Section {
+                    name: section.to_string(),
+                    text: format!("[{}]", section),
+                    line: None,
+                });
+
+                self.lines.push(entry);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn remove(&mut self, section: &str, key: &str) -> Option<ConfigItem> {
+        let mut removed = None;
+
+        for index in self.entries(section).into_iter().rev() {
+            if let ConfigLine::Entry { key: entry_key, .. } = &self.lines[index] {
+                if entry_key != key {
+                    continue;
+                }
+            }
+
+            if let ConfigLine::Entry { item, .. } = self.lines.remove(index) {
+                if removed.is_none() {
+                    removed = Some(item);
+                }
+            }
+        }
+
+        removed
+    }
+
+    /// Fails for keys of the section, that aren't in `known_keys`
+    pub fn check_keys(&self, section: &str, known_keys: &[&str]) -> VerboseResult<()> {
+        for key in self.keys(section) {
+            if !known_keys.contains(&key) {
+                return Err(self.error(section, key, "unknown key"));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fails with `message` and the line of the entry, if `check` returns false
+    pub fn validate<T>(
+        &self,
+        section: &str,
+        key: &str,
+        value: &T,
+        check: impl Fn(&T) -> bool,
+        message: &str,
+    ) -> VerboseResult<()> {
+        if check(value) {
+            Ok(())
+        } else {
+            Err(self.error(section, key, message))
+        }
+    }
+
+    // names can't be escaped, since they are not parsed as values
+    fn check_name(kind: &str, name: &str) -> VerboseResult<()> {
+        if name.is_empty()
+            || name.trim() != name
+            || name.contains(&['=', '#', '[', ']', '"', '\n', '\r'][..])
+        {
+            create_error!(format!("invalid {} name '{}'", kind, name));
+        }
+
+        Ok(())
+    }
+
+    fn error(&self, section: &str, key: &str, message: &str) -> UtilError {
+        // missing keys report the line of their section
+        let line = self.line(section, key).or_else(|| {
+            self.lines.iter().find_map(|line| match line {
+                ConfigLine::Section { name, line, .. } if name == section => *line,
+                _ => None,
+            })
+        });
+
+        match line {
+            Some(line) => {
+                UtilError::from(format!("line {}: [{}] {}: {}", line, section, key, message))
+            }
+            None => UtilError::from(format!("[{}] {}: {}", section, key, message)),
+        }
+    }
+
+    // indices of all entries of a section
+    fn entries(&self, section: &str) -> Vec<usize> {
+        let mut entries = Vec::new();
+        let mut current = None;
+
+        for (index, line) in self.lines.iter().enumerate() {
+            match line {
+                ConfigLine::Section { name, .. } => current = Some(name.as_str()),
+                ConfigLine::Entry { .. } if current == Some(section) => entries.push(index),
+                _ => (),
+            }
+        }
+
+        entries
+    }
+
+    fn find_entry(&self, section: &str, key: &str) -> Option<usize> {
+        self.entries(section)
+            .into_iter()
+            .rev()
+            .find(|index| match &self.lines[*index] {
+                ConfigLine::Entry { key: entry_key, .. } => entry_key == key,
+                _ => false,
+            })
+    }
+
+    // index behind the last entry of the section
+    fn section_end(&self, section: &str) -> Option<usize> {
+        let mut end = None;
+        let mut current = None;
+
+        for (index, line) in self.lines.iter().enumerate() {
+            match line {
+                ConfigLine::Section { name, .. } => {
+                    current = Some(name.as_str());
+
+                    if name == section {
+                        end = Some(index + 1);
+                    }
+                }
+                ConfigLine::Entry { .. } if current == Some(section) => end = Some(index + 1),
+                _ => (),
+            }
+        }
+
+        end
+    }
+
+    fn parse_lines(text: &str) -> Result<Vec<ConfigLine>, String> {
+        let mut lines = Vec::new();
+        let mut in_section = false;
+
+        for (index, line) in text.lines().enumerate() {
+            let number = index + 1;
+
+            match Self::parse_line(line, number, in_section) {
+                Ok(line) => {
+                    if let ConfigLine::Section { .. } = line {
+                        in_section = true;
+                    }
+
+                    lines.push(line);
+                }
+                Err(msg) => return Err(format!("line {}: {}", number, msg)),
+            }
+        }
+
+        Ok(lines)
+    }
+
+    fn parse_line(line: &str, number: usize, in_section: bool) -> Result<ConfigLine, String> {
+        let trimmed = line.trim();
+
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            return Ok(ConfigLine::Text(line.to_string()));
+        }
+
+        let (content, comment) = split_comment(trimmed)?;
+
+        if content.starts_with('[') {
+            if !content.ends_with(']') {
+                return Err("section header is missing ']'".to_string());
+            }
+
+            return Ok(ConfigLine::Section {
+                name: content[1..content.len() - 1].trim().to_string(),
+                text: line.to_string(),
+                line: Some(number),
+            });
+        }
+
+        if !in_section {
+            return Err("entry outside of a section".to_string());
+        }
+
+        let (key, value) = match content.find('=') {
+            Some(position) => (content[..position].trim(), &content[position + 1..]),
+            None => return Err("expected 'key = value'".to_string()),
+        };
+
+        if key.is_empty() {
+            return Err("entry is missing a key".to_string());
+        }
+
+        Ok(ConfigLine::Entry {
+            key: key.to_string(),
+            item: ValueParser::parse(value)?,
+            comment: comment.map(|comment| comment.to_string()),
+            text: Some(line.to_string()),
+            line: Some(number),
+        })
+    }
+}
+
+impl fmt::Display for ConfigDocument {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in self.lines.iter() {
+            match line {
+                ConfigLine::Text(text) => writeln!(f, "{}", text)?,
+                ConfigLine::Section { text, .. } => writeln!(f, "{}", text)?,
+                ConfigLine::Entry {
+                    text: Some(text), ..
+                } => writeln!(f, "{}", text)?,
+                ConfigLine::Entry {
+                    key,
+                    item,
+                    comment,
+                    text: None,
+                    ..
+                } => {
+                    let entry = format!("{} = {}", key, item);
+
+                    match comment {
+                        Some(comment) => writeln!(f, "{} {}", entry.trim_end(), comment)?,
+                        None => writeln!(f, "{}", entry.trim_end())?,
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn is_escapable(c: char) -> bool {
+    matches!(c, '=' | ',' | ']' | '#' | '"' | '\\' | 'n' | 'r' | 't')
+}
+
+// splits at the first '#', that starts a comment and is neither quoted nor escaped
+fn split_comment(line: &str) -> Result<(&str, Option<&str>), String> {
+    let mut quoted = false;
+    let mut escaped = false;
+    let mut assigned = false;
+
+    // whether there is text in front of '#', since the start of the line or the first '='
+    let mut has_text = false;
+    let mut after_whitespace = false;
+
+    let mut chars = line.char_indices().peekable();
+
+    while let Some((index, c)) = chars.next() {
+        let next = chars.peek().map(|(_, next)| *next);
+
+        if escaped {
+            escaped = false;
+        } else {
+            match c {
+                '\\' if matches!(next, Some(next) if is_escapable(next)) => escaped = true,
+                '"' => quoted = !quoted,
+                '=' if !quoted && !assigned => {
+                    assigned = true;
+                    has_text = false;
+                    after_whitespace = true;
+
+                    continue;
+                }
+                '#' if !quoted
+                    && after_whitespace
+                    && (has_text || !matches!(next, Some(next) if !next.is_whitespace())) =>
+                {
+                    return Ok((line[..index].trim_end(), Some(&line[index..])));
+                }
+                _ => (),
+            }
+        }
+
+        after_whitespace = c.is_whitespace();
+
+        if !after_whitespace {
+            has_text = true;
+        }
+    }
+
+    if quoted {
+        return Err("string is missing the closing '\"'".to_string());
+    }
+
+    Ok((line, None))
+}
+
+struct ValueParser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> ValueParser<'a> {
+    fn parse(text: &'a str) -> Result<ConfigItem, String> {
+        let mut parser = ValueParser {
+            chars: text.chars().peekable(),
+        };
+
+        parser.skip_whitespace();
+
+        let item = parser.item(false)?;
+
+        parser.skip_whitespace();
+
+        match parser.chars.next() {
+            Some(c) => Err(format!("unexpected '{}' after the value", c)),
+            None => Ok(item),
+        }
+    }
+
+    fn item(&mut self, in_array: bool) -> Result<ConfigItem, String> {
+        match self.chars.peek() {
+            Some('[') => {
+                self.chars.next();
+                self.array()
+            }
+            Some('"') => {
+                self.chars.next();
+                self.quoted()
+            }
+            _ => self.plain(in_array),
+        }
+    }
+
+    fn array(&mut self) -> Result<ConfigItem, String> {
+        let mut items = Vec::new();
+
+        loop {
+            self.skip_whitespace();
+
+            match self.chars.peek() {
+                Some(']') => {
+                    self.chars.next();
+                    return Ok(ConfigItem::Array(items));
+                }
+                None => return Err("array is missing ']'".to_string()),
+                _ => (),
+            }
+
+            items.push(self.item(true)?);
+
+            self.skip_whitespace();
+
+            match self.chars.next() {
+                Some(',') => (),
+                Some(']') => return Ok(ConfigItem::Array(items)),
+                Some(c) => return Err(format!("expected ',' or ']', found '{}'", c)),
+                None => return Err("array is missing ']'".to_string()),
+            }
+        }
+    }
+
+    fn quoted(&mut self) -> Result<ConfigItem, String> {
+        let mut value = String::new();
+
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(ConfigItem::Value(value)),
+                Some('\\') => match self.chars.peek() {
+                    Some(&c) if is_escapable(c) => {
+                        self.chars.next();
+                        value.push(Self::unescape(c));
+                    }
+                    _ => value.push('\\'),
+                },
+                Some(c) => value.push(c),
+                None => return Err("string is missing the closing '\"'".to_string()),
+            }
+        }
+    }
+
+    fn plain(&mut self, in_array: bool) -> Result<ConfigItem, String> {
+        let mut value = String::new();
+        // trailing whitespace is dropped, unless it is escaped
+        let mut length = 0;
+
+        while let Some(&c) = self.chars.peek() {
+            if in_array && (c == ',' || c == ']') {
+                break;
+            }
+
+            self.chars.next();
+
+            match self.chars.peek() {
+                Some(&escaped) if c == '\\' && is_escapable(escaped) => {
+                    self.chars.next();
+                    value.push(Self::unescape(escaped));
+
+                    length = value.len();
+                }
+                _ => {
+                    value.push(c);
+
+                    if !c.is_whitespace() {
+                        length = value.len();
+                    }
+                }
+            }
+        }
+
+        value.truncate(length);
+
+        if value.is_empty() {
+            Ok(ConfigItem::Empty)
+        } else {
+            Ok(ConfigItem::Value(value))
+        }
+    }
+
+    fn unescape(c: char) -> char {
+        match c {
+            'n' => '\n',
+            'r' => '\r',
+            't' => '\t',
+            _ => c,
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.chars.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+
+            self.chars.next();
+        }
+    }
+}
+
+#[test]
+fn typed_config_escaping_round_trip() {
+    let items = [
+        ConfigItem::Value("a = b, c]".to_string()),
+        ConfigItem::Value(" padded # not a comment ".to_string()),
+        ConfigItem::Array(vec![
+            ConfigItem::Value("x,y".to_string()),
+            ConfigItem::Value(String::new()),
+            ConfigItem::Array(vec![ConfigItem::Value("\"quoted\"".to_string())]),
+        ]),
+    ];
+
+    let mut document = ConfigDocument::new();
+
+    for (index, item) in items.iter().enumerate() {
+        document
+            .set("Values", &format!("value{}", index), item.clone())
+            .unwrap();
+    }
+
+    let document = ConfigDocument::parse(&document.to_string()).unwrap();
+
+    for (index, item) in items.iter().enumerate() {
+        assert_eq!(
+            document.get("Values", &format!("value{}", index)),
+            Some(item)
+        );
+    }
+}
+
+#[test]
+fn typed_config_keeps_comments_and_ordering() {
+    let text =
+        "# settings\n[Window]\nwidth = 800 # pixels\nheight = 600\n\n[Audio]\nvolume = 0.5\n";
+
+    let mut document = ConfigDocument::parse(text).unwrap();
+
+    assert_eq!(document.to_string(), text);
+    assert_eq!(document.value::<u32>("Window", "width").unwrap(), Some(800));
+
+    document.set("Window", "width", 1024u32.to_item()).unwrap();
+    document
+        .set("Window", "fullscreen", true.to_item())
+        .unwrap();
+    let keys: Vec<char> = "=,".chars().collect();
+    document.set("Input", "keys", keys.to_item()).unwrap();
+
+    assert_eq!(
+        document.to_string(),
+        "# settings\n[Window]\nwidth = 1024 # pixels\nheight = 600\nfullscreen = true\n\n\
+         [Audio]\nvolume = 0.5\n\n[Input]\nkeys = [\"=\", \",\"]\n"
+    );
+}
+
+#[test]
+fn typed_config_section_defaults_and_errors() {
+    config_section! {
+        struct WindowSettings: "Window" {
+            width: u32 = 1280; where |width| *width > 0, "must not be 0",
+            title: String = "context".to_string(),
+            layers: Vec<Vec<u8>> = Vec::new(),
+            monitor: Option<usize> = None,
+        }
+    }
+
+    let document =
+        ConfigDocument::parse("[Window]\ntitle = \"a, b\"\nlayers = [[1, 2], []]\n").unwrap();
+    let settings = WindowSettings::from_document(&document).unwrap();
+
+    assert_eq!(settings.width, 1280);
+    assert_eq!(settings.title, "a, b");
+    assert_eq!(settings.layers, vec![vec![1, 2], vec![]]);
+    assert_eq!(settings.monitor, None);
+
+    let mut written = ConfigDocument::new();
+    settings.to_document(&mut written).unwrap();
+
+    let reread = WindowSettings::from_document(&written).unwrap();
+    assert_eq!(reread.title, settings.title);
+    assert_eq!(reread.layers, settings.layers);
+
+    let invalid = ConfigDocument::parse("[Window]\n\nwidth = 0\n").unwrap();
+    let err = format!("{}", WindowSettings::from_document(&invalid).err().unwrap());
+    assert!(err.contains("line 3"));
+
+    let unknown = ConfigDocument::parse("[Window]\nwidht = 5\n").unwrap();
+    assert!(WindowSettings::from_document(&unknown).is_err());
+
+    let syntax = ConfigDocument::parse("[Window]\nlayers = [1, 2\n");
+    assert!(format!("{}", syntax.err().unwrap()).contains("line 2"));
+}
+
+#[test]
+fn typed_config_backslashes_and_hashes() {
+    let document = ConfigDocument::parse(
+        "[Paths]\npath = C:\\Users\\me\nescaped = a\\, b\\\\c\ncolor = #ff0000 # red\n\
+         empty = # nothing\ntag = a#b\n",
+    )
+    .unwrap();
+
+    assert_eq!(
+        document.value::<String>("Paths", "path").unwrap(),
+        Some("C:\\Users\\me".to_string())
+    );
+    assert_eq!(
+        document.value::<String>("Paths", "escaped").unwrap(),
+        Some("a, b\\c".to_string())
+    );
+    assert_eq!(
+        document.value::<String>("Paths", "color").unwrap(),
+        Some("#ff0000".to_string())
+    );
+    assert_eq!(document.get("Paths", "empty"), Some(&ConfigItem::Empty));
+    assert_eq!(
+        document.value::<String>("Paths", "tag").unwrap(),
+        Some("a#b".to_string())
+    );
+}
+
+#[test]
+fn typed_config_empty_values_and_names() {
+    let values = [Some("line\r\nbreak".to_string()), Some(String::new()), None];
+    let arrays = [vec![None], vec![Some(1u32), None], vec![None, Some(2)]];
+
+    let mut document = ConfigDocument::new();
+
+    for (index, value) in values.iter().enumerate() {
+        document
+            .set("Values", &format!("value{}", index), value.to_item())
+            .unwrap();
+    }
+
+    for (index, array) in arrays.iter().enumerate() {
+        document
+            .set("Arrays", &format!("array{}", index), array.to_item())
+            .unwrap();
+    }
+
+    let document = ConfigDocument::parse(&document.to_string()).unwrap();
+
+    for (index, value) in values.iter().enumerate() {
+        assert_eq!(
+            &document
+                .value::<Option<String>>("Values", &format!("value{}", index))
+                .unwrap()
+                .unwrap(),
+            value
+        );
+    }
+
+    for (index, array) in arrays.iter().enumerate() {
+        assert_eq!(
+            &document
+                .value::<Vec<Option<u32>>>("Arrays", &format!("array{}", index))
+                .unwrap()
+                .unwrap(),
+            array
+        );
+    }
+
+    let mut document = ConfigDocument::new();
+
+    for (section, key) in &[
+        ("Section", "a=b"),
+        ("Section", "#key"),
+        ("Section", "[key]"),
+        ("Section", ""),
+        ("Sec]tion", "key"),
+        ("[Section", "key"),
+        ("Sec#tion", "key"),
+        ("Sec=tion", "key"),
+    ] {
+        assert!(document.set(section, key, true.to_item()).is_err());
+    }
+
+    assert!(document.sections().is_empty());
+}
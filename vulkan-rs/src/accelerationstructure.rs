@@ -403,6 +403,8 @@ impl AccelerationStructure {
             .set_usage(VK_BUFFER_USAGE_RAY_TRACING_BIT_NV)
             .set_memory_properties(VK_MEMORY_PROPERTY_DEVICE_LOCAL_BIT)
             .force_requirements(memory_requirements)
+            .set_allocation_type(AllocationType::AccelerationStructure)
+            .set_allocation_policy(AllocationPolicy::Dedicated)
             .build(device.clone())?;
 
         // bind the result buffer memory to the acceleration structure
@@ -5,6 +5,34 @@ use std::mem::size_of;
 use std::os::raw::c_void;
 use std::slice;
 
+/// The kind of resource, that requested a `Block`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum AllocationType {
+    Buffer,
+    Image,
+    LinearImage,
+    AccelerationStructure,
+}
+
+impl AllocationType {
+    /// Everything except optimal tiled images counts as linear resource (bufferImageGranularity)
+    pub fn is_linear(&self) -> bool {
+        *self != AllocationType::Image
+    }
+}
+
+/// Decides from where the `DeviceAllocator` takes the memory of a resource
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum AllocationPolicy {
+    /// Sub-allocated from chunks shared with other resources
+    #[default]
+    Chunked,
+    /// Gets its own device memory (VK_KHR_dedicated_allocation if available)
+    Dedicated,
+    /// Bump allocated from a pool, which is only reset once all its blocks are freed
+    LinearPool,
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct Block {
     memory: VkDeviceMemory,
@@ -14,6 +42,9 @@ pub struct Block {
 
     pub(crate) used: bool,
 
+    pub(crate) allocation_type: AllocationType,
+    pub(crate) policy: AllocationPolicy,
+
     mapping: Option<*mut c_void>,
 }
 
@@ -29,6 +60,9 @@ impl Block {
             size,
             used: false,
 
+            allocation_type: AllocationType::Buffer,
+            policy: AllocationPolicy::Chunked,
+
             mapping: None,
         }
     }
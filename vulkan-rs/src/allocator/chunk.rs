@@ -1,18 +1,18 @@
 use crate::prelude::*;
 use utilities::prelude::*;
 
-use super::block::Block;
+use super::block::{AllocationPolicy, AllocationType, Block};
 
 use std::os::raw::c_void;
-use std::sync::Arc;
 
 pub struct Chunk {
-    device: Arc<Device>,
-
     memory: VkDeviceMemory,
     memory_type_index: u32,
     size: VkDeviceSize,
+    buffer_image_granularity: VkDeviceSize,
+    policy: AllocationPolicy,
 
+    // sorted by offset, the blocks cover the complete chunk
     blocks: Vec<Block>,
 
     mapping: Option<*mut c_void>,
@@ -22,17 +22,75 @@ unsafe impl Sync for Chunk {}
 unsafe impl Send for Chunk {}
 
 impl Chunk {
-    pub fn new(
-        device: Arc<Device>,
+    pub fn new(device: &Device, memory_type_index: u32, size: VkDeviceSize) -> VerboseResult<Self> {
+        let memory_ci = VkMemoryAllocateInfo::new(size, memory_type_index);
+
+        Self::from_allocate_info(device, &memory_ci, AllocationPolicy::Chunked)
+    }
+
+    /// Creates a chunk which is meant to hold exactly one resource
+    pub fn dedicated(
+        device: &Device,
         memory_type_index: u32,
         size: VkDeviceSize,
+        dedicated_info: Option<&VkMemoryDedicatedAllocateInfoKHR>,
     ) -> VerboseResult<Self> {
-        let memory_ci = VkMemoryAllocateInfo::new(size, memory_type_index);
+        let mut memory_ci = VkMemoryAllocateInfo::new(size, memory_type_index);
+
+        if let Some(dedicated_info) = dedicated_info {
+            memory_ci.chain(dedicated_info);
+        }
+
+        Self::from_allocate_info(device, &memory_ci, AllocationPolicy::Dedicated)
+    }
+
+    fn from_allocate_info(
+        device: &Device,
+        memory_ci: &VkMemoryAllocateInfo,
+        policy: AllocationPolicy,
+    ) -> VerboseResult<Self> {
+        let (memory, mapping) = Self::allocate_device_memory(device, memory_ci)?;
+
+        Ok(Self::from_memory(
+            memory,
+            memory_ci.memoryTypeIndex,
+            memory_ci.allocationSize,
+            Self::buffer_image_granularity(device),
+            policy,
+            mapping,
+        ))
+    }
+
+    fn from_memory(
+        memory: VkDeviceMemory,
+        memory_type_index: u32,
+        size: VkDeviceSize,
+        buffer_image_granularity: VkDeviceSize,
+        policy: AllocationPolicy,
+        mapping: Option<*mut c_void>,
+    ) -> Self {
+        Chunk {
+            memory,
+            memory_type_index,
+            size,
+            buffer_image_granularity,
+            policy,
+
+            blocks: vec![Block::new(memory, 0, size)],
+
+            mapping,
+        }
+    }
 
-        let memory = device.allocate_memory(&memory_ci)?;
+    /// Allocates device memory and maps it completely, if it is host visible
+    pub(crate) fn allocate_device_memory(
+        device: &Device,
+        memory_ci: &VkMemoryAllocateInfo,
+    ) -> VerboseResult<(VkDeviceMemory, Option<*mut c_void>)> {
+        let memory = device.allocate_memory(memory_ci)?;
 
         let mapping = if (device.physical_device().memory_properties().memoryTypes
-            [memory_type_index as usize]
+            [memory_ci.memoryTypeIndex as usize]
             .propertyFlagBits
             & VK_MEMORY_PROPERTY_HOST_VISIBLE_BIT)
             == VK_MEMORY_PROPERTY_HOST_VISIBLE_BIT
@@ -42,107 +100,290 @@ impl Chunk {
             None
         };
 
-        Ok(Chunk {
-            device,
-
-            memory,
-            memory_type_index,
-            size,
-
-            blocks: vec![Block::new(memory, 0, size)],
+        Ok((memory, mapping))
+    }
 
-            mapping,
-        })
+    pub(crate) fn buffer_image_granularity(device: &Device) -> VkDeviceSize {
+        device
+            .physical_device()
+            .properties()
+            .limits
+            .bufferImageGranularity
     }
 
     pub fn allocate(
         &mut self,
         size: VkDeviceSize,
         alignment: VkDeviceSize,
+        allocation_type: AllocationType,
     ) -> VerboseResult<Option<Block>> {
+        let linear = allocation_type.is_linear();
+
         if self.size < size {
             return Ok(None);
         }
 
-        let mut result_block = None;
-        let mut new_block = None;
+        for i in 0..self.blocks.len() {
+            if self.blocks[i].used {
+                continue;
+            }
+
+            let block_start = self.blocks[i].offset;
+            let block_end = block_start + self.blocks[i].size;
+
+            let mut offset = Self::align_up(block_start, alignment);
 
-        for block in &mut self.blocks {
-            if !block.used {
-                let mut new_size = block.size;
+            // a preceding resource of the other kind must not share a page with this one
+            if i > 0 {
+                let previous = &self.blocks[i - 1];
 
-                if block.offset % alignment != 0 {
-                    new_size -= alignment - block.offset % alignment;
+                if previous.used
+                    && previous.allocation_type.is_linear() != linear
+                    && self.on_same_page(previous.offset + previous.size - 1, offset)
+                {
+                    offset = Self::align_up(offset, self.buffer_image_granularity);
                 }
+            }
+
+            let end = offset + size;
 
-                if new_size >= size {
-                    block.size = new_size;
+            if end > block_end {
+                continue;
+            }
 
-                    // We compute offset and size that care about alignment (for this Block)
-                    if block.offset % alignment != 0 {
-                        block.offset += alignment - block.offset % alignment;
-                    }
+            // same for a following resource of the other kind
+            if i + 1 < self.blocks.len() {
+                let next = &self.blocks[i + 1];
+
+                if next.used
+                    && next.allocation_type.is_linear() != linear
+                    && self.on_same_page(end - 1, next.offset)
+                {
+                    continue;
+                }
+            }
 
-                    // ptr address
-                    if let Some(mapping) = self.mapping {
-                        block.set_host_ptr(Some(unsafe { mapping.offset(block.offset as isize) }));
-                    }
+            let mut block = Block::new(self.memory, offset, size);
+            block.used = true;
+            block.allocation_type = allocation_type;
+            block.policy = self.policy;
 
-                    // check for perfect match
-                    if block.size != size {
-                        // create new empty block at the end
-                        new_block = Some(Block::new(
-                            self.memory,
-                            block.offset + size,
-                            block.size - size,
-                        ));
+            // ptr address
+            if let Some(mapping) = self.mapping {
+                block.set_host_ptr(Some(unsafe { mapping.offset(offset as isize) }));
+            }
 
-                        // set block size
-                        block.size = size;
-                    }
+            // split the free block into padding, the new block and the remainder
+            let mut insert_index = i;
+            self.blocks.remove(i);
 
-                    block.used = true;
+            if offset > block_start {
+                self.blocks.insert(
+                    insert_index,
+                    Block::new(self.memory, block_start, offset - block_start),
+                );
+                insert_index += 1;
+            }
 
-                    result_block = Some(block.clone());
+            self.blocks.insert(insert_index, block.clone());
 
-                    break;
-                }
+            if block_end > end {
+                self.blocks.insert(
+                    insert_index + 1,
+                    Block::new(self.memory, end, block_end - end),
+                );
             }
-        }
 
-        if let Some(block) = new_block {
-            self.blocks.push(block);
+            return Ok(Some(block));
         }
 
-        Ok(result_block)
+        Ok(None)
     }
 
     pub fn deallocate(&mut self, block: &Block) {
         debug_assert!(self.contains(block));
 
-        let internal_block = self
+        let mut index = self
             .blocks
-            .iter_mut()
-            .find(|b| *b == block)
+            .iter()
+            .position(|b| b.offset == block.offset && b.used)
             .expect("wrong chunk!");
 
-        internal_block.used = false;
+        self.blocks[index].used = false;
+        self.blocks[index].set_host_ptr(None);
+
+        // merge with the following free block
+        if index + 1 < self.blocks.len() && !self.blocks[index + 1].used {
+            let next = self.blocks.remove(index + 1);
+            self.blocks[index].size += next.size;
+        }
+
+        // merge with the preceding free block
+        if index > 0 && !self.blocks[index - 1].used {
+            let current = self.blocks.remove(index);
+            index -= 1;
+            self.blocks[index].size += current.size;
+        }
     }
 
     pub fn contains(&self, block: &Block) -> bool {
-        self.blocks.contains(block)
+        block.memory() == self.memory
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.len() == 1 && !self.blocks[0].used
     }
 
     pub fn memory_type_index(&self) -> u32 {
         self.memory_type_index
     }
-}
 
-impl Drop for Chunk {
-    fn drop(&mut self) {
+    pub fn size(&self) -> VkDeviceSize {
+        self.size
+    }
+
+    pub fn used_size(&self) -> VkDeviceSize {
+        self.used_blocks().map(|block| block.size).sum()
+    }
+
+    pub fn largest_free_block(&self) -> VkDeviceSize {
+        self.blocks
+            .iter()
+            .filter(|block| !block.used)
+            .map(|block| block.size)
+            .max()
+            .unwrap_or(0)
+    }
+
+    pub fn used_blocks(&self) -> impl Iterator<Item = &Block> {
+        self.blocks.iter().filter(|block| block.used)
+    }
+
+    /// Frees the device memory of this chunk, all blocks become invalid
+    pub fn destroy(self, device: &Device) {
         if self.mapping.is_some() {
-            self.mapping = None;
-            self.device.unmap_memory(self.memory);
+            device.unmap_memory(self.memory);
+        }
+
+        device.free_memory(self.memory);
+    }
+
+    #[inline]
+    pub(crate) fn align_up(offset: VkDeviceSize, alignment: VkDeviceSize) -> VkDeviceSize {
+        if alignment <= 1 {
+            offset
+        } else {
+            offset.div_ceil(alignment) * alignment
         }
     }
+
+    #[inline]
+    fn on_same_page(&self, end_of_first: VkDeviceSize, start_of_second: VkDeviceSize) -> bool {
+        if self.buffer_image_granularity <= 1 {
+            return false;
+        }
+
+        let page_mask = !(self.buffer_image_granularity - 1);
+
+        (end_of_first & page_mask) == (start_of_second & page_mask)
+    }
+}
+
+#[test]
+fn chunk_coalesces_free_blocks() {
+    let mut chunk = Chunk::from_memory(
+        VkDeviceMemory::NULL_HANDLE,
+        0,
+        1024,
+        1,
+        AllocationPolicy::Chunked,
+        None,
+    );
+
+    let first = chunk
+        .allocate(256, 1, AllocationType::Buffer)
+        .unwrap()
+        .unwrap();
+    let second = chunk
+        .allocate(256, 1, AllocationType::Buffer)
+        .unwrap()
+        .unwrap();
+    let third = chunk
+        .allocate(256, 1, AllocationType::Buffer)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!((first.offset, second.offset, third.offset), (0, 256, 512));
+    assert_eq!(chunk.used_size(), 768);
+
+    // the third block merges with the free rest of the chunk
+    chunk.deallocate(&first);
+    chunk.deallocate(&third);
+
+    assert_eq!(chunk.blocks.len(), 3);
+    assert_eq!(chunk.largest_free_block(), 512);
+    assert!(chunk
+        .allocate(768, 1, AllocationType::Buffer)
+        .unwrap()
+        .is_none());
+
+    // the middle block merges with both neighbours
+    chunk.deallocate(&second);
+
+    assert!(chunk.is_empty());
+    assert_eq!(chunk.largest_free_block(), 1024);
+
+    let block = chunk
+        .allocate(768, 1, AllocationType::Buffer)
+        .unwrap()
+        .unwrap();
+    assert_eq!(block.offset, 0);
+}
+
+#[test]
+fn chunk_respects_buffer_image_granularity() {
+    let mut chunk = Chunk::from_memory(
+        VkDeviceMemory::NULL_HANDLE,
+        0,
+        1024,
+        256,
+        AllocationPolicy::Chunked,
+        None,
+    );
+
+    let first = chunk
+        .allocate(100, 4, AllocationType::Buffer)
+        .unwrap()
+        .unwrap();
+    let second = chunk
+        .allocate(100, 4, AllocationType::Buffer)
+        .unwrap()
+        .unwrap();
+
+    // resources of the same kind may share a page
+    assert_eq!(second.offset, 100);
+
+    chunk.deallocate(&first);
+
+    // the free space in front of the buffer shares its page, an image is moved to the next page
+    let image = chunk
+        .allocate(50, 4, AllocationType::Image)
+        .unwrap()
+        .unwrap();
+    assert_eq!(image.offset, 256);
+
+    // a linear image counts as linear resource, so it fits in front of the buffer
+    let linear_image = chunk
+        .allocate(50, 4, AllocationType::LinearImage)
+        .unwrap()
+        .unwrap();
+    assert_eq!(linear_image.offset, 0);
+
+    // the remaining space in front of the buffer is too small, the page behind the image is
+    // skipped
+    let buffer = chunk
+        .allocate(100, 4, AllocationType::Buffer)
+        .unwrap()
+        .unwrap();
+    assert_eq!(buffer.offset, 512);
 }
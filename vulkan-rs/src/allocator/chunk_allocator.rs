@@ -2,8 +2,7 @@ use crate::prelude::*;
 use utilities::prelude::*;
 
 use super::chunk::Chunk;
-
-use std::sync::Arc;
+use super::linear_chunk::LinearChunk;
 
 pub struct ChunkAllocator {
     size: VkDeviceSize,
@@ -16,19 +15,37 @@ impl ChunkAllocator {
         ChunkAllocator { size }
     }
 
+    pub fn set_size(&mut self, size: VkDeviceSize) {
+        debug_assert!(Self::is_power_of_two(size));
+
+        self.size = size;
+    }
+
     pub fn allocate(
         &self,
-        device: Arc<Device>,
+        device: &Device,
         size: VkDeviceSize,
         memory_type_index: u32,
     ) -> VerboseResult<Chunk> {
-        let size = if size > self.size {
+        Chunk::new(device, memory_type_index, self.chunk_size(size))
+    }
+
+    pub fn allocate_linear(
+        &self,
+        device: &Device,
+        size: VkDeviceSize,
+        memory_type_index: u32,
+    ) -> VerboseResult<LinearChunk> {
+        LinearChunk::new(device, memory_type_index, self.chunk_size(size))
+    }
+
+    #[inline]
+    fn chunk_size(&self, size: VkDeviceSize) -> VkDeviceSize {
+        if size > self.size {
             Self::next_power_of_two(size)
         } else {
             self.size
-        };
-
-        Chunk::new(device, memory_type_index, size)
+        }
     }
 
     #[inline]
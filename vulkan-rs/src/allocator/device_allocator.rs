@@ -1,13 +1,18 @@
 use crate::prelude::*;
 use utilities::prelude::*;
 
-use super::{block::Block, chunk::Chunk, chunk_allocator::ChunkAllocator};
-
-use std::sync::Arc;
+use super::{
+    block::{AllocationPolicy, AllocationType, Block},
+    chunk::Chunk,
+    chunk_allocator::ChunkAllocator,
+    linear_chunk::LinearChunk,
+};
 
 pub struct DeviceAllocator {
     chunk_allocator: ChunkAllocator,
     chunks: Vec<Chunk>,
+    dedicated_chunks: Vec<Chunk>,
+    linear_chunks: Vec<LinearChunk>,
 }
 
 impl DeviceAllocator {
@@ -15,19 +20,27 @@ impl DeviceAllocator {
         DeviceAllocator {
             chunk_allocator: ChunkAllocator::new(size),
             chunks: Vec::new(),
+            dedicated_chunks: Vec::new(),
+            linear_chunks: Vec::new(),
         }
     }
 
+    /// Changes the size of chunks and linear pools, which are created from now on
+    pub fn set_chunk_size(&mut self, size: VkDeviceSize) {
+        self.chunk_allocator.set_size(size);
+    }
+
     pub fn allocate(
         &mut self,
-        device: Arc<Device>,
+        device: &Device,
         size: VkDeviceSize,
         memory_type_index: u32,
         alignment: VkDeviceSize,
+        allocation_type: AllocationType,
     ) -> VerboseResult<Block> {
         for chunk in &mut self.chunks {
             if chunk.memory_type_index() == memory_type_index {
-                if let Some(block) = chunk.allocate(size, alignment)? {
+                if let Some(block) = chunk.allocate(size, alignment, allocation_type)? {
                     return Ok(block);
                 }
             }
@@ -37,7 +50,7 @@ impl DeviceAllocator {
             .chunk_allocator
             .allocate(device, size, memory_type_index)?;
         let block = new_chunk
-            .allocate(size, alignment)?
+            .allocate(size, alignment, allocation_type)?
             .ok_or("couldn't allocate memory")?;
 
         self.chunks.push(new_chunk);
@@ -45,13 +58,144 @@ impl DeviceAllocator {
         Ok(block)
     }
 
-    pub fn deallocate(&mut self, block: &Block) {
-        for chunk in &mut self.chunks {
-            if chunk.contains(block) {
-                chunk.deallocate(block);
+    pub fn allocate_dedicated(
+        &mut self,
+        device: &Device,
+        size: VkDeviceSize,
+        memory_type_index: u32,
+        allocation_type: AllocationType,
+        dedicated_info: Option<&VkMemoryDedicatedAllocateInfoKHR>,
+    ) -> VerboseResult<Block> {
+        let mut chunk = Chunk::dedicated(device, memory_type_index, size, dedicated_info)?;
+
+        // the start of device memory satisfies every alignment requirement
+        let block = chunk
+            .allocate(size, 1, allocation_type)?
+            .ok_or("couldn't allocate dedicated memory")?;
+
+        self.dedicated_chunks.push(chunk);
+
+        Ok(block)
+    }
+
+    pub fn allocate_linear(
+        &mut self,
+        device: &Device,
+        size: VkDeviceSize,
+        memory_type_index: u32,
+        alignment: VkDeviceSize,
+        allocation_type: AllocationType,
+    ) -> VerboseResult<Block> {
+        for chunk in &mut self.linear_chunks {
+            if chunk.memory_type_index() == memory_type_index {
+                if let Some(block) = chunk.allocate(size, alignment, allocation_type) {
+                    return Ok(block);
+                }
+            }
+        }
+
+        let mut new_chunk =
+            self.chunk_allocator
+                .allocate_linear(device, size, memory_type_index)?;
+        let block = new_chunk
+            .allocate(size, alignment, allocation_type)
+            .ok_or("couldn't allocate memory")?;
+
+        self.linear_chunks.push(new_chunk);
+
+        Ok(block)
+    }
 
-                return;
+    pub fn deallocate(&mut self, device: &Device, block: &Block) {
+        match block.policy {
+            AllocationPolicy::Chunked => self.deallocate_chunked(device, block),
+            AllocationPolicy::Dedicated => self.deallocate_dedicated(device, block),
+            AllocationPolicy::LinearPool => self.deallocate_linear(device, block),
+        }
+    }
+
+    fn deallocate_chunked(&mut self, device: &Device, block: &Block) {
+        let index = match self.chunks.iter().position(|chunk| chunk.contains(block)) {
+            Some(index) => index,
+            None => return,
+        };
+
+        self.chunks[index].deallocate(block);
+
+        if self.chunks[index].is_empty() {
+            let memory_type_index = self.chunks[index].memory_type_index();
+
+            // keep one empty chunk per memory type as spare
+            let has_spare = self.chunks.iter().enumerate().any(|(i, chunk)| {
+                i != index && chunk.memory_type_index() == memory_type_index && chunk.is_empty()
+            });
+
+            if has_spare {
+                self.chunks.swap_remove(index).destroy(device);
             }
         }
     }
+
+    fn deallocate_dedicated(&mut self, device: &Device, block: &Block) {
+        if let Some(index) = self
+            .dedicated_chunks
+            .iter()
+            .position(|chunk| chunk.contains(block))
+        {
+            self.dedicated_chunks.swap_remove(index).destroy(device);
+        }
+    }
+
+    fn deallocate_linear(&mut self, device: &Device, block: &Block) {
+        let index = match self
+            .linear_chunks
+            .iter()
+            .position(|chunk| chunk.contains(block))
+        {
+            Some(index) => index,
+            None => return,
+        };
+
+        self.linear_chunks[index].deallocate(block);
+
+        if self.linear_chunks[index].is_empty() {
+            let memory_type_index = self.linear_chunks[index].memory_type_index();
+
+            // keep one empty pool per memory type as spare
+            let has_spare = self.linear_chunks.iter().enumerate().any(|(i, chunk)| {
+                i != index && chunk.memory_type_index() == memory_type_index && chunk.is_empty()
+            });
+
+            if has_spare {
+                self.linear_chunks.swap_remove(index).destroy(device);
+            }
+        }
+    }
+
+    pub fn chunks(&self) -> &[Chunk] {
+        &self.chunks
+    }
+
+    pub fn dedicated_chunks(&self) -> &[Chunk] {
+        &self.dedicated_chunks
+    }
+
+    pub fn linear_chunks(&self) -> &[LinearChunk] {
+        &self.linear_chunks
+    }
+
+    /// This will free all chunks, no matter if their blocks are in use or not
+    pub fn clear(&mut self, device: &Device) {
+        for chunk in self.chunks.drain(..) {
+            chunk.destroy(device);
+        }
+
+        for chunk in self.dedicated_chunks.drain(..) {
+            chunk.destroy(device);
+        }
+
+        for chunk in self.linear_chunks.drain(..) {
+            chunk.destroy(device);
+        }
+    }
 }
@@ -0,0 +1,146 @@
+use crate::prelude::*;
+use utilities::prelude::*;
+
+use super::block::{AllocationPolicy, AllocationType, Block};
+use super::chunk::Chunk;
+
+use std::os::raw::c_void;
+
+/// Chunk that hands out blocks by bumping an offset
+///
+/// Freed blocks are not reused, the whole chunk is reset once all of its blocks are freed
+pub struct LinearChunk {
+    memory: VkDeviceMemory,
+    memory_type_index: u32,
+    size: VkDeviceSize,
+    buffer_image_granularity: VkDeviceSize,
+
+    offset: VkDeviceSize,
+    last_allocation_linear: Option<bool>,
+
+    // blocks in use, sorted by offset
+    blocks: Vec<Block>,
+
+    mapping: Option<*mut c_void>,
+}
+
+unsafe impl Sync for LinearChunk {}
+unsafe impl Send for LinearChunk {}
+
+impl LinearChunk {
+    pub fn new(device: &Device, memory_type_index: u32, size: VkDeviceSize) -> VerboseResult<Self> {
+        let memory_ci = VkMemoryAllocateInfo::new(size, memory_type_index);
+
+        let (memory, mapping) = Chunk::allocate_device_memory(device, &memory_ci)?;
+
+        Ok(LinearChunk {
+            memory,
+            memory_type_index,
+            size,
+            buffer_image_granularity: Chunk::buffer_image_granularity(device),
+
+            offset: 0,
+            last_allocation_linear: None,
+
+            blocks: Vec::new(),
+
+            mapping,
+        })
+    }
+
+    pub fn allocate(
+        &mut self,
+        size: VkDeviceSize,
+        alignment: VkDeviceSize,
+        allocation_type: AllocationType,
+    ) -> Option<Block> {
+        let linear = allocation_type.is_linear();
+
+        let mut offset = Chunk::align_up(self.offset, alignment);
+
+        // everything behind the current offset is unused, so it is sufficient
+        // to start a new page when the kind of resource changes
+        if let Some(last_linear) = self.last_allocation_linear {
+            if last_linear != linear {
+                offset = Chunk::align_up(offset, self.buffer_image_granularity);
+            }
+        }
+
+        let end = offset + size;
+
+        if end > self.size {
+            return None;
+        }
+
+        let mut block = Block::new(self.memory, offset, size);
+        block.used = true;
+        block.allocation_type = allocation_type;
+        block.policy = AllocationPolicy::LinearPool;
+
+        // ptr address
+        if let Some(mapping) = self.mapping {
+            block.set_host_ptr(Some(unsafe { mapping.offset(offset as isize) }));
+        }
+
+        self.offset = end;
+        self.last_allocation_linear = Some(linear);
+        self.blocks.push(block.clone());
+
+        Some(block)
+    }
+
+    pub fn deallocate(&mut self, block: &Block) {
+        debug_assert!(self.contains(block));
+
+        let index = self
+            .blocks
+            .iter()
+            .position(|b| b.offset == block.offset)
+            .expect("wrong chunk!");
+
+        self.blocks.remove(index);
+
+        if self.blocks.is_empty() {
+            self.offset = 0;
+            self.last_allocation_linear = None;
+        }
+    }
+
+    pub fn contains(&self, block: &Block) -> bool {
+        block.memory() == self.memory
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.is_empty()
+    }
+
+    pub fn memory_type_index(&self) -> u32 {
+        self.memory_type_index
+    }
+
+    pub fn size(&self) -> VkDeviceSize {
+        self.size
+    }
+
+    pub fn used_size(&self) -> VkDeviceSize {
+        self.blocks.iter().map(|block| block.size).sum()
+    }
+
+    /// Only the memory behind the current offset can be handed out again
+    pub fn largest_free_block(&self) -> VkDeviceSize {
+        self.size - self.offset
+    }
+
+    pub fn used_blocks(&self) -> impl Iterator<Item = &Block> {
+        self.blocks.iter()
+    }
+
+    /// Frees the device memory of this chunk, all blocks become invalid
+    pub fn destroy(self, device: &Device) {
+        if self.mapping.is_some() {
+            device.unmap_memory(self.memory);
+        }
+
+        device.free_memory(self.memory);
+    }
+}
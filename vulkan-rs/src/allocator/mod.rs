@@ -2,3 +2,4 @@ pub mod block;
 pub mod chunk;
 pub mod chunk_allocator;
 pub mod device_allocator;
+pub mod linear_chunk;
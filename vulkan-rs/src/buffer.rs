@@ -1,11 +1,12 @@
 use utilities::prelude::*;
 
+use crate::allocator::block::{AllocationPolicy, AllocationType};
 use crate::prelude::*;
 use crate::{impl_vk_handle_t, mappedmemory::VkMappedMemory};
 
 use std;
 use std::mem;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 
 pub struct BufferBuilder<'a, T> {
     flags: VkBufferCreateFlagBits,
@@ -16,6 +17,8 @@ pub struct BufferBuilder<'a, T> {
     size: VkDeviceSize,
 
     forced_requirements: Option<VkMemoryRequirements>,
+    allocation_type: AllocationType,
+    allocation_policy: AllocationPolicy,
 }
 
 impl<'a, T> BufferBuilder<'a, T> {
@@ -31,6 +34,18 @@ impl<'a, T> BufferBuilder<'a, T> {
         self
     }
 
+    pub(crate) fn set_allocation_type(mut self, allocation_type: AllocationType) -> Self {
+        self.allocation_type = allocation_type;
+
+        self
+    }
+
+    pub fn set_allocation_policy(mut self, allocation_policy: AllocationPolicy) -> Self {
+        self.allocation_policy = allocation_policy;
+
+        self
+    }
+
     pub fn set_memory_properties(
         mut self,
         set_memory_properties: impl Into<VkMemoryPropertyFlagBits>,
@@ -97,8 +112,16 @@ impl<'a, T: Clone> BufferBuilder<'a, T> {
                 self.set_memory_properties,
                 buffer,
                 memory_requirements,
+                self.allocation_type,
+                self.allocation_policy,
+            )?,
+            None => Memory::buffer_memory(
+                &device,
+                self.set_memory_properties,
+                buffer,
+                self.allocation_type,
+                self.allocation_policy,
             )?,
-            None => Memory::buffer_memory(&device, self.set_memory_properties, buffer)?,
         };
 
         let buffer = Arc::new(Buffer {
@@ -111,6 +134,11 @@ impl<'a, T: Clone> BufferBuilder<'a, T> {
             sharing_mode: self.sharing_mode,
 
             size,
+
+            state: Mutex::new(ResourceState::from_access(
+                0u32,
+                VK_PIPELINE_STAGE_TOP_OF_PIPE_BIT,
+            )),
         });
 
         if let Some(data) = self.data {
@@ -131,6 +159,8 @@ pub struct Buffer<T> {
     set_memory_properties: VkMemoryPropertyFlagBits,
     sharing_mode: VkSharingMode,
     size: VkDeviceSize,
+
+    state: Mutex<ResourceState>,
 }
 
 impl<T: Clone> Buffer<T> {
@@ -183,7 +213,7 @@ impl<T: Clone> Buffer<T> {
             VK_PIPELINE_STAGE_TRANSFER_BIT,
             access_mask,
             stage,
-        );
+        )?;
 
         Ok(device_local_buffer)
     }
@@ -200,6 +230,8 @@ impl<T> Buffer<T> {
             size: 0,
 
             forced_requirements: None,
+            allocation_type: AllocationType::Buffer,
+            allocation_policy: AllocationPolicy::default(),
         }
     }
 
@@ -214,6 +246,38 @@ impl<T> Buffer<T> {
     pub fn size(&self) -> VkDeviceSize {
         self.size
     }
+
+    pub fn state(&self) -> VerboseResult<ResourceState> {
+        Ok(*self.state.lock()?)
+    }
+
+    /// Marks the buffer to be accessed, e.g. after a hand written barrier
+    pub fn set_state(
+        &self,
+        access: impl Into<VkAccessFlagBits>,
+        stage: impl Into<VkPipelineStageFlagBits>,
+    ) -> VerboseResult<()> {
+        *self.state.lock()? = ResourceState::from_access(access, stage);
+
+        Ok(())
+    }
+
+    /// Updates the state and returns source access and stage of the barrier, that is
+    /// needed before `next`, together with the new state
+    pub(crate) fn transition(
+        &self,
+        next: &ResourceState,
+    ) -> VerboseResult<(
+        Option<(VkAccessFlagBits, VkPipelineStageFlagBits)>,
+        ResourceState,
+    )> {
+        let mut state = self.state.lock()?;
+
+        let (barrier, new_state) = state.transition(next);
+        *state = new_state;
+
+        Ok((barrier, new_state))
+    }
 }
 
 impl<T> VulkanDevice for Buffer<T> {
@@ -5,6 +5,7 @@ use crate::prelude::*;
 
 use crate::impl_vk_handle;
 
+use std::mem;
 use std::sync::{Arc, Mutex};
 
 pub struct QueryEnable {
@@ -117,7 +118,10 @@ impl CommandBuffer {
         src_stage: impl Into<VkPipelineStageFlagBits>,
         dst_access_mask: impl Into<VkAccessFlagBits>,
         dst_stage: impl Into<VkPipelineStageFlagBits>,
-    ) {
+    ) -> VerboseResult<()> {
+        let dst_access_mask = dst_access_mask.into();
+        let dst_stage = dst_stage.into();
+
         self.pipeline_barrier(
             src_stage,
             dst_stage,
@@ -134,6 +138,8 @@ impl CommandBuffer {
             )],
             &[],
         );
+
+        buffer.set_state(dst_access_mask, dst_stage)
     }
 
     pub fn image_barrier(
@@ -376,34 +382,29 @@ impl CommandBuffer {
         Ok(())
     }
 
+    /// Transitions the subresources of `subresource_range` into `new_image_layout`,
+    /// the barriers are derived from their tracked states
     pub fn set_image_layout(
         &self,
         image: &Image,
         new_image_layout: VkImageLayout,
         subresource_range: VkImageSubresourceRange,
     ) -> VerboseResult<()> {
-        let src_access = Image::src_layout_to_access(image.image_layout()?);
         let dst_access = Image::dst_layout_to_access(new_image_layout);
 
-        self.pipeline_barrier(
-            Self::access_to_stage(src_access),
-            Self::access_to_stage(dst_access),
-            0,
-            &[],
-            &[],
-            &[VkImageMemoryBarrier::new(
-                src_access,
-                dst_access,
-                image.image_layout()?,
-                new_image_layout,
-                VK_QUEUE_FAMILY_IGNORED,
-                VK_QUEUE_FAMILY_IGNORED,
-                image.vk_handle(),
+        self.barriers()
+            .image_state(
+                image,
                 subresource_range,
-            )],
-        );
+                ResourceState::new(
+                    dst_access,
+                    Self::access_to_stage(dst_access),
+                    new_image_layout,
+                ),
+            )?
+            .record();
 
-        image.set_image_layout(new_image_layout)
+        Ok(())
     }
 
     pub fn set_full_image_layout(
@@ -411,102 +412,242 @@ impl CommandBuffer {
         image: &Arc<Image>,
         new_image_layout: VkImageLayout,
     ) -> VerboseResult<()> {
-        let src_access = Image::src_layout_to_access(image.image_layout()?);
-        let dst_access = Image::dst_layout_to_access(new_image_layout);
+        self.set_image_layout(image, new_image_layout, image.full_resource_range())
+    }
 
-        self.pipeline_barrier(
-            Self::access_to_stage(src_access),
-            Self::access_to_stage(dst_access),
-            0,
-            &[],
-            &[],
-            &[VkImageMemoryBarrier::new(
-                src_access,
-                dst_access,
-                image.image_layout()?,
-                new_image_layout,
-                VK_QUEUE_FAMILY_IGNORED,
-                VK_QUEUE_FAMILY_IGNORED,
-                image.vk_handle(),
-                image.full_resource_range(),
-            )],
-        );
+    /// Starts collecting barriers, which are recorded together
+    pub fn barriers(&self) -> BarrierBatch<'_> {
+        BarrierBatch::new(self)
+    }
 
-        image.set_image_layout(new_image_layout)
+    /// Records the barriers, which are needed before `image` can be used as `usage`
+    pub fn use_image(&self, image: &Arc<Image>, usage: ResourceUsage) -> VerboseResult<()> {
+        self.barriers().image(image, usage)?.record();
+
+        Ok(())
+    }
+
+    /// Records the barrier, which is needed before `buffer` can be used as `usage`
+    pub fn use_buffer<T>(
+        &self,
+        buffer: &Arc<Buffer<T>>,
+        usage: ResourceUsage,
+    ) -> VerboseResult<()> {
+        self.barriers().buffer(buffer, usage)?.record();
+
+        Ok(())
     }
 
-    pub fn access_to_stage(access_mask: impl Into<VkAccessFlagBits>) -> VkPipelineStageFlags {
+    pub fn access_to_stage(access_mask: impl Into<VkAccessFlagBits>) -> VkPipelineStageFlagBits {
         let access_mask = access_mask.into();
 
         if access_mask == 0 {
-            VK_PIPELINE_STAGE_TOP_OF_PIPE_BIT
+            VK_PIPELINE_STAGE_TOP_OF_PIPE_BIT.into()
         } else if access_mask == VK_ACCESS_HOST_WRITE_BIT {
-            VK_PIPELINE_STAGE_HOST_BIT
+            VK_PIPELINE_STAGE_HOST_BIT.into()
         } else if access_mask == VK_ACCESS_TRANSFER_WRITE_BIT
             || access_mask == VK_ACCESS_TRANSFER_READ_BIT
         {
-            VK_PIPELINE_STAGE_TRANSFER_BIT
+            VK_PIPELINE_STAGE_TRANSFER_BIT.into()
         } else if access_mask == VK_ACCESS_SHADER_READ_BIT {
-            VK_PIPELINE_STAGE_FRAGMENT_SHADER_BIT
+            VK_PIPELINE_STAGE_FRAGMENT_SHADER_BIT.into()
         } else if access_mask == VK_ACCESS_COLOR_ATTACHMENT_WRITE_BIT {
-            VK_PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT_BIT
+            VK_PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT_BIT.into()
         } else if access_mask == VK_ACCESS_MEMORY_READ_BIT {
-            VK_PIPELINE_STAGE_TOP_OF_PIPE_BIT
+            VK_PIPELINE_STAGE_TOP_OF_PIPE_BIT.into()
         } else if access_mask == VK_ACCESS_MEMORY_READ_BIT | VK_ACCESS_MEMORY_WRITE_BIT {
-            VK_PIPELINE_STAGE_COMPUTE_SHADER_BIT
+            VK_PIPELINE_STAGE_COMPUTE_SHADER_BIT.into()
         } else if access_mask
             == VK_ACCESS_DEPTH_STENCIL_ATTACHMENT_READ_BIT
                 | VK_ACCESS_DEPTH_STENCIL_ATTACHMENT_WRITE_BIT
         {
-            VK_PIPELINE_STAGE_EARLY_FRAGMENT_TESTS_BIT
+            VK_PIPELINE_STAGE_EARLY_FRAGMENT_TESTS_BIT.into()
         } else {
-            unimplemented!("access mask not supported {:?}", access_mask)
+            Self::combined_access_to_stage(access_mask)
         }
     }
 
-    // TODO:
-    pub fn set_line_width(&self) {
-        unimplemented!();
+    // every stage, that is able to perform one of the accesses
+    fn combined_access_to_stage(access_mask: VkAccessFlagBits) -> VkPipelineStageFlagBits {
+        let shader_stages = VK_PIPELINE_STAGE_VERTEX_SHADER_BIT
+            | VK_PIPELINE_STAGE_FRAGMENT_SHADER_BIT
+            | VK_PIPELINE_STAGE_COMPUTE_SHADER_BIT;
+
+        let mapping = [
+            (
+                VK_ACCESS_INDIRECT_COMMAND_READ_BIT,
+                VK_PIPELINE_STAGE_DRAW_INDIRECT_BIT.into(),
+            ),
+            (
+                VK_ACCESS_INDEX_READ_BIT,
+                VK_PIPELINE_STAGE_VERTEX_INPUT_BIT.into(),
+            ),
+            (
+                VK_ACCESS_VERTEX_ATTRIBUTE_READ_BIT,
+                VK_PIPELINE_STAGE_VERTEX_INPUT_BIT.into(),
+            ),
+            (VK_ACCESS_UNIFORM_READ_BIT, shader_stages),
+            (
+                VK_ACCESS_INPUT_ATTACHMENT_READ_BIT,
+                VK_PIPELINE_STAGE_FRAGMENT_SHADER_BIT.into(),
+            ),
+            (VK_ACCESS_SHADER_READ_BIT, shader_stages),
+            (VK_ACCESS_SHADER_WRITE_BIT, shader_stages),
+            (
+                VK_ACCESS_COLOR_ATTACHMENT_READ_BIT,
+                VK_PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT_BIT.into(),
+            ),
+            (
+                VK_ACCESS_COLOR_ATTACHMENT_WRITE_BIT,
+                VK_PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT_BIT.into(),
+            ),
+            (
+                VK_ACCESS_DEPTH_STENCIL_ATTACHMENT_READ_BIT,
+                VK_PIPELINE_STAGE_EARLY_FRAGMENT_TESTS_BIT
+                    | VK_PIPELINE_STAGE_LATE_FRAGMENT_TESTS_BIT,
+            ),
+            (
+                VK_ACCESS_DEPTH_STENCIL_ATTACHMENT_WRITE_BIT,
+                VK_PIPELINE_STAGE_EARLY_FRAGMENT_TESTS_BIT
+                    | VK_PIPELINE_STAGE_LATE_FRAGMENT_TESTS_BIT,
+            ),
+            (
+                VK_ACCESS_TRANSFER_READ_BIT,
+                VK_PIPELINE_STAGE_TRANSFER_BIT.into(),
+            ),
+            (
+                VK_ACCESS_TRANSFER_WRITE_BIT,
+                VK_PIPELINE_STAGE_TRANSFER_BIT.into(),
+            ),
+            (VK_ACCESS_HOST_READ_BIT, VK_PIPELINE_STAGE_HOST_BIT.into()),
+            (VK_ACCESS_HOST_WRITE_BIT, VK_PIPELINE_STAGE_HOST_BIT.into()),
+            (
+                VK_ACCESS_ACCELERATION_STRUCTURE_READ_BIT_NV,
+                VK_PIPELINE_STAGE_ACCELERATION_STRUCTURE_BUILD_BIT_NV
+                    | VK_PIPELINE_STAGE_RAY_TRACING_SHADER_BIT_NV,
+            ),
+            (
+                VK_ACCESS_ACCELERATION_STRUCTURE_WRITE_BIT_NV,
+                VK_PIPELINE_STAGE_ACCELERATION_STRUCTURE_BUILD_BIT_NV.into(),
+            ),
+        ];
+
+        let mut remaining = access_mask;
+        let mut stages: VkPipelineStageFlagBits = 0u32.into();
+
+        for (access, stage) in mapping.iter() {
+            if (access_mask & *access) != 0 {
+                stages |= *stage;
+                remaining ^= *access;
+            }
+        }
+
+        // anything else (e.g. memory or extension accesses) has to wait for all commands
+        if remaining != 0 {
+            stages |= VK_PIPELINE_STAGE_ALL_COMMANDS_BIT;
+        }
+
+        stages
     }
 
-    pub fn set_depth_bias(&self) {
-        unimplemented!();
+    pub fn set_line_width(&self, line_width: f32) {
+        self.device.cmd_set_line_width(self.buffer, line_width);
     }
 
-    pub fn set_blend_constants(&self) {
-        unimplemented!();
+    pub fn set_depth_bias(
+        &self,
+        depth_bias_constant_factor: f32,
+        depth_bias_clamp: f32,
+        depth_bias_slope_factor: f32,
+    ) {
+        self.device.cmd_set_depth_bias(
+            self.buffer,
+            depth_bias_constant_factor,
+            depth_bias_clamp,
+            depth_bias_slope_factor,
+        );
     }
 
-    pub fn ste_depth_bounds(&self) {
-        unimplemented!();
+    pub fn set_blend_constants(&self, blend_constants: [f32; 4]) {
+        self.device
+            .cmd_set_blend_constants(self.buffer, blend_constants);
     }
 
-    pub fn set_stencil_compare_mask(&self) {
-        unimplemented!();
+    pub fn set_depth_bounds(&self, min_depth_bounds: f32, max_depth_bounds: f32) {
+        self.device
+            .cmd_set_depth_bounds(self.buffer, min_depth_bounds, max_depth_bounds);
     }
 
-    pub fn set_stencil_write_mask(&self) {
-        unimplemented!();
+    pub fn set_stencil_compare_mask(
+        &self,
+        face_mask: impl Into<VkStencilFaceFlagBits>,
+        compare_mask: u32,
+    ) {
+        self.device
+            .cmd_set_stencil_compare_mask(self.buffer, face_mask, compare_mask);
     }
 
-    pub fn set_stencil_reference(&self) {
-        unimplemented!();
+    pub fn set_stencil_write_mask(
+        &self,
+        face_mask: impl Into<VkStencilFaceFlagBits>,
+        write_mask: u32,
+    ) {
+        self.device
+            .cmd_set_stencil_write_mask(self.buffer, face_mask, write_mask);
     }
 
-    pub fn draw_indirect(&self) {
-        unimplemented!();
+    pub fn set_stencil_reference(
+        &self,
+        face_mask: impl Into<VkStencilFaceFlagBits>,
+        reference: u32,
+    ) {
+        self.device
+            .cmd_set_stencil_reference(self.buffer, face_mask, reference);
     }
 
-    pub fn draw_indexed_indirect(&self) {
-        unimplemented!();
+    /// `offset` is given in bytes, the commands are tightly packed
+    pub fn draw_indirect(
+        &self,
+        buffer: &Arc<Buffer<VkDrawIndirectCommand>>,
+        offset: VkDeviceSize,
+        draw_count: u32,
+    ) {
+        self.device.cmd_draw_indirect(
+            self.buffer,
+            buffer.vk_handle(),
+            offset,
+            draw_count,
+            mem::size_of::<VkDrawIndirectCommand>() as u32,
+        );
+    }
+
+    /// `offset` is given in bytes, the commands are tightly packed
+    pub fn draw_indexed_indirect(
+        &self,
+        buffer: &Arc<Buffer<VkDrawIndexedIndirectCommand>>,
+        offset: VkDeviceSize,
+        draw_count: u32,
+    ) {
+        self.device.cmd_draw_indexed_indirect(
+            self.buffer,
+            buffer.vk_handle(),
+            offset,
+            draw_count,
+            mem::size_of::<VkDrawIndexedIndirectCommand>() as u32,
+        );
     }
 
     pub fn dispatch(&self, x: u32, y: u32, z: u32) {
         self.device.cmd_dispatch(self.buffer, x, y, z);
     }
 
-    pub fn dispatch_indirect(&self) {
-        unimplemented!();
+    pub fn dispatch_indirect(
+        &self,
+        buffer: &Arc<Buffer<VkDispatchIndirectCommand>>,
+        offset: VkDeviceSize,
+    ) {
+        self.device
+            .cmd_dispatch_indirect(self.buffer, buffer.vk_handle(), offset);
     }
 
     pub fn copy_buffer<T, U>(
@@ -632,12 +773,66 @@ impl CommandBuffer {
         )
     }
 
-    pub fn update_buffer(&self) {
-        unimplemented!();
+    /// Writes `data` inline into the command buffer
+    ///
+    /// `offset` is given in bytes, like for all other copy and fill commands
+    pub fn update_buffer<T>(
+        &self,
+        buffer: &Arc<Buffer<T>>,
+        offset: VkDeviceSize,
+        data: &[T],
+    ) -> VerboseResult<()> {
+        let data_size = data.len() as VkDeviceSize * mem::size_of::<T>() as VkDeviceSize;
+
+        if data_size == 0 {
+            create_error!("update_buffer needs at least 4 bytes");
+        }
+
+        if data_size > 65536 {
+            create_error!("update_buffer is limited to 65536 bytes");
+        }
+
+        if !offset.is_multiple_of(4) || !data_size.is_multiple_of(4) {
+            create_error!("offset and size of update_buffer must be a multiple of 4");
+        }
+
+        if offset + data_size > buffer.byte_size() {
+            create_error!("data exceeds the buffer size");
+        }
+
+        self.device
+            .cmd_update_buffer(self.buffer, buffer.vk_handle(), offset, data);
+
+        Ok(())
     }
 
-    pub fn fill_buffer(&self) {
-        unimplemented!();
+    /// Fills `size` bytes starting at `offset` with the 4 byte value `data`
+    ///
+    /// `offset` and `size` are given in bytes and have to be multiples of 4, `VK_WHOLE_SIZE`
+    /// fills the buffer up to its end
+    pub fn fill_buffer<T>(
+        &self,
+        buffer: &Arc<Buffer<T>>,
+        offset: VkDeviceSize,
+        size: VkDeviceSize,
+        data: u32,
+    ) -> VerboseResult<()> {
+        if !offset.is_multiple_of(4) || (size != VK_WHOLE_SIZE && !size.is_multiple_of(4)) {
+            create_error!("offset and size of fill_buffer must be a multiple of 4");
+        }
+
+        if offset >= buffer.byte_size() {
+            create_error!("offset exceeds the buffer size");
+        }
+
+        if size != VK_WHOLE_SIZE && (size == 0 || size > buffer.byte_size() - offset) {
+            create_error!("fill_buffer needs a size between 4 bytes and the rest of the buffer");
+        }
+
+        self.device
+            .cmd_fill_buffer(self.buffer, buffer.vk_handle(), offset, size, data);
+
+        Ok(())
     }
 
     pub fn clear_color_image(
@@ -656,40 +851,109 @@ impl CommandBuffer {
         Ok(())
     }
 
-    pub fn clear_depth_stencil_image(&self) {
-        unimplemented!();
+    pub fn clear_depth_stencil_image(
+        &self,
+        image: &Arc<Image>,
+        clear_value: VkClearDepthStencilValue,
+    ) -> VerboseResult<()> {
+        self.device.cmd_clear_depth_stencil_image(
+            self.buffer,
+            image.vk_handle(),
+            image.image_layout()?,
+            clear_value,
+            &[image.full_resource_range()],
+        );
+
+        Ok(())
     }
 
-    pub fn clear_attachments(&self) {
-        unimplemented!();
+    pub fn clear_attachments(&self, attachments: &[VkClearAttachment], rects: &[VkClearRect]) {
+        self.device
+            .cmd_clear_attachments(self.buffer, attachments, rects);
     }
 
-    pub fn resolve_image(&self) {
-        unimplemented!();
+    pub fn resolve_image(
+        &self,
+        src_image: &Arc<Image>,
+        dst_image: &Arc<Image>,
+        src_layout: VkImageLayout,
+        dst_layout: VkImageLayout,
+        regions: &[VkImageResolve],
+    ) {
+        self.device.cmd_resolve_image(
+            self.buffer,
+            src_image.vk_handle(),
+            src_layout,
+            dst_image.vk_handle(),
+            dst_layout,
+            regions,
+        );
     }
 
-    pub fn set_event(&self) {
-        unimplemented!();
+    pub fn set_event(&self, event: &Arc<Event>, stage_mask: impl Into<VkPipelineStageFlagBits>) {
+        self.device
+            .cmd_set_event(self.buffer, event.vk_handle(), stage_mask);
     }
 
-    pub fn reset_event(&self) {
-        unimplemented!();
+    pub fn reset_event(&self, event: &Arc<Event>, stage_mask: impl Into<VkPipelineStageFlagBits>) {
+        self.device
+            .cmd_reset_event(self.buffer, event.vk_handle(), stage_mask);
     }
 
-    pub fn wait_events(&self) {
-        unimplemented!();
+    pub fn wait_events(
+        &self,
+        events: &[&Arc<Event>],
+        src_stage_mask: impl Into<VkPipelineStageFlagBits>,
+        dst_stage_mask: impl Into<VkPipelineStageFlagBits>,
+        memory_barriers: &[VkMemoryBarrier],
+        buffer_memory_barriers: &[VkBufferMemoryBarrier],
+        image_memory_barriers: &[VkImageMemoryBarrier],
+    ) {
+        let vk_events: Vec<VkEvent> = events.iter().map(|event| event.vk_handle()).collect();
+
+        self.device.cmd_wait_events(
+            self.buffer,
+            vk_events.as_slice(),
+            src_stage_mask,
+            dst_stage_mask,
+            memory_barriers,
+            buffer_memory_barriers,
+            image_memory_barriers,
+        );
+    }
+
+    pub fn begin_query(
+        &self,
+        query_pool: &Arc<QueryPool>,
+        query: u32,
+        flags: impl Into<VkQueryControlFlagBits>,
+    ) {
+        self.device
+            .cmd_begin_query(self.buffer, query_pool.vk_handle(), query, flags);
     }
 
-    pub fn begin_query(&self) {
-        unimplemented!();
+    /// Begins a query with the control flags of the pool, e.g. precise occlusion
+    pub fn begin_pool_query(&self, query_pool: &Arc<QueryPool>, query: u32) {
+        self.begin_query(query_pool, query, query_pool.control_flags());
     }
 
-    pub fn end_query(&self) {
-        unimplemented!();
+    pub fn end_query(&self, query_pool: &Arc<QueryPool>, query: u32) {
+        self.device
+            .cmd_end_query(self.buffer, query_pool.vk_handle(), query);
     }
 
-    pub fn reset_query_pool(&self) {
-        unimplemented!();
+    pub fn reset_query_pool(
+        &self,
+        query_pool: &Arc<QueryPool>,
+        first_query: u32,
+        query_count: u32,
+    ) {
+        self.device.cmd_reset_query_pool(
+            self.buffer,
+            query_pool.vk_handle(),
+            first_query,
+            query_count,
+        );
     }
 
     pub fn write_timestamp(
@@ -821,8 +1085,78 @@ impl CommandBuffer {
         )
     }
 
-    pub fn copy_query_pool_results(&self) {
-        unimplemented!();
+    /// `dst_offset` and `stride` are given in bytes
+    pub fn copy_query_pool_results<T>(
+        &self,
+        query_pool: &Arc<QueryPool>,
+        first_query: u32,
+        query_count: u32,
+        dst_buffer: &Arc<Buffer<T>>,
+        dst_offset: VkDeviceSize,
+        stride: VkDeviceSize,
+        flags: impl Into<VkQueryResultFlagBits>,
+    ) {
+        self.device.cmd_copy_query_pool_results(
+            self.buffer,
+            query_pool.vk_handle(),
+            first_query,
+            query_count,
+            dst_buffer.vk_handle(),
+            dst_offset,
+            stride,
+            flags,
+        );
+    }
+
+    /// Copies 64 bit results with availability into `dst_buffer`
+    ///
+    /// `dst_offset` is given in bytes and has to be a multiple of 8. Each query occupies
+    /// `QueryPool::result_stride(true)` bytes, the last value of a query is non zero if
+    /// its results are available
+    pub fn copy_query_results(
+        &self,
+        query_pool: &Arc<QueryPool>,
+        first_query: u32,
+        query_count: u32,
+        dst_buffer: &Arc<Buffer<u64>>,
+        dst_offset: VkDeviceSize,
+        wait: bool,
+    ) -> VerboseResult<()> {
+        let stride = query_pool.result_stride(true);
+
+        if !dst_offset.is_multiple_of(mem::size_of::<u64>() as VkDeviceSize) {
+            create_error!(format!(
+                "offset ({}) of 64 bit query results must be a multiple of 8",
+                dst_offset
+            ));
+        }
+
+        if dst_offset + stride * query_count as VkDeviceSize > dst_buffer.byte_size() {
+            create_error!(format!(
+                "buffer ({} bytes) is too small for {} query results at offset {}",
+                dst_buffer.byte_size(),
+                query_count,
+                dst_offset
+            ));
+        }
+
+        let mut flags = VK_QUERY_RESULT_64_BIT | VK_QUERY_RESULT_WITH_AVAILABILITY_BIT;
+
+        if wait {
+            flags |= VK_QUERY_RESULT_WAIT_BIT;
+        }
+
+        self.copy_query_pool_results(
+            query_pool,
+            first_query,
+            query_count,
+            dst_buffer,
+            dst_offset,
+            stride,
+            flags,
+        );
+
+        Ok(())
     }
 
     pub fn inheritance_info(
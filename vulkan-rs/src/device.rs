@@ -1,6 +1,10 @@
 use utilities::prelude::*;
 
-use crate::allocator::{block::Block, device_allocator::DeviceAllocator};
+use crate::allocator::{
+    block::{AllocationPolicy, AllocationType, Block},
+    chunk::Chunk,
+    device_allocator::DeviceAllocator,
+};
 use crate::impl_vk_handle;
 use crate::loader::*;
 use crate::prelude::*;
@@ -22,23 +26,75 @@ Extensions!(DeviceExtensions, {
     (maintenance3, "VK_KHR_maintenance3"),
     (descriptor_indexing, "VK_EXT_descriptor_indexing"),
     (memory_requirements2, "VK_KHR_get_memory_requirements2"),
+    (dedicated_allocation, "VK_KHR_dedicated_allocation"),
+    (timeline_semaphore, "VK_KHR_timeline_semaphore"),
     (swapchain, "VK_KHR_swapchain"),
     (memory_budget, "VK_EXT_memory_budget"),
     (memory_priority, "VK_EXT_memory_priority"),
     (debug_marker, "VK_EXT_debug_marker"),
+    (hdr_metadata, "VK_EXT_hdr_metadata"),
 });
 
 pub use vulkan_sys::prelude::VkPhysicalDeviceFeatures as DeviceFeatures;
 
+const DEFAULT_CHUNK_SIZE: VkDeviceSize = 104857600;
+
+/// Smallest size accepted by `Device::set_memory_chunk_size` (1 MiB)
+pub const MIN_MEMORY_CHUNK_SIZE: VkDeviceSize = 1048576;
+
 pub struct MemoryHeap {
     pub usage: VkDeviceSize,
     pub budget: VkDeviceSize,
 }
 
+#[derive(Debug, Clone)]
+pub struct MemoryTypeStatistics {
+    pub memory_type_index: u32,
+    pub heap_index: u32,
+
+    pub chunk_count: usize,
+    pub dedicated_count: usize,
+    pub reserved: VkDeviceSize,
+    pub used: VkDeviceSize,
+
+    pub largest_free_block: VkDeviceSize,
+    /// 0.0 means all free memory is in one block, approaching 1.0 the free memory is scattered
+    pub fragmentation: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct MemoryHeapStatistics {
+    // reported by the driver (VK_EXT_memory_budget)
+    pub usage: VkDeviceSize,
+    pub budget: VkDeviceSize,
+
+    // tracked by the allocator
+    pub reserved: VkDeviceSize,
+    pub used: VkDeviceSize,
+}
+
+#[derive(Debug, Clone)]
+pub struct MemoryStatistics {
+    pub memory_types: Vec<MemoryTypeStatistics>,
+    pub heaps: Vec<MemoryHeapStatistics>,
+}
+
+#[derive(Debug, Clone)]
+pub struct LiveAllocation {
+    pub allocation_type: AllocationType,
+    pub policy: AllocationPolicy,
+    pub memory_type_index: u32,
+    pub offset: VkDeviceSize,
+    pub size: VkDeviceSize,
+}
+
 pub struct Device {
     device_functions: DeviceFunctions,
     device_wsi_functions: DeviceWSIFunctions,
     maintenance3_functions: Maintenance3Functions,
+    memory_requirements2_functions: MemoryRequirements2Functions,
+    timeline_semaphore_functions: TimelineSemaphoreFunctions,
+    hdr_metadata_functions: HdrMetadataFunctions,
 
     nv_ray_tracing_functions: NVRayTracingFunctions,
 
@@ -94,9 +150,23 @@ impl Device {
             &requested_device_features,
         );
 
+        let enabled_extensions = DeviceExtensions::from_list(&checked_extensions);
+
         let descriptor_indexing_features = physical_device.descriptor_indexing_features();
 
-        device_ci.chain(&descriptor_indexing_features);
+        // feature structs of extensions are only allowed in the chain, when the extension is enabled
+        let mut timeline_semaphore_features = VkPhysicalDeviceTimelineSemaphoreFeaturesKHR::new(
+            physical_device
+                .timeline_semaphore_features()
+                .timelineSemaphore,
+        );
+
+        if enabled_extensions.timeline_semaphore {
+            timeline_semaphore_features.chain(descriptor_indexing_features);
+            device_ci.chain(&timeline_semaphore_features);
+        } else {
+            device_ci.chain(descriptor_indexing_features);
+        }
 
         let instance = physical_device.instance();
 
@@ -118,8 +188,18 @@ impl Device {
             |device, name| instance.get_device_proc_addr_raw(device, name),
             device,
         );
-
-        let enabled_extensions = DeviceExtensions::from_list(&checked_extensions);
+        let memory_requirements2_functions = load_memory_requirements2(
+            |device, name| instance.get_device_proc_addr_raw(device, name),
+            device,
+        );
+        let timeline_semaphore_functions = load_timeline_semaphore(
+            |device, name| instance.get_device_proc_addr_raw(device, name),
+            device,
+        );
+        let hdr_metadata_functions = load_hdr_metadata(
+            |device, name| instance.get_device_proc_addr_raw(device, name),
+            device,
+        );
 
         if let Err(missing_extensions) = extensions.check_availability(&enabled_extensions) {
             for m in missing_extensions {
@@ -131,6 +211,9 @@ impl Device {
             device_functions,
             device_wsi_functions,
             maintenance3_functions,
+            memory_requirements2_functions,
+            timeline_semaphore_functions,
+            hdr_metadata_functions,
 
             nv_ray_tracing_functions,
 
@@ -139,8 +222,8 @@ impl Device {
             physical_device,
             device,
 
-            // request chunks in 100 MiB
-            memory_allocator: Mutex::new(DeviceAllocator::new(104857600)),
+            // request chunks in 100 MiB by default
+            memory_allocator: Mutex::new(DeviceAllocator::new(DEFAULT_CHUNK_SIZE)),
 
             sampler_manager: SamplerManager::new(),
         }))
@@ -200,6 +283,40 @@ impl Device {
         &self.enabled_extensions
     }
 
+    /// Sets the size of memory chunks, which are allocated from now on.
+    /// Already existing chunks are not affected.
+    ///
+    /// The size must be at least `MIN_MEMORY_CHUNK_SIZE` and a multiple of `bufferImageGranularity`.
+    pub fn set_memory_chunk_size(&self, size: VkDeviceSize) -> VerboseResult<()> {
+        if size < MIN_MEMORY_CHUNK_SIZE {
+            create_error!(format!(
+                "memory chunk size ({}) must be at least {} bytes",
+                size, MIN_MEMORY_CHUNK_SIZE
+            ));
+        }
+
+        let granularity = self
+            .physical_device
+            .properties()
+            .limits
+            .bufferImageGranularity;
+
+        if Chunk::align_up(size, granularity) != size {
+            create_error!(format!(
+                "memory chunk size ({}) must be a multiple of bufferImageGranularity ({})",
+                size, granularity
+            ));
+        }
+
+        self.memory_allocator.lock()?.set_chunk_size(size);
+
+        Ok(())
+    }
+
+    pub(crate) fn dedicated_allocation_enabled(&self) -> bool {
+        self.enabled_extensions.memory_requirements2 && self.enabled_extensions.dedicated_allocation
+    }
+
     pub fn memory_budgets(&self) -> Vec<MemoryHeap> {
         let phys_dev = self.physical_device();
 
@@ -221,6 +338,158 @@ impl Device {
         heaps
     }
 
+    /// Gathers usage information of the internal memory allocator,
+    /// combined with the driver reported budgets of `memory_budgets()`
+    pub fn memory_statistics(&self) -> VerboseResult<MemoryStatistics> {
+        let memory_properties = self.physical_device.memory_properties();
+
+        let mut memory_types: Vec<MemoryTypeStatistics> = (0..memory_properties.memoryTypeCount)
+            .map(|i| MemoryTypeStatistics {
+                memory_type_index: i,
+                heap_index: memory_properties.memoryTypes[i as usize].heapIndex,
+
+                chunk_count: 0,
+                dedicated_count: 0,
+                reserved: 0,
+                used: 0,
+
+                largest_free_block: 0,
+                fragmentation: 0.0,
+            })
+            .collect();
+
+        {
+            let memory_allocator = self.memory_allocator.lock()?;
+
+            for chunk in memory_allocator.chunks() {
+                let statistics = &mut memory_types[chunk.memory_type_index() as usize];
+
+                statistics.chunk_count += 1;
+                statistics.reserved += chunk.size();
+                statistics.used += chunk.used_size();
+                statistics.largest_free_block = statistics
+                    .largest_free_block
+                    .max(chunk.largest_free_block());
+            }
+
+            for chunk in memory_allocator.linear_chunks() {
+                let statistics = &mut memory_types[chunk.memory_type_index() as usize];
+
+                statistics.chunk_count += 1;
+                statistics.reserved += chunk.size();
+                statistics.used += chunk.used_size();
+                statistics.largest_free_block = statistics
+                    .largest_free_block
+                    .max(chunk.largest_free_block());
+            }
+
+            // dedicated allocations are completely used by definition
+            for chunk in memory_allocator.dedicated_chunks() {
+                let statistics = &mut memory_types[chunk.memory_type_index() as usize];
+
+                statistics.dedicated_count += 1;
+                statistics.reserved += chunk.size();
+                statistics.used += chunk.size();
+            }
+        }
+
+        for statistics in memory_types.iter_mut() {
+            let free = statistics.reserved - statistics.used;
+
+            if free > 0 {
+                statistics.fragmentation =
+                    1.0 - (statistics.largest_free_block as f64 / free as f64) as f32;
+            }
+        }
+
+        let mut heaps: Vec<MemoryHeapStatistics> = self
+            .memory_budgets()
+            .iter()
+            .map(|heap| MemoryHeapStatistics {
+                usage: heap.usage,
+                budget: heap.budget,
+
+                reserved: 0,
+                used: 0,
+            })
+            .collect();
+
+        for statistics in memory_types.iter() {
+            if let Some(heap) = heaps.get_mut(statistics.heap_index as usize) {
+                heap.reserved += statistics.reserved;
+                heap.used += statistics.used;
+            }
+        }
+
+        Ok(MemoryStatistics {
+            memory_types,
+            heaps,
+        })
+    }
+
+    /// Lists all blocks of the internal memory allocator, that are currently in use
+    pub fn live_allocations(&self) -> VerboseResult<Vec<LiveAllocation>> {
+        Ok(Self::collect_live_allocations(
+            &*self.memory_allocator.lock()?,
+        ))
+    }
+
+    fn collect_live_allocations(memory_allocator: &DeviceAllocator) -> Vec<LiveAllocation> {
+        let chunk_blocks = memory_allocator
+            .chunks()
+            .iter()
+            .chain(memory_allocator.dedicated_chunks().iter())
+            .flat_map(|chunk| {
+                chunk
+                    .used_blocks()
+                    .map(move |block| (chunk.memory_type_index(), block))
+            });
+
+        let linear_blocks = memory_allocator.linear_chunks().iter().flat_map(|chunk| {
+            chunk
+                .used_blocks()
+                .map(move |block| (chunk.memory_type_index(), block))
+        });
+
+        chunk_blocks
+            .chain(linear_blocks)
+            .map(|(memory_type_index, block)| LiveAllocation {
+                allocation_type: block.allocation_type,
+                policy: block.policy,
+                memory_type_index,
+                offset: block.offset,
+                size: block.size,
+            })
+            .collect()
+    }
+
+    /// Prints all blocks of the internal memory allocator, that are currently in use
+    ///
+    /// Every `Memory` keeps the device alive, so leaks have to be reported explicitly
+    /// at shutdown, e.g. after all scenes were removed
+    pub fn report_live_allocations(&self) -> VerboseResult<()> {
+        let live_allocations = self.live_allocations()?;
+
+        if live_allocations.is_empty() {
+            return Ok(());
+        }
+
+        println!("{} live memory allocation(s):", live_allocations.len());
+
+        for allocation in live_allocations {
+            println!(
+                "\t- {:?} ({:?}): {} bytes at offset {} (memory type {})",
+                allocation.allocation_type,
+                allocation.policy,
+                allocation.size,
+                allocation.offset,
+                allocation.memory_type_index
+            );
+        }
+
+        Ok(())
+    }
+
     pub fn max_supported_sample_count(
         &self,
         requested_sample_count: VkSampleCountFlags,
@@ -273,6 +542,15 @@ impl Drop for Device {
                 .clear(self);
         }
 
+        {
+            let mut memory_allocator = self
+                .memory_allocator
+                .lock()
+                .expect("failed to lock memory allocator at drop of device");
+
+            memory_allocator.clear(self);
+        }
+
         self.destroy_device();
     }
 }
@@ -334,26 +612,26 @@ impl Device {
         query_pool: VkQueryPool,
         first_query: u32,
         query_count: u32,
-        data: &mut T,
+        data: &mut [T],
         stride: VkDeviceSize,
         flags: impl Into<VkQueryResultFlagBits>,
-    ) -> VerboseResult<()> {
+    ) -> VerboseResult<bool> {
         unsafe {
             let result = self.device_functions.vkGetQueryPoolResults(
                 self.device,
                 query_pool,
                 first_query,
                 query_count,
-                size_of::<T>(),
-                data as *mut T as *mut c_void,
+                size_of::<T>() * data.len(),
+                data.as_mut_ptr() as *mut c_void,
                 stride,
                 flags.into(),
             );
 
-            if result == VK_SUCCESS {
-                Ok(())
-            } else {
-                create_error!(format!("failed getting query pool results {:?}", result))
+            match result {
+                VK_SUCCESS => Ok(true),
+                VK_NOT_READY => Ok(false),
+                _ => create_error!(format!("failed getting query pool results {:?}", result)),
             }
         }
     }
@@ -432,22 +710,89 @@ impl Device {
         }
     }
 
+    pub fn buffer_memory_requirements2(
+        &self,
+        buffer: VkBuffer,
+    ) -> (VkMemoryRequirements, VkMemoryDedicatedRequirementsKHR) {
+        let info = VkBufferMemoryRequirementsInfo2KHR::new(buffer);
+        let mut dedicated_requirements = VkMemoryDedicatedRequirementsKHR::default();
+        let mut memory_requirements = VkMemoryRequirements2KHR::default();
+
+        memory_requirements.chain_mut(&mut dedicated_requirements);
+
+        unsafe {
+            self.memory_requirements2_functions
+                .vkGetBufferMemoryRequirements2KHR(self.device, &info, &mut memory_requirements)
+        };
+
+        dedicated_requirements.pNext = ptr::null_mut();
+
+        (
+            memory_requirements.memoryRequirements,
+            dedicated_requirements,
+        )
+    }
+
+    pub fn image_memory_requirements2(
+        &self,
+        image: VkImage,
+    ) -> (VkMemoryRequirements, VkMemoryDedicatedRequirementsKHR) {
+        let info = VkImageMemoryRequirementsInfo2KHR::new(image);
+        let mut dedicated_requirements = VkMemoryDedicatedRequirementsKHR::default();
+        let mut memory_requirements = VkMemoryRequirements2KHR::default();
+
+        memory_requirements.chain_mut(&mut dedicated_requirements);
+
+        unsafe {
+            self.memory_requirements2_functions
+                .vkGetImageMemoryRequirements2KHR(self.device, &info, &mut memory_requirements)
+        };
+
+        dedicated_requirements.pNext = ptr::null_mut();
+
+        (
+            memory_requirements.memoryRequirements,
+            dedicated_requirements,
+        )
+    }
+
     pub(crate) fn allocate_memory_from_allocator(
-        me: &Arc<Device>,
-        allocation_size: VkDeviceSize,
+        &self,
+        memory_requirements: &VkMemoryRequirements,
         memory_type_index: u32,
-        alignment: VkDeviceSize,
+        allocation_type: AllocationType,
+        policy: AllocationPolicy,
+        dedicated_info: Option<&VkMemoryDedicatedAllocateInfoKHR>,
     ) -> VerboseResult<Block> {
-        me.memory_allocator.lock()?.allocate(
-            me.clone(),
-            allocation_size,
-            memory_type_index,
-            alignment,
-        )
+        let mut memory_allocator = self.memory_allocator.lock()?;
+
+        match policy {
+            AllocationPolicy::Chunked => memory_allocator.allocate(
+                self,
+                memory_requirements.size,
+                memory_type_index,
+                memory_requirements.alignment,
+                allocation_type,
+            ),
+            AllocationPolicy::Dedicated => memory_allocator.allocate_dedicated(
+                self,
+                memory_requirements.size,
+                memory_type_index,
+                allocation_type,
+                dedicated_info,
+            ),
+            AllocationPolicy::LinearPool => memory_allocator.allocate_linear(
+                self,
+                memory_requirements.size,
+                memory_type_index,
+                memory_requirements.alignment,
+                allocation_type,
+            ),
+        }
     }
 
     pub(crate) fn free_memory_from_allocator(&self, block: &Block) -> VerboseResult<()> {
-        self.memory_allocator.lock()?.deallocate(block);
+        self.memory_allocator.lock()?.deallocate(self, block);
 
         Ok(())
     }
@@ -840,6 +1185,60 @@ impl Device {
         }
     }
 
+    pub fn semaphore_counter_value(&self, semaphore: VkSemaphore) -> VerboseResult<u64> {
+        unsafe {
+            let mut value = MaybeUninit::uninit();
+
+            let result = self
+                .timeline_semaphore_functions
+                .vkGetSemaphoreCounterValueKHR(self.device, semaphore, value.as_mut_ptr());
+
+            if result == VK_SUCCESS {
+                Ok(value.assume_init())
+            } else {
+                create_error!(format!(
+                    "failed getting semaphore counter value {:?}",
+                    result
+                ))
+            }
+        }
+    }
+
+    /// Returns false, if the timeout expired before the semaphores reached their values
+    pub fn wait_semaphores(
+        &self,
+        wait_info: &VkSemaphoreWaitInfoKHR,
+        timeout: u64,
+    ) -> VerboseResult<bool> {
+        unsafe {
+            let result = self.timeline_semaphore_functions.vkWaitSemaphoresKHR(
+                self.device,
+                wait_info,
+                timeout,
+            );
+
+            match result {
+                VK_SUCCESS => Ok(true),
+                VK_TIMEOUT => Ok(false),
+                _ => create_error!(format!("failed waiting for semaphores {:?}", result)),
+            }
+        }
+    }
+
+    pub fn signal_semaphore(&self, signal_info: &VkSemaphoreSignalInfoKHR) -> VerboseResult<()> {
+        unsafe {
+            let result = self
+                .timeline_semaphore_functions
+                .vkSignalSemaphoreKHR(self.device, signal_info);
+
+            if result == VK_SUCCESS {
+                Ok(())
+            } else {
+                create_error!(format!("failed signaling semaphore {:?}", result))
+            }
+        }
+    }
+
     pub fn destroy_semaphore(&self, semaphore: VkSemaphore) {
         unsafe {
             self.device_functions
@@ -1050,14 +1449,14 @@ impl Device {
         };
     }
 
-    pub fn event_status(&self, event: VkEvent) -> VerboseResult<()> {
+    pub fn event_status(&self, event: VkEvent) -> VerboseResult<bool> {
         unsafe {
             let result = self.device_functions.vkGetEventStatus(self.device, event);
 
-            if result == VK_SUCCESS {
-                Ok(())
-            } else {
-                create_error!(format!("failed getting event status {:?}", result))
+            match result {
+                VK_EVENT_SET => Ok(true),
+                VK_EVENT_RESET => Ok(false),
+                _ => create_error!(format!("failed getting event status {:?}", result)),
             }
         }
     }
@@ -1546,6 +1945,20 @@ impl Device {
             }
         }
     }
+
+    /// Requires the `hdr_metadata` extension
+    pub fn set_hdr_metadata(&self, swapchains: &[VkSwapchainKHR], metadata: &[VkHdrMetadataEXT]) {
+        debug_assert_eq!(swapchains.len(), metadata.len());
+
+        unsafe {
+            self.hdr_metadata_functions.vkSetHdrMetadataEXT(
+                self.device,
+                swapchains.len() as u32,
+                swapchains.as_ptr(),
+                metadata.as_ptr(),
+            )
+        };
+    }
 }
 
 // command buffer functions
@@ -1659,6 +2072,84 @@ impl Device {
         }
     }
 
+    pub fn cmd_set_line_width(&self, command_buffer: VkCommandBuffer, line_width: f32) {
+        unsafe {
+            self.device_functions
+                .vkCmdSetLineWidth(command_buffer, line_width)
+        }
+    }
+
+    pub fn cmd_set_blend_constants(
+        &self,
+        command_buffer: VkCommandBuffer,
+        blend_constants: [f32; 4],
+    ) {
+        unsafe {
+            self.device_functions
+                .vkCmdSetBlendConstants(command_buffer, blend_constants.as_ptr())
+        }
+    }
+
+    pub fn cmd_set_depth_bounds(
+        &self,
+        command_buffer: VkCommandBuffer,
+        min_depth_bounds: f32,
+        max_depth_bounds: f32,
+    ) {
+        unsafe {
+            self.device_functions.vkCmdSetDepthBounds(
+                command_buffer,
+                min_depth_bounds,
+                max_depth_bounds,
+            )
+        }
+    }
+
+    pub fn cmd_set_stencil_compare_mask(
+        &self,
+        command_buffer: VkCommandBuffer,
+        face_mask: impl Into<VkStencilFaceFlagBits>,
+        compare_mask: u32,
+    ) {
+        unsafe {
+            self.device_functions.vkCmdSetStencilCompareMask(
+                command_buffer,
+                face_mask.into(),
+                compare_mask,
+            )
+        }
+    }
+
+    pub fn cmd_set_stencil_write_mask(
+        &self,
+        command_buffer: VkCommandBuffer,
+        face_mask: impl Into<VkStencilFaceFlagBits>,
+        write_mask: u32,
+    ) {
+        unsafe {
+            self.device_functions.vkCmdSetStencilWriteMask(
+                command_buffer,
+                face_mask.into(),
+                write_mask,
+            )
+        }
+    }
+
+    pub fn cmd_set_stencil_reference(
+        &self,
+        command_buffer: VkCommandBuffer,
+        face_mask: impl Into<VkStencilFaceFlagBits>,
+        reference: u32,
+    ) {
+        unsafe {
+            self.device_functions.vkCmdSetStencilReference(
+                command_buffer,
+                face_mask.into(),
+                reference,
+            )
+        }
+    }
+
     pub fn cmd_bind_descriptor_sets(
         &self,
         command_buffer: VkCommandBuffer,
@@ -1756,10 +2247,60 @@ impl Device {
         }
     }
 
+    pub fn cmd_draw_indirect(
+        &self,
+        command_buffer: VkCommandBuffer,
+        buffer: VkBuffer,
+        offset: VkDeviceSize,
+        draw_count: u32,
+        stride: u32,
+    ) {
+        unsafe {
+            self.device_functions.vkCmdDrawIndirect(
+                command_buffer,
+                buffer,
+                offset,
+                draw_count,
+                stride,
+            )
+        }
+    }
+
+    pub fn cmd_draw_indexed_indirect(
+        &self,
+        command_buffer: VkCommandBuffer,
+        buffer: VkBuffer,
+        offset: VkDeviceSize,
+        draw_count: u32,
+        stride: u32,
+    ) {
+        unsafe {
+            self.device_functions.vkCmdDrawIndexedIndirect(
+                command_buffer,
+                buffer,
+                offset,
+                draw_count,
+                stride,
+            )
+        }
+    }
+
     pub fn cmd_dispatch(&self, command_buffer: VkCommandBuffer, x: u32, y: u32, z: u32) {
         unsafe { self.device_functions.vkCmdDispatch(command_buffer, x, y, z) }
     }
 
+    pub fn cmd_dispatch_indirect(
+        &self,
+        command_buffer: VkCommandBuffer,
+        buffer: VkBuffer,
+        offset: VkDeviceSize,
+    ) {
+        unsafe {
+            self.device_functions
+                .vkCmdDispatchIndirect(command_buffer, buffer, offset)
+        }
+    }
+
     pub fn cmd_begin_render_pass(
         &self,
         command_buffer: VkCommandBuffer,
@@ -1797,6 +2338,57 @@ impl Device {
         }
     }
 
+    pub fn cmd_set_event(
+        &self,
+        command_buffer: VkCommandBuffer,
+        event: VkEvent,
+        stage_mask: impl Into<VkPipelineStageFlagBits>,
+    ) {
+        unsafe {
+            self.device_functions
+                .vkCmdSetEvent(command_buffer, event, stage_mask.into())
+        }
+    }
+
+    pub fn cmd_reset_event(
+        &self,
+        command_buffer: VkCommandBuffer,
+        event: VkEvent,
+        stage_mask: impl Into<VkPipelineStageFlagBits>,
+    ) {
+        unsafe {
+            self.device_functions
+                .vkCmdResetEvent(command_buffer, event, stage_mask.into())
+        }
+    }
+
+    pub fn cmd_wait_events(
+        &self,
+        command_buffer: VkCommandBuffer,
+        events: &[VkEvent],
+        src_stage_mask: impl Into<VkPipelineStageFlagBits>,
+        dst_stage_mask: impl Into<VkPipelineStageFlagBits>,
+        memory_barriers: &[VkMemoryBarrier],
+        buffer_memory_barriers: &[VkBufferMemoryBarrier],
+        image_memory_barriers: &[VkImageMemoryBarrier],
+    ) {
+        unsafe {
+            self.device_functions.vkCmdWaitEvents(
+                command_buffer,
+                events.len() as u32,
+                events.as_ptr(),
+                src_stage_mask.into(),
+                dst_stage_mask.into(),
+                memory_barriers.len() as u32,
+                memory_barriers.as_ptr(),
+                buffer_memory_barriers.len() as u32,
+                buffer_memory_barriers.as_ptr(),
+                image_memory_barriers.len() as u32,
+                image_memory_barriers.as_ptr(),
+            )
+        }
+    }
+
     pub fn cmd_pipeline_barrier(
         &self,
         command_buffer: VkCommandBuffer,
@@ -1927,6 +2519,38 @@ impl Device {
         }
     }
 
+    pub fn cmd_update_buffer<T>(
+        &self,
+        command_buffer: VkCommandBuffer,
+        buffer: VkBuffer,
+        offset: VkDeviceSize,
+        data: &[T],
+    ) {
+        unsafe {
+            self.device_functions.vkCmdUpdateBuffer(
+                command_buffer,
+                buffer,
+                offset,
+                (data.len() * size_of::<T>()) as VkDeviceSize,
+                data.as_ptr() as *const u32,
+            )
+        }
+    }
+
+    pub fn cmd_fill_buffer(
+        &self,
+        command_buffer: VkCommandBuffer,
+        buffer: VkBuffer,
+        offset: VkDeviceSize,
+        size: VkDeviceSize,
+        data: u32,
+    ) {
+        unsafe {
+            self.device_functions
+                .vkCmdFillBuffer(command_buffer, buffer, offset, size, data)
+        }
+    }
+
     pub fn cmd_push_constants<T>(
         &self,
         command_buffer: VkCommandBuffer,
@@ -2006,6 +2630,31 @@ impl Device {
         }
     }
 
+    pub fn cmd_copy_query_pool_results(
+        &self,
+        command_buffer: VkCommandBuffer,
+        query_pool: VkQueryPool,
+        first_query: u32,
+        query_count: u32,
+        dst_buffer: VkBuffer,
+        dst_offset: VkDeviceSize,
+        stride: VkDeviceSize,
+        flags: impl Into<VkQueryResultFlagBits>,
+    ) {
+        unsafe {
+            self.device_functions.vkCmdCopyQueryPoolResults(
+                command_buffer,
+                query_pool,
+                first_query,
+                query_count,
+                dst_buffer,
+                dst_offset,
+                stride,
+                flags.into(),
+            )
+        }
+    }
+
     pub fn cmd_clear_color_image(
         &self,
         command_buffer: VkCommandBuffer,
@@ -2026,6 +2675,65 @@ impl Device {
         }
     }
 
+    pub fn cmd_clear_depth_stencil_image(
+        &self,
+        command_buffer: VkCommandBuffer,
+        image: VkImage,
+        image_layout: VkImageLayout,
+        clear_value: VkClearDepthStencilValue,
+        ranges: &[VkImageSubresourceRange],
+    ) {
+        unsafe {
+            self.device_functions.vkCmdClearDepthStencilImage(
+                command_buffer,
+                image,
+                image_layout,
+                &clear_value,
+                ranges.len() as u32,
+                ranges.as_ptr(),
+            )
+        }
+    }
+
+    pub fn cmd_clear_attachments(
+        &self,
+        command_buffer: VkCommandBuffer,
+        attachments: &[VkClearAttachment],
+        rects: &[VkClearRect],
+    ) {
+        unsafe {
+            self.device_functions.vkCmdClearAttachments(
+                command_buffer,
+                attachments.len() as u32,
+                attachments.as_ptr(),
+                rects.len() as u32,
+                rects.as_ptr(),
+            )
+        }
+    }
+
+    pub fn cmd_resolve_image(
+        &self,
+        command_buffer: VkCommandBuffer,
+        src_image: VkImage,
+        src_image_layout: VkImageLayout,
+        dst_image: VkImage,
+        dst_image_layout: VkImageLayout,
+        regions: &[VkImageResolve],
+    ) {
+        unsafe {
+            self.device_functions.vkCmdResolveImage(
+                command_buffer,
+                src_image,
+                src_image_layout,
+                dst_image,
+                dst_image_layout,
+                regions.len() as u32,
+                regions.as_ptr(),
+            )
+        }
+    }
+
     pub fn descriptor_set_layout_support(
         &self,
         create_info: &VkDescriptorSetLayoutCreateInfo,
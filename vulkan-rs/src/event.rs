@@ -0,0 +1,49 @@
+use utilities::prelude::*;
+
+use crate::impl_vk_handle;
+use crate::prelude::*;
+
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub struct Event {
+    device: Arc<Device>,
+    event: VkEvent,
+}
+
+impl Event {
+    pub fn new(device: Arc<Device>) -> VerboseResult<Arc<Event>> {
+        let event_ci = VkEventCreateInfo::new(VK_EVENT_CREATE_NULL_BIT);
+
+        let event = device.create_event(&event_ci)?;
+
+        Ok(Arc::new(Event { device, event }))
+    }
+
+    pub fn set(&self) -> VerboseResult<()> {
+        self.device.set_event(self.event)
+    }
+
+    pub fn reset(&self) -> VerboseResult<()> {
+        self.device.reset_event(self.event)
+    }
+
+    /// Returns true, if the event is signaled
+    pub fn status(&self) -> VerboseResult<bool> {
+        self.device.event_status(self.event)
+    }
+}
+
+impl VulkanDevice for Event {
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+impl_vk_handle!(Event, VkEvent, event);
+
+impl Drop for Event {
+    fn drop(&mut self) {
+        self.device.destroy_event(self.event);
+    }
+}
@@ -0,0 +1,409 @@
+use utilities::prelude::*;
+
+use crate::prelude::*;
+
+use std::sync::{
+    atomic::{AtomicBool, Ordering::SeqCst},
+    Arc, Mutex,
+};
+use std::time::Duration;
+
+// query 0 and 1 are reserved for the start and the end of a frame
+const FRAME_BEGIN_QUERY: u32 = 0;
+const FRAME_END_QUERY: u32 = 1;
+const FIRST_SCOPE_QUERY: u32 = 2;
+
+/// Measured GPU time of a named scope, nested scopes are contained in `children`
+#[derive(Debug, Clone)]
+pub struct GpuScope {
+    pub name: String,
+    pub duration: Duration,
+    pub children: Vec<GpuScope>,
+}
+
+/// GPU timings of a completed frame
+#[derive(Debug, Clone)]
+pub struct GpuFrame {
+    /// running number of the frame, starting at 0
+    pub frame: u64,
+    pub duration: Duration,
+    pub scopes: Vec<GpuScope>,
+}
+
+impl GpuFrame {
+    /// Searches depth first for the first scope with the given name
+    pub fn find(&self, name: &str) -> Option<&GpuScope> {
+        fn find_in<'a>(scopes: &'a [GpuScope], name: &str) -> Option<&'a GpuScope> {
+            for scope in scopes {
+                if scope.name == name {
+                    return Some(scope);
+                }
+
+                if let Some(scope) = find_in(&scope.children, name) {
+                    return Some(scope);
+                }
+            }
+
+            None
+        }
+
+        find_in(&self.scopes, name)
+    }
+}
+
+struct RecordedScope {
+    name: String,
+    parent: Option<usize>,
+    begin_query: u32,
+    ended: bool,
+}
+
+impl RecordedScope {
+    fn end_query(&self) -> u32 {
+        self.begin_query + 1
+    }
+}
+
+struct FrameQueries {
+    query_pool: Arc<QueryPool>,
+
+    frame: u64,
+    finished: bool,
+    next_query: u32,
+    scopes: Vec<RecordedScope>,
+}
+
+struct ProfilerState {
+    frames: Vec<FrameQueries>,
+    frame_count: u64,
+    // query pool of the frame, that is currently recorded
+    current: usize,
+
+    // open scopes of the current frame, None marks a scope that didn't fit into the pool
+    stack: Vec<Option<usize>>,
+    recording: bool,
+
+    last_frame: Option<GpuFrame>,
+}
+
+/// Measures GPU execution times of named scopes with timestamp queries
+///
+/// Every frame in flight gets its own query pool, the results of a pool are read back
+/// when it is reused. Therefore the frame that used that pool has to be finished on the
+/// GPU when `begin_frame` is called again with its frame index.
+pub struct GpuProfiler {
+    device: Arc<Device>,
+
+    // nanoseconds per timestamp tick
+    timestamp_period: f64,
+    supported: bool,
+    enabled: AtomicBool,
+
+    state: Mutex<ProfilerState>,
+}
+
+impl GpuProfiler {
+    pub fn new(
+        device: &Arc<Device>,
+        frames_in_flight: usize,
+        max_scopes: u32,
+    ) -> VerboseResult<Arc<GpuProfiler>> {
+        if frames_in_flight == 0 {
+            create_error!("GpuProfiler requires at least one frame in flight");
+        }
+
+        let limits = &device.physical_device().properties().limits;
+        let supported = limits.timestampComputeAndGraphics == VK_TRUE;
+
+        let mut frames = Vec::with_capacity(frames_in_flight);
+
+        if supported {
+            for _ in 0..frames_in_flight {
+                frames.push(FrameQueries {
+                    query_pool: QueryPool::new(
+                        device.clone(),
+                        VK_QUERY_TYPE_TIMESTAMP,
+                        FIRST_SCOPE_QUERY + 2 * max_scopes,
+                        0,
+                    )?,
+
+                    frame: 0,
+                    finished: false,
+                    next_query: FIRST_SCOPE_QUERY,
+                    scopes: Vec::new(),
+                });
+            }
+        } else {
+            println!("timestamp queries are not supported, GpuProfiler is disabled");
+        }
+
+        Ok(Arc::new(GpuProfiler {
+            device: device.clone(),
+
+            timestamp_period: limits.timestampPeriod as f64,
+            supported,
+            enabled: AtomicBool::new(supported),
+
+            state: Mutex::new(ProfilerState {
+                frames,
+                frame_count: 0,
+                current: 0,
+
+                stack: Vec::new(),
+                recording: false,
+
+                last_frame: None,
+            }),
+        }))
+    }
+
+    pub fn is_supported(&self) -> bool {
+        self.supported
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(SeqCst)
+    }
+
+    /// Has no effect if timestamp queries aren't supported by the device
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled && self.supported, SeqCst);
+    }
+
+    /// Timings of the most recent frame, that was read back
+    pub fn last_frame(&self) -> VerboseResult<Option<GpuFrame>> {
+        Ok(self.state.lock()?.last_frame.clone())
+    }
+
+    /// Starts a new frame, has to be recorded outside of a render pass
+    ///
+    /// `frame_index` selects the query pool and has to be the frame in flight, that
+    /// `command_buffer` belongs to. Reads back the results of the frame, that used
+    /// the query pool before.
+    pub fn begin_frame(
+        &self,
+        command_buffer: &Arc<CommandBuffer>,
+        frame_index: usize,
+    ) -> VerboseResult<()> {
+        let mut state = self.state.lock()?;
+
+        state.recording = false;
+        state.stack.clear();
+
+        if !self.is_enabled() {
+            return Ok(());
+        }
+
+        if frame_index >= state.frames.len() {
+            create_error!(format!(
+                "GpuProfiler: frame index {} exceeds frames in flight ({})",
+                frame_index,
+                state.frames.len()
+            ));
+        }
+
+        if let Some(frame) = self.resolve(&state.frames[frame_index])? {
+            state.last_frame = Some(frame);
+        }
+
+        let frame_count = state.frame_count;
+        let frame = &mut state.frames[frame_index];
+
+        command_buffer.reset_query_pool(&frame.query_pool, 0, frame.query_pool.query_count());
+        command_buffer.write_timestamp(
+            &frame.query_pool,
+            FRAME_BEGIN_QUERY,
+            VK_PIPELINE_STAGE_TOP_OF_PIPE_BIT,
+        );
+
+        frame.frame = frame_count;
+        frame.finished = false;
+        frame.next_query = FIRST_SCOPE_QUERY;
+        frame.scopes.clear();
+
+        state.frame_count += 1;
+        state.current = frame_index;
+        state.recording = true;
+
+        Ok(())
+    }
+
+    pub fn end_frame(&self, command_buffer: &Arc<CommandBuffer>) -> VerboseResult<()> {
+        let mut state = self.state.lock()?;
+
+        if !state.recording {
+            return Ok(());
+        }
+
+        if !state.stack.is_empty() {
+            create_error!(format!(
+                "GpuProfiler: {} scope(s) not ended at the end of the frame",
+                state.stack.len()
+            ));
+        }
+
+        let frame = Self::current_frame(&mut state);
+
+        command_buffer.write_timestamp(
+            &frame.query_pool,
+            FRAME_END_QUERY,
+            VK_PIPELINE_STAGE_BOTTOM_OF_PIPE_BIT,
+        );
+
+        frame.finished = true;
+        state.recording = false;
+
+        Ok(())
+    }
+
+    /// Opens a named scope, scopes can be nested
+    ///
+    /// Scopes that exceed the maximal scope count of a frame are ignored
+    pub fn begin_scope(
+        &self,
+        command_buffer: &Arc<CommandBuffer>,
+        name: &str,
+    ) -> VerboseResult<()> {
+        let mut state = self.state.lock()?;
+
+        if !state.recording {
+            return Ok(());
+        }
+
+        let parent = state.stack.last().cloned().flatten();
+        let frame = Self::current_frame(&mut state);
+
+        if frame.next_query + 2 > frame.query_pool.query_count() {
+            state.stack.push(None);
+            return Ok(());
+        }
+
+        let begin_query = frame.next_query;
+        frame.next_query += 2;
+
+        command_buffer.write_timestamp(
+            &frame.query_pool,
+            begin_query,
+            VK_PIPELINE_STAGE_TOP_OF_PIPE_BIT,
+        );
+
+        let index = frame.scopes.len();
+
+        frame.scopes.push(RecordedScope {
+            name: name.to_string(),
+            parent,
+            begin_query,
+            ended: false,
+        });
+
+        state.stack.push(Some(index));
+
+        Ok(())
+    }
+
+    /// Closes the most recently opened scope
+    pub fn end_scope(&self, command_buffer: &Arc<CommandBuffer>) -> VerboseResult<()> {
+        let mut state = self.state.lock()?;
+
+        if !state.recording {
+            return Ok(());
+        }
+
+        let index = match state.stack.pop() {
+            Some(Some(index)) => index,
+            Some(None) => return Ok(()),
+            None => create_error!("GpuProfiler: end_scope without matching begin_scope"),
+        };
+
+        let frame = Self::current_frame(&mut state);
+        let scope = &mut frame.scopes[index];
+
+        command_buffer.write_timestamp(
+            &frame.query_pool,
+            scope.end_query(),
+            VK_PIPELINE_STAGE_BOTTOM_OF_PIPE_BIT,
+        );
+
+        scope.ended = true;
+
+        Ok(())
+    }
+
+    /// Wraps `f` into a named scope, the scope is ended even if `f` fails
+    pub fn scope<F, T>(
+        &self,
+        command_buffer: &Arc<CommandBuffer>,
+        name: &str,
+        f: F,
+    ) -> VerboseResult<T>
+    where
+        F: FnOnce() -> VerboseResult<T>,
+    {
+        self.begin_scope(command_buffer, name)?;
+        let result = f();
+        self.end_scope(command_buffer)?;
+
+        result
+    }
+
+    fn current_frame(state: &mut ProfilerState) -> &mut FrameQueries {
+        let index = state.current;
+        &mut state.frames[index]
+    }
+
+    fn resolve(&self, frame: &FrameQueries) -> VerboseResult<Option<GpuFrame>> {
+        if !frame.finished {
+            return Ok(None);
+        }
+
+        let timestamps = frame
+            .query_pool
+            .results(0, frame.next_query, VK_QUERY_RESULT_WAIT_BIT)?;
+
+        let duration = |begin: u32, end: u32| {
+            let ticks = timestamps[end as usize].saturating_sub(timestamps[begin as usize]);
+
+            Duration::from_nanos((ticks as f64 * self.timestamp_period) as u64)
+        };
+
+        Ok(Some(GpuFrame {
+            frame: frame.frame,
+            duration: duration(FRAME_BEGIN_QUERY, FRAME_END_QUERY),
+            scopes: Self::collect_scopes(&frame.scopes, None, &duration),
+        }))
+    }
+
+    fn collect_scopes(
+        scopes: &[RecordedScope],
+        parent: Option<usize>,
+        duration: &impl Fn(u32, u32) -> Duration,
+    ) -> Vec<GpuScope> {
+        scopes
+            .iter()
+            .enumerate()
+            .filter(|(_, scope)| scope.parent == parent && scope.ended)
+            .map(|(index, scope)| GpuScope {
+                name: scope.name.clone(),
+                duration: duration(scope.begin_query, scope.end_query()),
+                children: Self::collect_scopes(scopes, Some(index), duration),
+            })
+            .collect()
+    }
+}
+
+impl VulkanDevice for GpuProfiler {
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+impl std::fmt::Debug for GpuProfiler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "GpuProfiler {{ supported: {}, enabled: {} }}",
+            self.supported,
+            self.is_enabled()
+        )
+    }
+}
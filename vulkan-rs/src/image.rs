@@ -2,6 +2,7 @@ use utilities::prelude::*;
 
 use crate::impl_vk_handle;
 use crate::prelude::*;
+use crate::resourcestate::{transition_subresources, SubresourceBarrier};
 
 use std::cmp;
 use std::sync::{Arc, Mutex};
@@ -18,6 +19,7 @@ struct ImageCreateInfo {
 
     source_type: ImageSourceType,
     memory_properties: VkMemoryPropertyFlagBits,
+    allocation_policy: AllocationPolicy,
 }
 
 impl ImageCreateInfo {
@@ -44,6 +46,7 @@ impl ImageCreateInfo {
 
             source_type,
             memory_properties: VK_MEMORY_PROPERTY_DEVICE_LOCAL_BIT.into(),
+            allocation_policy: AllocationPolicy::default(),
         }
     }
 }
@@ -119,7 +122,10 @@ impl ImageBuilder {
                     sampler: self.sampler,
 
                     format: preinitialized_image.format,
-                    image_layout: Mutex::new(VK_IMAGE_LAYOUT_UNDEFINED),
+                    states: Mutex::new(vec![
+                        ResourceState::from_layout(VK_IMAGE_LAYOUT_UNDEFINED);
+                        preinitialized_image.layers as usize
+                    ]),
 
                     aspect_mask: self.subresource_range.aspectMask,
 
@@ -128,7 +134,7 @@ impl ImageBuilder {
                     layers: preinitialized_image.layers,
                     levels: 1,
                     sample_count: preinitialized_image.sample_count,
-                    _usage: preinitialized_image.usage,
+                    usage: preinitialized_image.usage,
                 });
 
                 if preinitialized_image.layout != VK_IMAGE_LAYOUT_UNDEFINED {
@@ -361,6 +367,17 @@ impl ImageBuilder {
         self
     }
 
+    pub fn allocation_policy(mut self, allocation_policy: AllocationPolicy) -> Self {
+        match self.builder_type {
+            ImageBuilderInternalType::NewImage(ref mut info) => {
+                info.allocation_policy = allocation_policy;
+            }
+            _ => panic!("wrong builder type in ImageBuilder"),
+        }
+
+        self
+    }
+
     fn calc_mip_map_levels(width: u32, height: u32) -> u32 {
         1 + (cmp::max(width, height) as f32).log2().floor() as u32
     }
@@ -390,8 +407,12 @@ impl ImageBuilder {
     ) -> VerboseResult<Arc<Image>> {
         let format = view_ci.format;
 
-        let (image, memory) =
-            Self::create_texture(device, &info.vk_image_create_info, info.memory_properties)?;
+        let (image, memory) = Self::create_texture(
+            device,
+            &info.vk_image_create_info,
+            info.memory_properties,
+            info.allocation_policy,
+        )?;
 
         view_ci.image = image;
 
@@ -408,7 +429,13 @@ impl ImageBuilder {
             sampler,
 
             format,
-            image_layout: Mutex::new(info.vk_image_create_info.initialLayout),
+            states: Mutex::new(vec![
+                ResourceState::from_layout(
+                    info.vk_image_create_info.initialLayout
+                );
+                (info.vk_image_create_info.arrayLayers * info.vk_image_create_info.mipLevels)
+                    as usize
+            ]),
 
             aspect_mask: view_ci.subresourceRange.aspectMask,
 
@@ -417,7 +444,7 @@ impl ImageBuilder {
             layers: info.vk_image_create_info.arrayLayers,
             levels: info.vk_image_create_info.mipLevels,
             sample_count: info.vk_image_create_info.samples,
-            _usage: info.vk_image_create_info.usage,
+            usage: info.vk_image_create_info.usage,
         }))
     }
 
@@ -425,9 +452,10 @@ impl ImageBuilder {
         device: &Arc<Device>,
         image_ci: &VkImageCreateInfo,
         reqs: VkMemoryPropertyFlagBits,
+        allocation_policy: AllocationPolicy,
     ) -> VerboseResult<(VkImage, Arc<Memory<u8>>)> {
         let image = Self::create_image(device, image_ci)?;
-        let memory = Memory::image_memory(device, reqs, image)?;
+        let memory = Memory::image_memory(device, reqs, image, image_ci.tiling, allocation_policy)?;
 
         Ok((image, memory))
     }
@@ -481,7 +509,8 @@ pub struct Image {
 
     // image information
     format: VkFormat,
-    image_layout: Mutex<VkImageLayout>,
+    // state per subresource, indexed by layer * levels + level
+    states: Mutex<Vec<ResourceState>>,
 
     aspect_mask: VkImageAspectFlagBits,
     width: u32,
@@ -489,7 +518,7 @@ pub struct Image {
     layers: u32, // array layers
     levels: u32, // mip map levels
     sample_count: VkSampleCountFlagBits,
-    _usage: VkImageUsageFlagBits,
+    usage: VkImageUsageFlagBits,
 }
 
 impl Image {
@@ -703,18 +732,51 @@ impl Image {
         self.sample_count
     }
 
+    pub fn usage(&self) -> VkImageUsageFlagBits {
+        self.usage
+    }
+
+    /// Layout of the first mip level of the first layer
     pub fn image_layout(&self) -> VerboseResult<VkImageLayout> {
-        Ok(self.image_layout.lock()?.clone())
+        Ok(self.states.lock()?[0].layout)
     }
 
+    /// Marks every subresource to be in `layout`, e.g. after a hand written barrier
     pub fn set_image_layout(&self, layout: VkImageLayout) -> VerboseResult<()> {
-        let mut image_layout = self.image_layout.lock()?;
+        let mut states = self.states.lock()?;
 
-        *image_layout = layout;
+        for state in states.iter_mut() {
+            *state = ResourceState::from_layout(layout);
+        }
 
         Ok(())
     }
 
+    pub fn subresource_state(&self, level: u32, layer: u32) -> VerboseResult<ResourceState> {
+        if level >= self.levels || layer >= self.layers {
+            create_error!(format!(
+                "subresource (level: {}, layer: {}) is out of range",
+                level, layer
+            ));
+        }
+
+        Ok(self.states.lock()?[(layer * self.levels + level) as usize])
+    }
+
+    pub(crate) fn transition(
+        &self,
+        subresource_range: &VkImageSubresourceRange,
+        next: &ResourceState,
+    ) -> VerboseResult<Vec<SubresourceBarrier>> {
+        transition_subresources(
+            &mut self.states.lock()?,
+            self.levels,
+            self.layers,
+            subresource_range,
+            next,
+        )
+    }
+
     pub fn full_resource_range(&self) -> VkImageSubresourceRange {
         VkImageSubresourceRange {
             aspectMask: self.aspect_mask,
@@ -734,6 +796,165 @@ impl Image {
         }
     }
 
+    /// Copies the color information of the first mip level and layer
+    /// into a host visible buffer
+    ///
+    /// The image is transitioned into `VK_IMAGE_LAYOUT_TRANSFER_SRC_OPTIMAL` for the copy
+    /// and back into its previous layout afterwards
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The source image, needs to be created with `VK_IMAGE_USAGE_TRANSFER_SRC_BIT`
+    pub fn copy_to_buffer(image: &Arc<Image>) -> VerboseResult<Arc<Buffer<u8>>> {
+        let buffer = Self::create_read_back_buffer(image)?;
+
+        copy_image_to_buffer(image, &buffer)?;
+
+        Ok(buffer)
+    }
+
+    /// Creates a host visible buffer, that can hold the first mip level and layer of `image`
+    pub fn create_read_back_buffer(image: &Arc<Image>) -> VerboseResult<Arc<Buffer<u8>>> {
+        if (image.usage & VK_IMAGE_USAGE_TRANSFER_SRC_BIT) == 0 {
+            create_error!("image requires VK_IMAGE_USAGE_TRANSFER_SRC_BIT to be copied");
+        }
+
+        let size = (image.width * image.height * Self::pixel_size(image.format)?) as VkDeviceSize;
+
+        Buffer::builder()
+            .set_usage(VK_BUFFER_USAGE_TRANSFER_DST_BIT)
+            .set_memory_properties(
+                VK_MEMORY_PROPERTY_HOST_VISIBLE_BIT | VK_MEMORY_PROPERTY_HOST_COHERENT_BIT,
+            )
+            .set_size(size)
+            .build(image.device.clone())
+    }
+
+    /// Records the copy of the first mip level and layer into `buffer`, e.g. into the command
+    /// buffer of a frame before the image is presented
+    ///
+    /// The image is transitioned into `VK_IMAGE_LAYOUT_TRANSFER_SRC_OPTIMAL` for the copy
+    /// and back into its previous layout afterwards
+    pub fn record_copy_to_buffer(
+        image: &Arc<Image>,
+        command_buffer: &Arc<CommandBuffer>,
+        buffer: &Arc<Buffer<u8>>,
+    ) -> VerboseResult<()> {
+        // remember the current layout (e.g. VK_IMAGE_LAYOUT_PRESENT_SRC_KHR) to restore it afterwards
+        let previous_layout = image.image_layout()?;
+
+        // subresource information
+        let subresource_range = VkImageSubresourceRange {
+            aspectMask: VK_IMAGE_ASPECT_COLOR_BIT.into(),
+            baseMipLevel: 0,
+            levelCount: image.levels(),
+            baseArrayLayer: 0,
+            layerCount: image.layers(),
+        };
+
+        // set image layout to be a copy source
+        command_buffer.set_image_layout(
+            image,
+            VK_IMAGE_LAYOUT_TRANSFER_SRC_OPTIMAL,
+            subresource_range.clone(),
+        )?;
+
+        // copy info for copying the content of the image into the buffer
+        let buffer_image_copy = VkBufferImageCopy {
+            bufferOffset: 0,
+            bufferRowLength: 0,
+            bufferImageHeight: 0,
+            imageSubresource: VkImageSubresourceLayers {
+                aspectMask: VK_IMAGE_ASPECT_COLOR_BIT.into(),
+                mipLevel: 0,
+                baseArrayLayer: 0,
+                layerCount: 1,
+            },
+            imageOffset: VkOffset3D { x: 0, y: 0, z: 0 },
+            imageExtent: VkExtent3D {
+                width: image.width(),
+                height: image.height(),
+                depth: 1,
+            },
+        };
+
+        // the actual copy command
+        command_buffer.copy_image_to_buffer(
+            image,
+            VK_IMAGE_LAYOUT_TRANSFER_SRC_OPTIMAL,
+            buffer,
+            &[buffer_image_copy],
+        );
+
+        // make the buffer content visible to the host
+        command_buffer.buffer_barrier(
+            buffer,
+            VK_ACCESS_TRANSFER_WRITE_BIT,
+            VK_PIPELINE_STAGE_TRANSFER_BIT,
+            VK_ACCESS_HOST_READ_BIT,
+            VK_PIPELINE_STAGE_HOST_BIT,
+        )?;
+
+        // an undefined or preinitialized layout can't be restored
+        match previous_layout {
+            VK_IMAGE_LAYOUT_UNDEFINED | VK_IMAGE_LAYOUT_PREINITIALIZED => (),
+            _ => command_buffer.set_image_layout(image, previous_layout, subresource_range)?,
+        }
+
+        Ok(())
+    }
+
+    /// Reads the color information back into an `image::RgbaImage`
+    ///
+    /// Supports 8 bit RGBA and BGRA formats, BGRA is swizzled into RGBA
+    ///
+    /// # Arguments
+    ///
+    /// * `image` - The source image, needs to be created with `VK_IMAGE_USAGE_TRANSFER_SRC_BIT`
+    pub fn to_rgba(image: &Arc<Image>) -> VerboseResult<image::RgbaImage> {
+        let buffer = Self::copy_to_buffer(image)?;
+
+        Self::read_back_rgba(image, &buffer)
+    }
+
+    /// Converts the content of a buffer, that `image` was copied into, into an `image::RgbaImage`
+    ///
+    /// The copy has to be finished on the GPU already
+    pub fn read_back_rgba(
+        image: &Arc<Image>,
+        buffer: &Arc<Buffer<u8>>,
+    ) -> VerboseResult<image::RgbaImage> {
+        let mut data: Vec<u8> = buffer.map_complete()?.iter().cloned().collect();
+
+        match image.format {
+            VK_FORMAT_R8G8B8A8_UNORM | VK_FORMAT_R8G8B8A8_SRGB => (),
+            VK_FORMAT_B8G8R8A8_UNORM | VK_FORMAT_B8G8R8A8_SRGB => {
+                for pixel in data.chunks_exact_mut(4) {
+                    pixel.swap(0, 2);
+                }
+            }
+            _ => create_error!(format!(
+                "format ({:?}) can't be converted to RGBA",
+                image.format
+            )),
+        }
+
+        match image::RgbaImage::from_raw(image.width, image.height, data) {
+            Some(rgba_image) => Ok(rgba_image),
+            None => create_error!("read back data doesn't match image dimensions"),
+        }
+    }
+
+    fn pixel_size(format: VkFormat) -> VerboseResult<u32> {
+        match format {
+            VK_FORMAT_R8G8B8A8_UNORM
+            | VK_FORMAT_R8G8B8A8_SRGB
+            | VK_FORMAT_B8G8R8A8_UNORM
+            | VK_FORMAT_B8G8R8A8_SRGB => Ok(4),
+            _ => create_error!(format!("pixel size of format ({:?}) is not known", format)),
+        }
+    }
+
     pub fn src_layout_to_access(image_layout: VkImageLayout) -> VkAccessFlagBits {
         match image_layout {
             VK_IMAGE_LAYOUT_UNDEFINED => 0u32.into(),
@@ -748,7 +969,9 @@ impl Image {
                     | VK_ACCESS_DEPTH_STENCIL_ATTACHMENT_WRITE_BIT
             }
             VK_IMAGE_LAYOUT_GENERAL => VK_ACCESS_MEMORY_READ_BIT | VK_ACCESS_MEMORY_WRITE_BIT,
-            _ => unimplemented!("source image layout ({:?})", image_layout),
+            VK_IMAGE_LAYOUT_DEPTH_STENCIL_READ_ONLY_OPTIMAL => {
+                VK_ACCESS_DEPTH_STENCIL_ATTACHMENT_READ_BIT | VK_ACCESS_SHADER_READ_BIT
+            }
         }
     }
 
@@ -938,6 +1161,41 @@ where
     Ok(())
 }
 
+fn copy_image_to_buffer(image: &Arc<Image>, buffer: &Arc<Buffer<u8>>) -> VerboseResult<()> {
+    let queue_lock = image.queue.lock()?;
+
+    // create a new command pool
+    let command_pool = CommandPool::builder()
+        .set_queue_family_index(queue_lock.family_index())
+        .build(image.device.clone())?;
+
+    // create a new command buffer
+    let command_buffer = CommandPool::allocate_primary_buffer(&command_pool)?;
+
+    // begin recording into this command buffer
+    command_buffer.begin(VkCommandBufferBeginInfo::new(
+        VK_COMMAND_BUFFER_USAGE_ONE_TIME_SUBMIT_BIT,
+    ))?;
+
+    Image::record_copy_to_buffer(image, &command_buffer, buffer)?;
+
+    // end command buffer recording
+    command_buffer.end()?;
+
+    // submit current queue
+    let submit = SubmitInfo::default().add_command_buffer(&command_buffer);
+    let fence = Fence::builder().build(image.device.clone())?;
+
+    queue_lock.submit(Some(&fence), &[submit])?;
+
+    // large images can take longer than any fixed timeout, u64::MAX waits without limit
+    image
+        .device
+        .wait_for_fences(&[&fence], true, Duration::from_nanos(u64::MAX))?;
+
+    Ok(())
+}
+
 fn copy_images_to_imagearray(
     device: &Arc<Device>,
     queue: &Arc<Mutex<Queue>>,
@@ -26,6 +26,7 @@ Extensions!(InstanceExtensions, {
     (win32_surface, "VK_KHR_win32_surface"),
     (surface, "VK_KHR_surface"),
     (physical_device_properties2, "VK_KHR_get_physical_device_properties2"),
+    (swapchain_colorspace, "VK_EXT_swapchain_colorspace"),
 });
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
@@ -11,9 +11,11 @@ pub mod descriptorpool;
 pub mod descriptorset;
 pub mod descriptorsetlayout;
 pub mod device;
+pub mod event;
 pub mod fence;
 pub mod framebuffer;
 pub mod googledisplaytiming;
+pub mod gpuprofiler;
 pub mod image;
 pub mod instance;
 pub mod mappedmemory;
@@ -26,6 +28,7 @@ pub mod pipelines;
 pub mod querypool;
 pub mod queue;
 pub mod renderpass;
+pub mod resourcestate;
 pub mod semaphore;
 pub mod shadermodule;
 pub mod surface;
@@ -110,3 +110,24 @@ where
 {
     Maintenance3Functions::load(|name| unsafe { transmute(f(device, name)) })
 }
+
+pub fn load_memory_requirements2<F>(f: F, device: VkDevice) -> MemoryRequirements2Functions
+where
+    F: Fn(VkDevice, &CStr) -> PFN_vkVoidFunction,
+{
+    MemoryRequirements2Functions::load(|name| unsafe { transmute(f(device, name)) })
+}
+
+pub fn load_hdr_metadata<F>(f: F, device: VkDevice) -> HdrMetadataFunctions
+where
+    F: Fn(VkDevice, &CStr) -> PFN_vkVoidFunction,
+{
+    HdrMetadataFunctions::load(|name| unsafe { transmute(f(device, name)) })
+}
+
+pub fn load_timeline_semaphore<F>(f: F, device: VkDevice) -> TimelineSemaphoreFunctions
+where
+    F: Fn(VkDevice, &CStr) -> PFN_vkVoidFunction,
+{
+    TimelineSemaphoreFunctions::load(|name| unsafe { transmute(f(device, name)) })
+}
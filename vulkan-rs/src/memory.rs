@@ -1,4 +1,4 @@
-use crate::allocator::block::Block;
+use crate::allocator::block::{AllocationPolicy, AllocationType, Block};
 use crate::prelude::*;
 
 use utilities::prelude::*;
@@ -21,8 +21,19 @@ impl<T> Memory<T> {
         memory_properties: VkMemoryPropertyFlagBits,
         buffer: VkBuffer,
         memory_requirements: VkMemoryRequirements,
+        allocation_type: AllocationType,
+        policy: AllocationPolicy,
     ) -> VerboseResult<Arc<Memory<T>>> {
-        let memory = Self::new(device, memory_requirements, memory_properties)?;
+        // forced requirements don't belong to the buffer (e.g. they come from an acceleration
+        // structure), so the memory must not be dedicated to the buffer
+        let memory = Self::new(
+            device,
+            memory_requirements,
+            memory_properties,
+            allocation_type,
+            policy,
+            None,
+        )?;
 
         device.bind_buffer_memory(buffer, memory.block.memory(), memory.block.offset)?;
 
@@ -33,20 +44,70 @@ impl<T> Memory<T> {
         device: &Arc<Device>,
         memory_properties: VkMemoryPropertyFlagBits,
         buffer: VkBuffer,
+        allocation_type: AllocationType,
+        policy: AllocationPolicy,
     ) -> VerboseResult<Arc<Memory<T>>> {
-        let memory_requirements = device.buffer_memory_requirements(buffer);
+        let (memory_requirements, policy) = if device.dedicated_allocation_enabled() {
+            let (memory_requirements, dedicated_requirements) =
+                device.buffer_memory_requirements2(buffer);
+
+            (
+                memory_requirements,
+                Self::select_policy(policy, &dedicated_requirements),
+            )
+        } else {
+            (device.buffer_memory_requirements(buffer), policy)
+        };
 
-        Self::forced_requirements(device, memory_properties, buffer, memory_requirements)
+        let dedicated_info = Self::dedicated_info(device, policy, VkImage::NULL_HANDLE, buffer);
+
+        let memory = Self::new(
+            device,
+            memory_requirements,
+            memory_properties,
+            allocation_type,
+            policy,
+            dedicated_info.as_ref(),
+        )?;
+
+        device.bind_buffer_memory(buffer, memory.block.memory(), memory.block.offset)?;
+
+        Ok(memory)
     }
 
     pub(crate) fn image_memory(
         device: &Arc<Device>,
         memory_properties: VkMemoryPropertyFlagBits,
         image: VkImage,
+        tiling: VkImageTiling,
+        policy: AllocationPolicy,
     ) -> VerboseResult<Arc<Memory<T>>> {
-        let memory_requirements = device.image_memory_requirements(image);
+        let (memory_requirements, policy) = if device.dedicated_allocation_enabled() {
+            let (memory_requirements, dedicated_requirements) =
+                device.image_memory_requirements2(image);
+
+            (
+                memory_requirements,
+                Self::select_policy(policy, &dedicated_requirements),
+            )
+        } else {
+            (device.image_memory_requirements(image), policy)
+        };
 
-        let memory = Self::new(device, memory_requirements, memory_properties)?;
+        let dedicated_info = Self::dedicated_info(device, policy, image, VkBuffer::NULL_HANDLE);
+
+        let memory = Self::new(
+            device,
+            memory_requirements,
+            memory_properties,
+            if tiling == VK_IMAGE_TILING_LINEAR {
+                AllocationType::LinearImage
+            } else {
+                AllocationType::Image
+            },
+            policy,
+            dedicated_info.as_ref(),
+        )?;
 
         device.bind_image_memory(image, memory.block.memory(), memory.block.offset)?;
 
@@ -57,15 +118,19 @@ impl<T> Memory<T> {
         device: &Arc<Device>,
         memory_requirements: VkMemoryRequirements,
         memory_properties: VkMemoryPropertyFlagBits,
+        allocation_type: AllocationType,
+        policy: AllocationPolicy,
+        dedicated_info: Option<&VkMemoryDedicatedAllocateInfoKHR>,
     ) -> VerboseResult<Arc<Memory<T>>> {
         let memory_type_index = device
             .memory_type_from_properties(memory_requirements.memoryTypeBits, memory_properties)?;
 
-        let block = Device::allocate_memory_from_allocator(
-            device,
-            memory_requirements.size,
+        let block = device.allocate_memory_from_allocator(
+            &memory_requirements,
             memory_type_index,
-            memory_requirements.alignment,
+            allocation_type,
+            policy,
+            dedicated_info,
         )?;
 
         Ok(Arc::new(Memory {
@@ -77,6 +142,33 @@ impl<T> Memory<T> {
         }))
     }
 
+    /// The driver's wish for a dedicated allocation overrules the requested policy
+    fn select_policy(
+        policy: AllocationPolicy,
+        dedicated_requirements: &VkMemoryDedicatedRequirementsKHR,
+    ) -> AllocationPolicy {
+        if dedicated_requirements.prefersDedicatedAllocation == VK_TRUE
+            || dedicated_requirements.requiresDedicatedAllocation == VK_TRUE
+        {
+            AllocationPolicy::Dedicated
+        } else {
+            policy
+        }
+    }
+
+    fn dedicated_info(
+        device: &Device,
+        policy: AllocationPolicy,
+        image: VkImage,
+        buffer: VkBuffer,
+    ) -> Option<VkMemoryDedicatedAllocateInfoKHR> {
+        if policy == AllocationPolicy::Dedicated && device.dedicated_allocation_enabled() {
+            Some(VkMemoryDedicatedAllocateInfoKHR::new(image, buffer))
+        } else {
+            None
+        }
+    }
+
     pub(crate) fn vk_handle(&self) -> VkDeviceMemory {
         self.block.memory()
     }
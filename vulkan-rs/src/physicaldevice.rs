@@ -17,6 +17,7 @@ pub struct PhysicalDevice {
     // extension info
     ray_tracing_properties: VkPhysicalDeviceRayTracingPropertiesNV,
     descriptor_indexing_features: VkPhysicalDeviceDescriptorIndexingFeaturesEXT,
+    timeline_semaphore_features: VkPhysicalDeviceTimelineSemaphoreFeaturesKHR,
     descriptor_indexing_properties: VkPhysicalDeviceDescriptorIndexingPropertiesEXT,
 }
 
@@ -93,6 +94,17 @@ impl PhysicalDevice {
         device_features2.chain(&descriptor_indexing_features);
         instance.physical_device_features2(physical_device, &mut device_features2);
 
+        // get timeline semaphore features, only known to devices with the extension
+        //
+        // like the device creation this relies on the extension, since the instance doesn't
+        // request Vulkan 1.2, where timeline semaphores became core
+        let timeline_semaphore_features = VkPhysicalDeviceTimelineSemaphoreFeaturesKHR::default();
+
+        if extensions.contains(&VkString::new("VK_KHR_timeline_semaphore")) {
+            device_features2.chain(&timeline_semaphore_features);
+            instance.physical_device_features2(physical_device, &mut device_features2);
+        }
+
         Ok(Arc::new(PhysicalDevice {
             instance,
             physical_device,
@@ -104,6 +116,7 @@ impl PhysicalDevice {
             ray_tracing_properties,
             descriptor_indexing_properties,
             descriptor_indexing_features,
+            timeline_semaphore_features,
         }))
     }
 }
@@ -144,6 +157,10 @@ impl PhysicalDevice {
         &self.descriptor_indexing_features
     }
 
+    pub fn timeline_semaphore_features(&self) -> &VkPhysicalDeviceTimelineSemaphoreFeaturesKHR {
+        &self.timeline_semaphore_features
+    }
+
     pub fn check_optimal_format_features(
         &self,
         format: VkFormat,
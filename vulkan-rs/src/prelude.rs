@@ -1,15 +1,21 @@
 // vulkan structures
 pub use super::accelerationstructure::{AccelerationStructure, AccelerationStructureBuilder};
+pub use super::allocator::block::{AllocationPolicy, AllocationType};
 pub use super::buffer::Buffer;
 pub use super::commandbuffer::{CommandBuffer, CommandBufferBuilder, QueryEnable};
 pub use super::commandpool::CommandPool;
 pub use super::descriptorpool::DescriptorPool;
 pub use super::descriptorset::*;
 pub use super::descriptorsetlayout::DescriptorSetLayout;
-pub use super::device::{Device, DeviceExtensions, DeviceFeatures};
+pub use super::device::{
+    Device, DeviceExtensions, DeviceFeatures, LiveAllocation, MemoryHeapStatistics,
+    MemoryStatistics, MemoryTypeStatistics, MIN_MEMORY_CHUNK_SIZE,
+};
+pub use super::event::Event;
 pub use super::fence::Fence;
 pub use super::framebuffer::{Framebuffer, FramebufferBuilder};
 pub use super::googledisplaytiming::*;
+pub use super::gpuprofiler::{GpuFrame, GpuProfiler, GpuScope};
 pub use super::image::*;
 pub use super::instance::*;
 pub use super::memory::Memory;
@@ -17,16 +23,17 @@ pub use super::physicaldevice::PhysicalDevice;
 pub use super::pipeline::Pipeline;
 pub use super::pipelinecache::PipelineCache;
 pub use super::pipelinelayout::{PipelineLayout, PipelineLayoutBuilder};
-pub use super::querypool::QueryPool;
+pub use super::querypool::{PipelineStatistics, QueryPool};
 pub use super::queue::*;
 pub use super::renderpass::RenderPass;
+pub use super::resourcestate::{BarrierBatch, ResourceState, ResourceUsage};
 pub use super::sampler_manager::{Sampler, SamplerBuilder};
 pub use super::semaphore::Semaphore;
 pub use super::shadermodule::{
     AddSpecializationConstant, ShaderModule, ShaderType, SpecializationConstants,
 };
 pub use super::surface::Surface;
-pub use super::swapchain::Swapchain;
+pub use super::swapchain::{HdrMetadata, Swapchain};
 
 pub use super::pipelines::{
     compute_pipeline::ComputePipelineBuilder,
@@ -6,11 +6,73 @@ use crate::prelude::*;
 use std::mem;
 use std::sync::Arc;
 
+/// Every statistic in the order the driver writes them into the results
+const PIPELINE_STATISTICS: [VkQueryPipelineStatisticFlags; 11] = [
+    VK_QUERY_PIPELINE_STATISTIC_INPUT_ASSEMBLY_VERTICES_BIT,
+    VK_QUERY_PIPELINE_STATISTIC_INPUT_ASSEMBLY_PRIMITIVES_BIT,
+    VK_QUERY_PIPELINE_STATISTIC_VERTEX_SHADER_INVOCATIONS_BIT,
+    VK_QUERY_PIPELINE_STATISTIC_GEOMETRY_SHADER_INVOCATIONS_BIT,
+    VK_QUERY_PIPELINE_STATISTIC_GEOMETRY_SHADER_PRIMITIVES_BIT,
+    VK_QUERY_PIPELINE_STATISTIC_CLIPPING_INVOCATIONS_BIT,
+    VK_QUERY_PIPELINE_STATISTIC_CLIPPING_PRIMITIVES_BIT,
+    VK_QUERY_PIPELINE_STATISTIC_FRAGMENT_SHADER_INVOCATIONS_BIT,
+    VK_QUERY_PIPELINE_STATISTIC_TESSELLATION_CONTROL_SHADER_PATCHES_BIT,
+    VK_QUERY_PIPELINE_STATISTIC_TESSELLATION_EVALUATION_SHADER_INVOCATIONS_BIT,
+    VK_QUERY_PIPELINE_STATISTIC_COMPUTE_SHADER_INVOCATIONS_BIT,
+];
+
+/// Decoded result of a single pipeline statistics query
+#[derive(Debug, Clone, PartialEq)]
+pub struct PipelineStatistics {
+    values: Vec<(VkQueryPipelineStatisticFlags, u64)>,
+}
+
+impl PipelineStatistics {
+    // `values` are in the order of `statistics`
+    fn new(statistics: &[VkQueryPipelineStatisticFlags], values: &[u64]) -> Self {
+        PipelineStatistics {
+            values: statistics
+                .iter()
+                .cloned()
+                .zip(values.iter().cloned())
+                .collect(),
+        }
+    }
+
+    /// `None` if the statistic wasn't enabled for the query pool
+    pub fn get(&self, statistic: VkQueryPipelineStatisticFlags) -> Option<u64> {
+        self.values
+            .iter()
+            .find(|(flag, _)| *flag == statistic)
+            .map(|(_, value)| *value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(VkQueryPipelineStatisticFlags, u64)> {
+        self.values.iter()
+    }
+
+    pub fn vertex_shader_invocations(&self) -> Option<u64> {
+        self.get(VK_QUERY_PIPELINE_STATISTIC_VERTEX_SHADER_INVOCATIONS_BIT)
+    }
+
+    pub fn fragment_shader_invocations(&self) -> Option<u64> {
+        self.get(VK_QUERY_PIPELINE_STATISTIC_FRAGMENT_SHADER_INVOCATIONS_BIT)
+    }
+
+    pub fn compute_shader_invocations(&self) -> Option<u64> {
+        self.get(VK_QUERY_PIPELINE_STATISTIC_COMPUTE_SHADER_INVOCATIONS_BIT)
+    }
+}
+
 #[derive(Debug)]
 pub struct QueryPool {
     device: Arc<Device>,
     query_pool: VkQueryPool,
     query_count: u32,
+
+    query_type: VkQueryType,
+    statistics: Vec<VkQueryPipelineStatisticFlags>,
+    precise: bool,
 }
 
 impl QueryPool {
@@ -20,6 +82,18 @@ impl QueryPool {
         query_count: u32,
         pipeline_statistics: impl Into<VkQueryPipelineStatisticFlagBits>,
     ) -> VerboseResult<Arc<QueryPool>> {
+        Self::create(device, query_type, query_count, pipeline_statistics, false)
+    }
+
+    fn create(
+        device: Arc<Device>,
+        query_type: VkQueryType,
+        query_count: u32,
+        pipeline_statistics: impl Into<VkQueryPipelineStatisticFlagBits>,
+        precise: bool,
+    ) -> VerboseResult<Arc<QueryPool>> {
+        let pipeline_statistics = pipeline_statistics.into();
+
         let query_pool_ci = VkQueryPoolCreateInfo::new(
             VK_QUERY_POOL_CREATE_NULL_BIT,
             query_type,
@@ -33,23 +107,227 @@ impl QueryPool {
             device,
             query_pool,
             query_count,
+
+            query_type,
+            statistics: Self::enabled_statistics(query_type, pipeline_statistics),
+            precise,
         }))
     }
 
+    fn enabled_statistics(
+        query_type: VkQueryType,
+        pipeline_statistics: VkQueryPipelineStatisticFlagBits,
+    ) -> Vec<VkQueryPipelineStatisticFlags> {
+        if query_type != VK_QUERY_TYPE_PIPELINE_STATISTICS {
+            return Vec::new();
+        }
+
+        PIPELINE_STATISTICS
+            .iter()
+            .filter(|statistic| (pipeline_statistics & **statistic) != 0)
+            .cloned()
+            .collect()
+    }
+
+    /// Creates a pool of occlusion queries
+    ///
+    /// Precise queries count the exact number of passed samples and require the
+    /// `occlusionQueryPrecise` device feature, otherwise only zero and non zero is reliable
+    pub fn occlusion(
+        device: Arc<Device>,
+        query_count: u32,
+        precise: bool,
+    ) -> VerboseResult<Arc<QueryPool>> {
+        Self::create(device, VK_QUERY_TYPE_OCCLUSION, query_count, 0, precise)
+    }
+
+    /// Creates a pool of pipeline statistics queries, requires the
+    /// `pipelineStatisticsQuery` device feature
+    pub fn pipeline_statistics(
+        device: Arc<Device>,
+        query_count: u32,
+        statistics: impl Into<VkQueryPipelineStatisticFlagBits>,
+    ) -> VerboseResult<Arc<QueryPool>> {
+        let statistics = statistics.into();
+
+        if statistics == 0 {
+            create_error!("pipeline statistics query pool requires at least one statistic");
+        }
+
+        Self::new(
+            device,
+            VK_QUERY_TYPE_PIPELINE_STATISTICS,
+            query_count,
+            statistics,
+        )
+    }
+
+    pub fn query_count(&self) -> u32 {
+        self.query_count
+    }
+
+    pub fn query_type(&self) -> VkQueryType {
+        self.query_type
+    }
+
+    pub fn is_precise(&self) -> bool {
+        self.precise
+    }
+
+    /// Enabled statistics, in the order they appear in the results
+    pub fn statistics(&self) -> &[VkQueryPipelineStatisticFlags] {
+        &self.statistics
+    }
+
+    /// Flags that have to be used when beginning a query of this pool
+    pub fn control_flags(&self) -> VkQueryControlFlagBits {
+        if self.precise {
+            VK_QUERY_CONTROL_PRECISE_BIT.into()
+        } else {
+            0.into()
+        }
+    }
+
+    /// Number of `u64` values a single query writes, without availability
+    pub fn values_per_query(&self) -> usize {
+        match self.query_type {
+            VK_QUERY_TYPE_PIPELINE_STATISTICS => self.statistics.len(),
+            _ => 1,
+        }
+    }
+
+    /// Size in bytes of the results of a single query with 64 bit values
+    ///
+    /// With availability, an additional value is appended to each query, which is non zero if
+    /// the results are available
+    pub fn result_stride(&self, with_availability: bool) -> VkDeviceSize {
+        let value_count = if with_availability {
+            self.values_per_query() + 1
+        } else {
+            self.values_per_query()
+        };
+
+        (value_count * mem::size_of::<u64>()) as VkDeviceSize
+    }
+
     pub fn get_results(&self) -> VerboseResult<Vec<u64>> {
-        let mut data = vec![0; self.query_count as usize];
+        self.results(0, self.query_count, VK_QUERY_RESULT_64_BIT)
+    }
+
+    /// Reads `query_count` results starting at `first_query` as `u64`
+    ///
+    /// The values of all queries are written consecutively, see `result_stride`
+    ///
+    /// Fails if results aren't available yet, unless `VK_QUERY_RESULT_WAIT_BIT` or
+    /// `VK_QUERY_RESULT_WITH_AVAILABILITY_BIT` is set
+    pub fn results(
+        &self,
+        first_query: u32,
+        query_count: u32,
+        flags: impl Into<VkQueryResultFlagBits>,
+    ) -> VerboseResult<Vec<u64>> {
+        match first_query.checked_add(query_count) {
+            Some(end) if end <= self.query_count => (),
+            _ => create_error!(format!(
+                "query range ({} queries starting at {}) exceeds query pool size ({})",
+                query_count, first_query, self.query_count
+            )),
+        }
+
+        let flags: VkQueryResultFlagBits = flags.into();
+        let with_availability = (flags & VK_QUERY_RESULT_WITH_AVAILABILITY_BIT) != 0;
+        let stride = self.result_stride(with_availability);
+
+        let mut data = vec![0; query_count as usize * stride as usize / mem::size_of::<u64>()];
 
-        self.device.query_pool_results(
+        let available = self.device.query_pool_results(
             self.query_pool,
-            0,
-            self.query_count,
+            first_query,
+            query_count,
             &mut data,
-            mem::size_of::<u64>() as u64,
-            VK_QUERY_RESULT_64_BIT,
+            stride,
+            flags | VK_QUERY_RESULT_64_BIT,
         )?;
 
+        // without availability there is no way to tell which values are valid
+        if !available && !with_availability {
+            create_error!("query results are not available yet");
+        }
+
         Ok(data)
     }
+
+    /// Number of passed samples per query, `None` if the result isn't available yet
+    pub fn occlusion_results(
+        &self,
+        first_query: u32,
+        query_count: u32,
+        wait: bool,
+    ) -> VerboseResult<Vec<Option<u64>>> {
+        if self.query_type != VK_QUERY_TYPE_OCCLUSION {
+            create_error!(format!(
+                "occlusion results requested from {:?} query pool",
+                self.query_type
+            ));
+        }
+
+        Ok(self
+            .available_results(first_query, query_count, wait)?
+            .into_iter()
+            .map(|values| values.map(|values| values[0]))
+            .collect())
+    }
+
+    /// Decoded statistics per query, `None` if the result isn't available yet
+    pub fn pipeline_statistics_results(
+        &self,
+        first_query: u32,
+        query_count: u32,
+        wait: bool,
+    ) -> VerboseResult<Vec<Option<PipelineStatistics>>> {
+        if self.query_type != VK_QUERY_TYPE_PIPELINE_STATISTICS {
+            create_error!(format!(
+                "pipeline statistics requested from {:?} query pool",
+                self.query_type
+            ));
+        }
+
+        Ok(self
+            .available_results(first_query, query_count, wait)?
+            .into_iter()
+            .map(|values| values.map(|values| PipelineStatistics::new(&self.statistics, &values)))
+            .collect())
+    }
+
+    fn available_results(
+        &self,
+        first_query: u32,
+        query_count: u32,
+        wait: bool,
+    ) -> VerboseResult<Vec<Option<Vec<u64>>>> {
+        let mut flags: VkQueryResultFlagBits = VK_QUERY_RESULT_WITH_AVAILABILITY_BIT.into();
+
+        if wait {
+            flags |= VK_QUERY_RESULT_WAIT_BIT;
+        }
+
+        let data = self.results(first_query, query_count, flags)?;
+
+        Ok(Self::split_available(&data, self.values_per_query()))
+    }
+
+    // splits results with availability into the values of each query
+    fn split_available(data: &[u64], value_count: usize) -> Vec<Option<Vec<u64>>> {
+        data.chunks(value_count + 1)
+            .map(|query| {
+                if query[value_count] != 0 {
+                    Some(query[..value_count].to_vec())
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
 }
 
 impl VulkanDevice for QueryPool {
@@ -65,3 +343,56 @@ impl Drop for QueryPool {
         self.device.destroy_query_pool(self.query_pool);
     }
 }
+
+#[test]
+fn query_pool_statistics_order() {
+    // the results are ordered by the bits, not by the order of the flags
+    let statistics = QueryPool::enabled_statistics(
+        VK_QUERY_TYPE_PIPELINE_STATISTICS,
+        VK_QUERY_PIPELINE_STATISTIC_COMPUTE_SHADER_INVOCATIONS_BIT
+            | VK_QUERY_PIPELINE_STATISTIC_INPUT_ASSEMBLY_VERTICES_BIT
+            | VK_QUERY_PIPELINE_STATISTIC_FRAGMENT_SHADER_INVOCATIONS_BIT,
+    );
+
+    assert_eq!(
+        statistics,
+        vec![
+            VK_QUERY_PIPELINE_STATISTIC_INPUT_ASSEMBLY_VERTICES_BIT,
+            VK_QUERY_PIPELINE_STATISTIC_FRAGMENT_SHADER_INVOCATIONS_BIT,
+            VK_QUERY_PIPELINE_STATISTIC_COMPUTE_SHADER_INVOCATIONS_BIT,
+        ]
+    );
+
+    let pipeline_statistics = PipelineStatistics::new(&statistics, &[300, 1200, 64]);
+
+    assert_eq!(
+        pipeline_statistics.fragment_shader_invocations(),
+        Some(1200)
+    );
+    assert_eq!(pipeline_statistics.compute_shader_invocations(), Some(64));
+    assert_eq!(pipeline_statistics.vertex_shader_invocations(), None);
+
+    // other query types ignore the statistics
+    assert!(QueryPool::enabled_statistics(
+        VK_QUERY_TYPE_OCCLUSION,
+        VK_QUERY_PIPELINE_STATISTIC_INPUT_ASSEMBLY_VERTICES_BIT.into(),
+    )
+    .is_empty());
+}
+
+#[test]
+fn query_pool_availability() {
+    // two values per query, followed by their availability
+    let data = [1, 2, 1, 0, 0, 0, 5, 6, 1];
+
+    assert_eq!(
+        QueryPool::split_available(&data, 2),
+        vec![Some(vec![1, 2]), None, Some(vec![5, 6])]
+    );
+
+    // occlusion queries write a single value
+    assert_eq!(
+        QueryPool::split_available(&[0, 1, 7, 0], 1),
+        vec![Some(vec![0]), None]
+    );
+}
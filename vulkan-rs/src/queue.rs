@@ -76,7 +76,23 @@ impl Queue {
 
     /// really expensiv call, since its locks the queue until it is idle
     pub fn submit(&self, fence: Option<&Arc<Fence>>, submits: &[SubmitInfo]) -> VerboseResult<()> {
-        let submit_infos: Vec<VkSubmitInfo> = submits.iter().map(|s| s.as_vk_submit()).collect();
+        // the timeline infos have to outlive the submit infos, which point to them
+        let timeline_infos: Vec<Option<VkTimelineSemaphoreSubmitInfoKHR>> =
+            submits.iter().map(|s| s.as_timeline_submit()).collect();
+
+        let submit_infos: Vec<VkSubmitInfo> = submits
+            .iter()
+            .zip(timeline_infos.iter())
+            .map(|(s, timeline_info)| {
+                let mut submit_info = s.as_vk_submit();
+
+                if let Some(timeline_info) = timeline_info {
+                    submit_info.chain(timeline_info);
+                }
+
+                submit_info
+            })
+            .collect();
 
         let fence = match fence {
             Some(fence) => fence.vk_handle(),
@@ -180,11 +196,30 @@ pub struct SubmitInfo {
     wait_stages: Vec<VkPipelineStageFlagBits>,
     command_buffers: Vec<VkCommandBuffer>,
     signal_semaphores: Vec<VkSemaphore>,
+
+    // values for timeline semaphores, binary semaphores ignore them
+    wait_values: Vec<u64>,
+    signal_values: Vec<u64>,
+    uses_timeline: bool,
 }
 
 impl SubmitInfo {
     pub fn add_wait_semaphore(mut self, wait_semaphore: impl VkHandle<VkSemaphore>) -> Self {
         self.wait_semaphores.push(wait_semaphore.vk_handle());
+        self.wait_values.push(0);
+
+        self
+    }
+
+    /// Waits until the timeline semaphore reached `value`
+    pub fn add_wait_semaphore_value(
+        mut self,
+        wait_semaphore: impl VkHandle<VkSemaphore>,
+        value: u64,
+    ) -> Self {
+        self.wait_semaphores.push(wait_semaphore.vk_handle());
+        self.wait_values.push(value);
+        self.uses_timeline = true;
 
         self
     }
@@ -203,6 +238,20 @@ impl SubmitInfo {
 
     pub fn add_signal_semaphore(mut self, signal_semaphore: impl VkHandle<VkSemaphore>) -> Self {
         self.signal_semaphores.push(signal_semaphore.vk_handle());
+        self.signal_values.push(0);
+
+        self
+    }
+
+    /// Sets the timeline semaphore to `value`, when the submit completed
+    pub fn add_signal_semaphore_value(
+        mut self,
+        signal_semaphore: impl VkHandle<VkSemaphore>,
+        value: u64,
+    ) -> Self {
+        self.signal_semaphores.push(signal_semaphore.vk_handle());
+        self.signal_values.push(value);
+        self.uses_timeline = true;
 
         self
     }
@@ -215,4 +264,15 @@ impl SubmitInfo {
             self.signal_semaphores.as_slice(),
         )
     }
+
+    fn as_timeline_submit(&self) -> Option<VkTimelineSemaphoreSubmitInfoKHR> {
+        if self.uses_timeline {
+            Some(VkTimelineSemaphoreSubmitInfoKHR::new(
+                self.wait_values.as_slice(),
+                self.signal_values.as_slice(),
+            ))
+        } else {
+            None
+        }
+    }
 }
@@ -0,0 +1,635 @@
+use utilities::prelude::*;
+
+use crate::prelude::*;
+
+use std::sync::Arc;
+
+/// Describes how the following commands are going to use a resource
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResourceUsage {
+    TransferRead,
+    TransferWrite,
+    HostRead,
+    HostWrite,
+
+    // buffer only usages
+    VertexBuffer,
+    IndexBuffer,
+    IndirectBuffer,
+    UniformBuffer(VkPipelineStageFlagBits),
+
+    /// Sampled image or storage buffer read in the given shader stages
+    ShaderRead(VkPipelineStageFlagBits),
+    /// Storage image or storage buffer write in the given shader stages
+    ShaderWrite(VkPipelineStageFlagBits),
+    ShaderReadWrite(VkPipelineStageFlagBits),
+
+    // image only usages
+    ColorAttachment,
+    DepthStencilAttachment,
+    DepthStencilReadOnly,
+    Present,
+}
+
+impl ResourceUsage {
+    pub fn access(&self) -> VkAccessFlagBits {
+        match self {
+            ResourceUsage::TransferRead => VK_ACCESS_TRANSFER_READ_BIT.into(),
+            ResourceUsage::TransferWrite => VK_ACCESS_TRANSFER_WRITE_BIT.into(),
+            ResourceUsage::HostRead => VK_ACCESS_HOST_READ_BIT.into(),
+            ResourceUsage::HostWrite => VK_ACCESS_HOST_WRITE_BIT.into(),
+            ResourceUsage::VertexBuffer => VK_ACCESS_VERTEX_ATTRIBUTE_READ_BIT.into(),
+            ResourceUsage::IndexBuffer => VK_ACCESS_INDEX_READ_BIT.into(),
+            ResourceUsage::IndirectBuffer => VK_ACCESS_INDIRECT_COMMAND_READ_BIT.into(),
+            ResourceUsage::UniformBuffer(_) => VK_ACCESS_UNIFORM_READ_BIT.into(),
+            ResourceUsage::ShaderRead(_) => VK_ACCESS_SHADER_READ_BIT.into(),
+            ResourceUsage::ShaderWrite(_) => VK_ACCESS_SHADER_WRITE_BIT.into(),
+            ResourceUsage::ShaderReadWrite(_) => {
+                VK_ACCESS_SHADER_READ_BIT | VK_ACCESS_SHADER_WRITE_BIT
+            }
+            ResourceUsage::ColorAttachment => {
+                VK_ACCESS_COLOR_ATTACHMENT_READ_BIT | VK_ACCESS_COLOR_ATTACHMENT_WRITE_BIT
+            }
+            ResourceUsage::DepthStencilAttachment => {
+                VK_ACCESS_DEPTH_STENCIL_ATTACHMENT_READ_BIT
+                    | VK_ACCESS_DEPTH_STENCIL_ATTACHMENT_WRITE_BIT
+            }
+            ResourceUsage::DepthStencilReadOnly => {
+                VK_ACCESS_DEPTH_STENCIL_ATTACHMENT_READ_BIT | VK_ACCESS_SHADER_READ_BIT
+            }
+            // presentation engine takes care of visibility
+            ResourceUsage::Present => 0u32.into(),
+        }
+    }
+
+    pub fn stage(&self) -> VkPipelineStageFlagBits {
+        match self {
+            ResourceUsage::TransferRead | ResourceUsage::TransferWrite => {
+                VK_PIPELINE_STAGE_TRANSFER_BIT.into()
+            }
+            ResourceUsage::HostRead | ResourceUsage::HostWrite => VK_PIPELINE_STAGE_HOST_BIT.into(),
+            ResourceUsage::VertexBuffer | ResourceUsage::IndexBuffer => {
+                VK_PIPELINE_STAGE_VERTEX_INPUT_BIT.into()
+            }
+            ResourceUsage::IndirectBuffer => VK_PIPELINE_STAGE_DRAW_INDIRECT_BIT.into(),
+            ResourceUsage::UniformBuffer(stages)
+            | ResourceUsage::ShaderRead(stages)
+            | ResourceUsage::ShaderWrite(stages)
+            | ResourceUsage::ShaderReadWrite(stages) => *stages,
+            ResourceUsage::ColorAttachment => VK_PIPELINE_STAGE_COLOR_ATTACHMENT_OUTPUT_BIT.into(),
+            ResourceUsage::DepthStencilAttachment => {
+                VK_PIPELINE_STAGE_EARLY_FRAGMENT_TESTS_BIT
+                    | VK_PIPELINE_STAGE_LATE_FRAGMENT_TESTS_BIT
+            }
+            ResourceUsage::DepthStencilReadOnly => {
+                VK_PIPELINE_STAGE_EARLY_FRAGMENT_TESTS_BIT
+                    | VK_PIPELINE_STAGE_LATE_FRAGMENT_TESTS_BIT
+                    | VK_PIPELINE_STAGE_FRAGMENT_SHADER_BIT
+            }
+            ResourceUsage::Present => VK_PIPELINE_STAGE_BOTTOM_OF_PIPE_BIT.into(),
+        }
+    }
+
+    /// Layout an image needs for this usage, `None` for buffer only usages
+    pub fn image_layout(&self) -> Option<VkImageLayout> {
+        match self {
+            ResourceUsage::TransferRead => Some(VK_IMAGE_LAYOUT_TRANSFER_SRC_OPTIMAL),
+            ResourceUsage::TransferWrite => Some(VK_IMAGE_LAYOUT_TRANSFER_DST_OPTIMAL),
+            ResourceUsage::HostRead | ResourceUsage::HostWrite => Some(VK_IMAGE_LAYOUT_GENERAL),
+            ResourceUsage::VertexBuffer
+            | ResourceUsage::IndexBuffer
+            | ResourceUsage::IndirectBuffer
+            | ResourceUsage::UniformBuffer(_) => None,
+            ResourceUsage::ShaderRead(_) => Some(VK_IMAGE_LAYOUT_SHADER_READ_ONLY_OPTIMAL),
+            ResourceUsage::ShaderWrite(_) | ResourceUsage::ShaderReadWrite(_) => {
+                Some(VK_IMAGE_LAYOUT_GENERAL)
+            }
+            ResourceUsage::ColorAttachment => Some(VK_IMAGE_LAYOUT_COLOR_ATTACHMENT_OPTIMAL),
+            ResourceUsage::DepthStencilAttachment => {
+                Some(VK_IMAGE_LAYOUT_DEPTH_STENCIL_ATTACHMENT_OPTIMAL)
+            }
+            ResourceUsage::DepthStencilReadOnly => {
+                Some(VK_IMAGE_LAYOUT_DEPTH_STENCIL_READ_ONLY_OPTIMAL)
+            }
+            ResourceUsage::Present => Some(VK_IMAGE_LAYOUT_PRESENT_SRC_KHR),
+        }
+    }
+
+    fn image_state(&self) -> VerboseResult<ResourceState> {
+        match self.image_layout() {
+            Some(layout) => Ok(ResourceState::new(self.access(), self.stage(), layout)),
+            None => create_error!(format!("{:?} can't be used for images", self)),
+        }
+    }
+
+    fn buffer_state(&self) -> VerboseResult<ResourceState> {
+        match self {
+            ResourceUsage::ColorAttachment
+            | ResourceUsage::DepthStencilAttachment
+            | ResourceUsage::DepthStencilReadOnly
+            | ResourceUsage::Present => {
+                create_error!(format!("{:?} can't be used for buffers", self))
+            }
+            _ => Ok(ResourceState::new(
+                self.access(),
+                self.stage(),
+                VK_IMAGE_LAYOUT_UNDEFINED,
+            )),
+        }
+    }
+}
+
+/// Last known access of a buffer or an image subresource
+///
+/// The layout of buffers is always `VK_IMAGE_LAYOUT_UNDEFINED`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceState {
+    pub access: VkAccessFlagBits,
+    pub stage: VkPipelineStageFlagBits,
+    pub layout: VkImageLayout,
+
+    /// Last write or layout transition, that has been made visible to `access` in `stage`
+    pub last_write: VkAccessFlagBits,
+    pub last_write_stage: VkPipelineStageFlagBits,
+}
+
+impl ResourceState {
+    pub(crate) fn new(
+        access: impl Into<VkAccessFlagBits>,
+        stage: impl Into<VkPipelineStageFlagBits>,
+        layout: VkImageLayout,
+    ) -> Self {
+        ResourceState {
+            access: access.into(),
+            stage: stage.into(),
+            layout,
+
+            last_write: 0u32.into(),
+            last_write_stage: 0u32.into(),
+        }
+    }
+
+    /// State of a resource, that was put into `layout` by a hand written barrier
+    pub(crate) fn from_layout(layout: VkImageLayout) -> Self {
+        let access = Image::src_layout_to_access(layout);
+
+        ResourceState::new(access, CommandBuffer::access_to_stage(access), layout)
+    }
+
+    pub(crate) fn from_access(
+        access: impl Into<VkAccessFlagBits>,
+        stage: impl Into<VkPipelineStageFlagBits>,
+    ) -> Self {
+        ResourceState::new(access, stage, VK_IMAGE_LAYOUT_UNDEFINED)
+    }
+
+    fn is_written(&self) -> bool {
+        (self.access & Self::write_access()) != 0
+    }
+
+    fn write_access() -> VkAccessFlagBits {
+        VK_ACCESS_SHADER_WRITE_BIT
+            | VK_ACCESS_COLOR_ATTACHMENT_WRITE_BIT
+            | VK_ACCESS_DEPTH_STENCIL_ATTACHMENT_WRITE_BIT
+            | VK_ACCESS_TRANSFER_WRITE_BIT
+            | VK_ACCESS_HOST_WRITE_BIT
+            | VK_ACCESS_MEMORY_WRITE_BIT
+            | VK_ACCESS_TRANSFORM_FEEDBACK_WRITE_BIT_EXT
+            | VK_ACCESS_TRANSFORM_FEEDBACK_COUNTER_WRITE_BIT_EXT
+            | VK_ACCESS_COMMAND_PROCESS_WRITE_BIT_NVX
+            | VK_ACCESS_ACCELERATION_STRUCTURE_WRITE_BIT_NV
+    }
+
+    /// Returns source access and stage of the barrier, which is needed before `next`,
+    /// and the resulting state
+    ///
+    /// The destination of the barrier is the access and stage of the resulting state
+    pub(crate) fn transition(
+        &self,
+        next: &ResourceState,
+    ) -> (
+        Option<(VkAccessFlagBits, VkPipelineStageFlagBits)>,
+        ResourceState,
+    ) {
+        if self.layout == next.layout && !self.is_written() {
+            // read after read, remember every reader for the next write
+            if !next.is_written() {
+                let state = ResourceState {
+                    access: self.access | next.access,
+                    stage: self.stage | next.stage,
+                    ..*self
+                };
+
+                // the last write has to be made visible to new readers as well
+                let covered = (self.access & next.access) == next.access
+                    && (self.stage & next.stage) == next.stage;
+
+                if covered || self.last_write_stage == 0 {
+                    return (None, state);
+                }
+
+                return (Some((self.last_write, self.last_write_stage)), state);
+            }
+
+            // nothing to wait for
+            if self.access == 0 {
+                return (None, *next);
+            }
+        }
+
+        // only writes need to be made available, reads just need an execution dependency
+        let src_access = self.access & Self::write_access();
+
+        let state = if next.is_written() {
+            *next
+        } else {
+            let mut last_write_stage = if src_access == 0 {
+                0u32.into()
+            } else {
+                self.stage
+            };
+
+            // later readers have to wait for the layout transition, which happens
+            // before the stages of `next`
+            if self.layout != next.layout {
+                last_write_stage |= next.stage;
+            }
+
+            ResourceState {
+                last_write: src_access,
+                last_write_stage,
+                ..*next
+            }
+        };
+
+        (Some((src_access, self.stage)), state)
+    }
+}
+
+/// Collects the barriers of several resources and records them with a single
+/// `vkCmdPipelineBarrier`
+///
+/// Resource states are updated while collecting, so barriers have to be recorded in
+/// the order of execution
+pub struct BarrierBatch<'a> {
+    command_buffer: &'a CommandBuffer,
+
+    src_stage: VkPipelineStageFlagBits,
+    dst_stage: VkPipelineStageFlagBits,
+
+    buffer_barriers: Vec<VkBufferMemoryBarrier>,
+    image_barriers: Vec<VkImageMemoryBarrier>,
+}
+
+impl<'a> BarrierBatch<'a> {
+    pub(crate) fn new(command_buffer: &'a CommandBuffer) -> Self {
+        BarrierBatch {
+            command_buffer,
+
+            src_stage: 0u32.into(),
+            dst_stage: 0u32.into(),
+
+            buffer_barriers: Vec::new(),
+            image_barriers: Vec::new(),
+        }
+    }
+
+    pub fn image(self, image: &Image, usage: ResourceUsage) -> VerboseResult<Self> {
+        self.image_range(image, image.full_resource_range(), usage)
+    }
+
+    pub fn image_range(
+        self,
+        image: &Image,
+        subresource_range: VkImageSubresourceRange,
+        usage: ResourceUsage,
+    ) -> VerboseResult<Self> {
+        self.image_state(image, subresource_range, usage.image_state()?)
+    }
+
+    pub fn buffer<T>(
+        mut self,
+        buffer: &Arc<Buffer<T>>,
+        usage: ResourceUsage,
+    ) -> VerboseResult<Self> {
+        let next = usage.buffer_state()?;
+
+        if let (Some((src_access, src_stage)), state) = buffer.transition(&next)? {
+            self.src_stage |= src_stage;
+            self.dst_stage |= state.stage;
+
+            self.buffer_barriers.push(VkBufferMemoryBarrier::new(
+                src_access,
+                state.access,
+                VK_QUEUE_FAMILY_IGNORED,
+                VK_QUEUE_FAMILY_IGNORED,
+                buffer.vk_handle(),
+                0,
+                buffer.byte_size(),
+            ));
+        }
+
+        Ok(self)
+    }
+
+    pub(crate) fn image_state(
+        mut self,
+        image: &Image,
+        subresource_range: VkImageSubresourceRange,
+        next: ResourceState,
+    ) -> VerboseResult<Self> {
+        for barrier in image.transition(&subresource_range, &next)? {
+            self.src_stage |= barrier.src_stage;
+            self.dst_stage |= barrier.dst_stage;
+
+            self.image_barriers.push(VkImageMemoryBarrier::new(
+                barrier.src_access,
+                barrier.dst_access,
+                barrier.old_layout,
+                next.layout,
+                VK_QUEUE_FAMILY_IGNORED,
+                VK_QUEUE_FAMILY_IGNORED,
+                image.vk_handle(),
+                barrier.range,
+            ));
+        }
+
+        Ok(self)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer_barriers.is_empty() && self.image_barriers.is_empty()
+    }
+
+    /// Records all collected barriers, nothing is recorded if none are needed
+    pub fn record(self) {
+        if self.is_empty() {
+            return;
+        }
+
+        self.command_buffer.pipeline_barrier(
+            self.src_stage,
+            self.dst_stage,
+            0,
+            &[],
+            &self.buffer_barriers,
+            &self.image_barriers,
+        );
+    }
+}
+
+/// Subresources with the same previous and next state, which can share a barrier
+struct SubresourceRun {
+    level: u32,
+    level_count: u32,
+    layer: u32,
+    layer_count: u32,
+
+    src_access: VkAccessFlagBits,
+    src_stage: VkPipelineStageFlagBits,
+    old_layout: VkImageLayout,
+
+    dst_access: VkAccessFlagBits,
+    dst_stage: VkPipelineStageFlagBits,
+}
+
+impl SubresourceRun {
+    fn same_barrier(&self, other: &SubresourceRun) -> bool {
+        self.src_access == other.src_access
+            && self.src_stage == other.src_stage
+            && self.old_layout == other.old_layout
+            && self.dst_access == other.dst_access
+            && self.dst_stage == other.dst_stage
+    }
+}
+
+pub(crate) struct SubresourceBarrier {
+    pub src_access: VkAccessFlagBits,
+    pub src_stage: VkPipelineStageFlagBits,
+    pub old_layout: VkImageLayout,
+    pub dst_access: VkAccessFlagBits,
+    pub dst_stage: VkPipelineStageFlagBits,
+    pub range: VkImageSubresourceRange,
+}
+
+/// Updates the states of all subresources in `range` and returns the barriers, which are
+/// needed before `next`, with neighbouring subresources combined
+pub(crate) fn transition_subresources(
+    states: &mut [ResourceState],
+    levels: u32,
+    layers: u32,
+    range: &VkImageSubresourceRange,
+    next: &ResourceState,
+) -> VerboseResult<Vec<SubresourceBarrier>> {
+    if range.baseMipLevel + range.levelCount > levels
+        || range.baseArrayLayer + range.layerCount > layers
+    {
+        create_error!(format!(
+            "subresource range ({:?}) exceeds image ({} levels, {} layers)",
+            range, levels, layers
+        ));
+    }
+
+    let mut runs: Vec<SubresourceRun> = Vec::new();
+
+    for level in range.baseMipLevel..(range.baseMipLevel + range.levelCount) {
+        let mut level_runs: Vec<SubresourceRun> = Vec::new();
+
+        for layer in range.baseArrayLayer..(range.baseArrayLayer + range.layerCount) {
+            let state = &mut states[(layer * levels + level) as usize];
+            let old_layout = state.layout;
+
+            let (barrier, new_state) = state.transition(next);
+            *state = new_state;
+
+            if let Some((src_access, src_stage)) = barrier {
+                let run = SubresourceRun {
+                    level,
+                    level_count: 1,
+                    layer,
+                    layer_count: 1,
+
+                    src_access,
+                    src_stage,
+                    old_layout,
+
+                    dst_access: new_state.access,
+                    dst_stage: new_state.stage,
+                };
+
+                match level_runs.last_mut() {
+                    Some(last)
+                        if last.same_barrier(&run) && last.layer + last.layer_count == layer =>
+                    {
+                        last.layer_count += 1;
+                    }
+                    _ => level_runs.push(run),
+                }
+            }
+        }
+
+        // combine with the previous level if it covers the same layers
+        for run in level_runs {
+            match runs.iter_mut().find(|last| {
+                last.same_barrier(&run)
+                    && last.level + last.level_count == level
+                    && last.layer == run.layer
+                    && last.layer_count == run.layer_count
+            }) {
+                Some(last) => last.level_count += 1,
+                None => runs.push(run),
+            }
+        }
+    }
+
+    Ok(runs
+        .into_iter()
+        .map(|run| SubresourceBarrier {
+            src_access: run.src_access,
+            src_stage: run.src_stage,
+            old_layout: run.old_layout,
+            dst_access: run.dst_access,
+            dst_stage: run.dst_stage,
+            range: VkImageSubresourceRange {
+                aspectMask: range.aspectMask,
+                baseMipLevel: run.level,
+                levelCount: run.level_count,
+                baseArrayLayer: run.layer,
+                layerCount: run.layer_count,
+            },
+        })
+        .collect())
+}
+
+#[test]
+fn resource_state_transitions() {
+    let vertex_shader: VkPipelineStageFlagBits = VK_PIPELINE_STAGE_VERTEX_SHADER_BIT.into();
+
+    let write = ResourceUsage::TransferWrite.buffer_state().unwrap();
+    let vertices = ResourceUsage::VertexBuffer.buffer_state().unwrap();
+    let uniforms = ResourceUsage::UniformBuffer(vertex_shader)
+        .buffer_state()
+        .unwrap();
+
+    // nothing accessed the buffer yet
+    let state = ResourceState::from_access(0u32, VK_PIPELINE_STAGE_TOP_OF_PIPE_BIT);
+    let (barrier, state) = state.transition(&write);
+    assert_eq!(barrier, None);
+    assert_eq!(state, write);
+
+    // read after write makes the write available
+    let (barrier, state) = state.transition(&vertices);
+    assert_eq!(
+        barrier,
+        Some((
+            VK_ACCESS_TRANSFER_WRITE_BIT.into(),
+            VK_PIPELINE_STAGE_TRANSFER_BIT.into()
+        ))
+    );
+    assert_eq!(state.access, vertices.access);
+    assert_eq!(state.last_write, VK_ACCESS_TRANSFER_WRITE_BIT);
+
+    // the same read again is already covered
+    let (barrier, state) = state.transition(&vertices);
+    assert_eq!(barrier, None);
+
+    // a different reader needs the write to be made visible as well
+    let (barrier, state) = state.transition(&uniforms);
+    assert_eq!(
+        barrier,
+        Some((
+            VK_ACCESS_TRANSFER_WRITE_BIT.into(),
+            VK_PIPELINE_STAGE_TRANSFER_BIT.into()
+        ))
+    );
+    assert_eq!(state.access, vertices.access | uniforms.access);
+    assert_eq!(state.stage, vertices.stage | uniforms.stage);
+
+    // all readers are remembered
+    let (barrier, state) = state.transition(&uniforms);
+    assert_eq!(barrier, None);
+
+    // write after read only waits for the readers
+    let (barrier, state) = state.transition(&write);
+    assert_eq!(
+        barrier,
+        Some((0u32.into(), vertices.stage | uniforms.stage))
+    );
+    assert_eq!(state, write);
+
+    // readers of a never written resource don't need barriers
+    let state = ResourceState::from_access(0u32, VK_PIPELINE_STAGE_TOP_OF_PIPE_BIT);
+    let (_, state) = state.transition(&vertices);
+    let (barrier, _) = state.transition(&uniforms);
+    assert_eq!(barrier, None);
+
+    assert!(ResourceUsage::Present.buffer_state().is_err());
+    assert!(ResourceUsage::IndexBuffer.image_state().is_err());
+}
+
+#[test]
+fn subresource_barriers_are_combined() {
+    let levels = 2;
+    let layers = 3;
+
+    let mut states = vec![ResourceState::from_layout(VK_IMAGE_LAYOUT_UNDEFINED); 6];
+
+    let range = |level, level_count, layer, layer_count| VkImageSubresourceRange {
+        aspectMask: VK_IMAGE_ASPECT_COLOR_BIT.into(),
+        baseMipLevel: level,
+        levelCount: level_count,
+        baseArrayLayer: layer,
+        layerCount: layer_count,
+    };
+
+    let read = ResourceUsage::ShaderRead(VK_PIPELINE_STAGE_FRAGMENT_SHADER_BIT.into())
+        .image_state()
+        .unwrap();
+    let write = ResourceUsage::TransferWrite.image_state().unwrap();
+
+    // all subresources share the same source, so a single barrier covers the image
+    let barriers =
+        transition_subresources(&mut states, levels, layers, &range(0, 2, 0, 3), &read).unwrap();
+
+    assert_eq!(barriers.len(), 1);
+    assert_eq!(barriers[0].old_layout, VK_IMAGE_LAYOUT_UNDEFINED);
+    assert_eq!(barriers[0].range.levelCount, 2);
+    assert_eq!(barriers[0].range.layerCount, 3);
+
+    let barriers =
+        transition_subresources(&mut states, levels, layers, &range(1, 1, 1, 1), &write).unwrap();
+
+    assert_eq!(barriers.len(), 1);
+    assert_eq!(
+        barriers[0].old_layout,
+        VK_IMAGE_LAYOUT_SHADER_READ_ONLY_OPTIMAL
+    );
+    assert_eq!(states[(levels + 1) as usize].layout, write.layout);
+
+    // only the written subresource needs a barrier to be read again
+    let barriers =
+        transition_subresources(&mut states, levels, layers, &range(0, 2, 0, 3), &read).unwrap();
+
+    assert_eq!(barriers.len(), 1);
+    assert_eq!(barriers[0].old_layout, VK_IMAGE_LAYOUT_TRANSFER_DST_OPTIMAL);
+    assert_eq!(barriers[0].src_access, VK_ACCESS_TRANSFER_WRITE_BIT);
+    assert_eq!(
+        (
+            barriers[0].range.baseMipLevel,
+            barriers[0].range.baseArrayLayer
+        ),
+        (1, 1)
+    );
+
+    // runs of neighbouring levels, which cover the same layers, are combined
+    let barriers =
+        transition_subresources(&mut states, levels, layers, &range(0, 2, 1, 2), &write).unwrap();
+
+    assert_eq!(barriers.len(), 1);
+    assert_eq!(barriers[0].range.levelCount, 2);
+    assert_eq!(barriers[0].range.layerCount, 2);
+
+    assert!(
+        transition_subresources(&mut states, levels, layers, &range(1, 2, 0, 1), &read).is_err()
+    );
+}
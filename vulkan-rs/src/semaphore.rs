@@ -4,11 +4,13 @@ use crate::impl_vk_handle;
 use crate::prelude::*;
 
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub struct Semaphore {
     device: Arc<Device>,
     semaphore: VkSemaphore,
+    timeline: bool,
 }
 
 impl Semaphore {
@@ -17,7 +19,93 @@ impl Semaphore {
 
         let semaphore = device.create_semaphore(&semaphore_ci)?;
 
-        Ok(Arc::new(Semaphore { device, semaphore }))
+        Ok(Arc::new(Semaphore {
+            device,
+            semaphore,
+            timeline: false,
+        }))
+    }
+
+    /// Creates a semaphore with a monotonically increasing counter (VK_KHR_timeline_semaphore)
+    pub fn timeline(device: Arc<Device>, initial_value: u64) -> VerboseResult<Arc<Semaphore>> {
+        if !device.enabled_extensions().timeline_semaphore {
+            create_error!("timeline semaphore extension is not enabled");
+        }
+
+        let mut semaphore_ci = VkSemaphoreCreateInfo::new(VK_SEMAPHORE_CREATE_NULL_BIT);
+        let semaphore_type_ci =
+            VkSemaphoreTypeCreateInfoKHR::new(VK_SEMAPHORE_TYPE_TIMELINE_KHR, initial_value);
+
+        semaphore_ci.chain(&semaphore_type_ci);
+
+        let semaphore = device.create_semaphore(&semaphore_ci)?;
+
+        Ok(Arc::new(Semaphore {
+            device,
+            semaphore,
+            timeline: true,
+        }))
+    }
+
+    pub fn is_timeline(&self) -> bool {
+        self.timeline
+    }
+
+    /// Current counter value of a timeline semaphore
+    pub fn value(&self) -> VerboseResult<u64> {
+        self.check_timeline()?;
+
+        self.device.semaphore_counter_value(self.semaphore)
+    }
+
+    /// Sets the counter of a timeline semaphore from the host
+    pub fn signal(&self, value: u64) -> VerboseResult<()> {
+        self.check_timeline()?;
+
+        self.device
+            .signal_semaphore(&VkSemaphoreSignalInfoKHR::new(self.semaphore, value))
+    }
+
+    /// Blocks until the counter reaches `value`, returns false if the timeout expired before
+    pub fn wait(&self, value: u64, timeout: Duration) -> VerboseResult<bool> {
+        Self::wait_for_semaphores(&self.device, &[(self, value)], true, timeout)
+    }
+
+    /// Waits for all (or any, if `wait_all` is false) timeline semaphores to reach their values
+    pub fn wait_for_semaphores(
+        device: &Arc<Device>,
+        semaphores: &[(&Semaphore, u64)],
+        wait_all: bool,
+        timeout: Duration,
+    ) -> VerboseResult<bool> {
+        let mut vk_semaphores = Vec::with_capacity(semaphores.len());
+        let mut values = Vec::with_capacity(semaphores.len());
+
+        for (semaphore, value) in semaphores {
+            semaphore.check_timeline()?;
+
+            vk_semaphores.push(semaphore.semaphore);
+            values.push(*value);
+        }
+
+        let flags = if wait_all {
+            VK_SEMAPHORE_WAIT_NULL_BIT_KHR
+        } else {
+            VK_SEMAPHORE_WAIT_ANY_BIT_KHR
+        };
+
+        device.wait_semaphores(
+            &VkSemaphoreWaitInfoKHR::new(flags, &vk_semaphores, &values),
+            timeout.as_nanos() as u64,
+        )
+    }
+
+    fn check_timeline(&self) -> VerboseResult<()> {
+        if !self.timeline {
+            create_error!("operation is only supported by timeline semaphores");
+        }
+
+        Ok(())
     }
 }
 
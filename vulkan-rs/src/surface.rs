@@ -28,6 +28,47 @@ impl Surface {
         )
     }
 
+    pub fn formats(&self, device: &Arc<Device>) -> VerboseResult<Vec<VkSurfaceFormatKHR>> {
+        self.instance
+            .physical_device_surface_formats(device.physical_device().vk_handle(), self.surface)
+    }
+
+    /// Takes the first color space of `color_spaces`, that is supported by the surface,
+    /// `prefered_format` is used if it is available in that color space
+    ///
+    /// Falls back to `format_colorspace`, if none of the color spaces is supported
+    pub fn format_colorspace_with(
+        &self,
+        device: &Arc<Device>,
+        prefered_format: VkFormat,
+        color_spaces: &[VkColorSpaceKHR],
+    ) -> VerboseResult<(VkFormat, VkColorSpaceKHR)> {
+        let surface_formats = self.formats(device)?;
+
+        for color_space in color_spaces {
+            let mut candidates = surface_formats
+                .iter()
+                .filter(|surface_format| surface_format.colorSpace == *color_space);
+
+            let first = match candidates.next() {
+                Some(surface_format) => surface_format,
+                None => continue,
+            };
+
+            let surface_format = if first.format == prefered_format {
+                first
+            } else {
+                candidates
+                    .find(|surface_format| surface_format.format == prefered_format)
+                    .unwrap_or(first)
+            };
+
+            return Ok((surface_format.format, surface_format.colorSpace));
+        }
+
+        self.format_colorspace(device, prefered_format)
+    }
+
     pub fn format_colorspace(
         &self,
         device: &Arc<Device>,
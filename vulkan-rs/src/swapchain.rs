@@ -8,6 +8,82 @@ use std::sync::{
     Arc, Mutex,
 };
 
+/// Mastering display and content light levels, used by the display to tone map HDR content
+///
+/// Chromaticities are CIE 1931 xy coordinates, luminance values are in nits
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HdrMetadata {
+    pub display_primary_red: [f32; 2],
+    pub display_primary_green: [f32; 2],
+    pub display_primary_blue: [f32; 2],
+    pub white_point: [f32; 2],
+
+    pub max_luminance: f32,
+    pub min_luminance: f32,
+
+    pub max_content_light_level: f32,
+    pub max_frame_average_light_level: f32,
+}
+
+impl HdrMetadata {
+    /// Rec. 2020 primaries with a D65 white point, as used by HDR10
+    pub fn bt2020(
+        max_luminance: f32,
+        min_luminance: f32,
+        max_content_light_level: f32,
+        max_frame_average_light_level: f32,
+    ) -> Self {
+        HdrMetadata {
+            display_primary_red: [0.708, 0.292],
+            display_primary_green: [0.170, 0.797],
+            display_primary_blue: [0.131, 0.046],
+            white_point: [0.3127, 0.3290],
+
+            max_luminance,
+            min_luminance,
+
+            max_content_light_level,
+            max_frame_average_light_level,
+        }
+    }
+
+    /// Display P3 primaries with a D65 white point
+    pub fn display_p3(
+        max_luminance: f32,
+        min_luminance: f32,
+        max_content_light_level: f32,
+        max_frame_average_light_level: f32,
+    ) -> Self {
+        HdrMetadata {
+            display_primary_red: [0.680, 0.320],
+            display_primary_green: [0.265, 0.690],
+            display_primary_blue: [0.150, 0.060],
+            white_point: [0.3127, 0.3290],
+
+            max_luminance,
+            min_luminance,
+
+            max_content_light_level,
+            max_frame_average_light_level,
+        }
+    }
+
+    fn vk_metadata(&self) -> VkHdrMetadataEXT {
+        let xy = |c: [f32; 2]| VkXYColorEXT::new(c[0], c[1]);
+
+        VkHdrMetadataEXT::new(
+            xy(self.display_primary_red),
+            xy(self.display_primary_green),
+            xy(self.display_primary_blue),
+            xy(self.white_point),
+            self.max_luminance,
+            self.min_luminance,
+            self.max_content_light_level,
+            self.max_frame_average_light_level,
+        )
+    }
+}
+
 #[derive(Debug)]
 pub struct Swapchain {
     width: AtomicU32,
@@ -18,16 +94,34 @@ pub struct Swapchain {
 
     create_info: Mutex<VkSwapchainCreateInfoKHR>,
     swapchain: Mutex<VkSwapchainKHR>,
+
+    // has to be set again for every new swapchain
+    hdr_metadata: Mutex<Option<HdrMetadata>>,
 }
 
 impl Swapchain {
+    /// Present mode preferences, that resemble a simple vsync switch
+    pub fn vsync_present_modes(vsync: bool) -> Vec<VkPresentModeKHR> {
+        if vsync {
+            vec![VK_PRESENT_MODE_FIFO_KHR]
+        } else {
+            vec![VK_PRESENT_MODE_MAILBOX_KHR, VK_PRESENT_MODE_IMMEDIATE_KHR]
+        }
+    }
+
+    /// `present_modes` is a list of preferences, the first supported one is used.
+    /// Falls back to FIFO, which is always supported.
+    ///
+    /// `color_spaces` works the same way, see `Surface::format_colorspace_with`.
+    /// Color spaces other than sRGB require the `swapchain_colorspace` instance extension.
     pub fn new(
         device: Arc<Device>,
         surface: &Arc<Surface>,
-        vsync: bool,
+        present_modes: &[VkPresentModeKHR],
         image_count: u32,
         image_usage: impl Into<VkImageUsageFlagBits>,
         prefered_format: VkFormat,
+        color_spaces: &[VkColorSpaceKHR],
         array_layers: u32,
     ) -> VerboseResult<Arc<Swapchain>> {
         let surface_caps = surface.capabilities(&device)?;
@@ -41,27 +135,8 @@ impl Swapchain {
             }
         };
 
-        let mut present_mode = VK_PRESENT_MODE_FIFO_KHR;
-
-        if !vsync {
-            for present_mode_iter in surface.present_modes(&device)? {
-                if present_mode_iter == VK_PRESENT_MODE_MAILBOX_KHR {
-                    present_mode = VK_PRESENT_MODE_MAILBOX_KHR;
-                    break;
-                } else if present_mode_iter == VK_PRESENT_MODE_IMMEDIATE_KHR {
-                    present_mode = VK_PRESENT_MODE_IMMEDIATE_KHR;
-                }
-            }
-        }
-
-        let swapchain_image_count = if surface_caps.maxImageCount < surface_caps.minImageCount {
-            cmp::max(image_count, surface_caps.minImageCount)
-        } else {
-            cmp::max(
-                cmp::min(image_count, surface_caps.maxImageCount),
-                surface_caps.minImageCount,
-            )
-        };
+        let present_mode = Self::select_present_mode(&device, surface, present_modes)?;
+        let swapchain_image_count = Self::clamp_image_count(&surface_caps, image_count);
 
         let pretransform =
             if (surface_caps.supportedTransforms & VK_SURFACE_TRANSFORM_IDENTITY_BIT_KHR) != 0 {
@@ -70,7 +145,8 @@ impl Swapchain {
                 surface_caps.currentTransform
             };
 
-        let (format, colorspace) = surface.format_colorspace(&device, prefered_format)?;
+        let (format, colorspace) =
+            surface.format_colorspace_with(&device, prefered_format, color_spaces)?;
 
         let swapchain_ci = VkSwapchainCreateInfoKHR::new(
             0,
@@ -101,6 +177,8 @@ impl Swapchain {
             create_info: Mutex::new(swapchain_ci),
 
             swapchain: Mutex::new(swapchain),
+
+            hdr_metadata: Mutex::new(None),
         }))
     }
 
@@ -132,9 +210,32 @@ impl Swapchain {
         self.width.store(extent.width, SeqCst);
         self.height.store(extent.height, SeqCst);
 
+        if let Some(metadata) = self.hdr_metadata.lock()?.as_ref() {
+            self.device
+                .set_hdr_metadata(&[swapchain], &[metadata.vk_metadata()]);
+        }
+
         Ok(())
     }
 
+    /// Changes the present mode and the image count, both take effect with the next `recreate`
+    ///
+    /// Returns the selected present mode
+    pub fn reconfigure(
+        &self,
+        present_modes: &[VkPresentModeKHR],
+        image_count: u32,
+    ) -> VerboseResult<VkPresentModeKHR> {
+        let surface_caps = self.surface.capabilities(&self.device)?;
+        let present_mode = Self::select_present_mode(&self.device, &self.surface, present_modes)?;
+
+        let mut swapchain_ci = self.create_info.lock()?;
+        swapchain_ci.presentMode = present_mode;
+        swapchain_ci.minImageCount = Self::clamp_image_count(&surface_caps, image_count);
+
+        Ok(present_mode)
+    }
+
     pub fn acquire_next_image(
         &self,
         time_out: u64,
@@ -171,6 +272,65 @@ impl Swapchain {
         Ok(self.create_info.lock()?.imageFormat)
     }
 
+    pub fn color_space(&self) -> VerboseResult<VkColorSpaceKHR> {
+        Ok(self.create_info.lock()?.imageColorSpace)
+    }
+
+    pub fn hdr_metadata(&self) -> VerboseResult<Option<HdrMetadata>> {
+        Ok(*self.hdr_metadata.lock()?)
+    }
+
+    /// Sets the mastering metadata of the swapchain, it is kept across `recreate`
+    ///
+    /// Requires the `hdr_metadata` device extension
+    pub fn set_hdr_metadata(&self, metadata: HdrMetadata) -> VerboseResult<()> {
+        if !self.device.enabled_extensions().hdr_metadata {
+            create_error!("hdr_metadata extension is not enabled");
+        }
+
+        self.device
+            .set_hdr_metadata(&[self.vk_handle()], &[metadata.vk_metadata()]);
+
+        *self.hdr_metadata.lock()? = Some(metadata);
+
+        Ok(())
+    }
+
+    pub fn present_mode(&self) -> VerboseResult<VkPresentModeKHR> {
+        Ok(self.create_info.lock()?.presentMode)
+    }
+
+    /// Requested minimal image count, the driver is allowed to create more images
+    pub fn image_count(&self) -> VerboseResult<u32> {
+        Ok(self.create_info.lock()?.minImageCount)
+    }
+
+    fn select_present_mode(
+        device: &Arc<Device>,
+        surface: &Arc<Surface>,
+        present_modes: &[VkPresentModeKHR],
+    ) -> VerboseResult<VkPresentModeKHR> {
+        let supported_modes = surface.present_modes(device)?;
+
+        Ok(present_modes
+            .iter()
+            .find(|present_mode| supported_modes.contains(present_mode))
+            .cloned()
+            .unwrap_or(VK_PRESENT_MODE_FIFO_KHR))
+    }
+
+    fn clamp_image_count(surface_caps: &VkSurfaceCapabilitiesKHR, image_count: u32) -> u32 {
+        // max image count of 0 means there is no limit
+        if surface_caps.maxImageCount < surface_caps.minImageCount {
+            cmp::max(image_count, surface_caps.minImageCount)
+        } else {
+            cmp::max(
+                cmp::min(image_count, surface_caps.maxImageCount),
+                surface_caps.minImageCount,
+            )
+        }
+    }
+
     #[inline]
     fn destroy(&self) {
         self.device
@@ -100,6 +100,8 @@ pub mod sampleraddressmode;
 pub mod samplercreateflags;
 pub mod samplermipmapmode;
 pub mod semaphorecreateflags;
+pub mod semaphoretypekhr;
+pub mod semaphorewaitflagskhr;
 pub mod shadermodulecreateflags;
 pub mod shaderstageflags;
 pub mod sharingmode;
@@ -100,6 +100,8 @@ pub use super::sampleraddressmode::*;
 pub use super::samplercreateflags::*;
 pub use super::samplermipmapmode::*;
 pub use super::semaphorecreateflags::*;
+pub use super::semaphoretypekhr::*;
+pub use super::semaphorewaitflagskhr::*;
 pub use super::shadermodulecreateflags::*;
 pub use super::shaderstageflags::*;
 pub use super::sharingmode::*;
@@ -0,0 +1,15 @@
+pub use VkSemaphoreWaitFlagsKHR::*;
+
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VkSemaphoreWaitFlagsKHR {
+    VK_SEMAPHORE_WAIT_NULL_BIT_KHR = 0,
+    VK_SEMAPHORE_WAIT_ANY_BIT_KHR = 0x0000_0001,
+}
+
+use crate::SetupVkFlags;
+
+#[repr(C)]
+#[derive(Clone, Copy, Eq, PartialEq, Hash)]
+pub struct VkSemaphoreWaitFlagBitsKHR(u32);
+SetupVkFlags!(VkSemaphoreWaitFlagsKHR, VkSemaphoreWaitFlagBitsKHR);
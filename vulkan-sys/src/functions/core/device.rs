@@ -404,7 +404,7 @@ load_function_ptrs!(DeviceFunctions, {
         depthBiasSlopeFactor: f32
     ) -> (),
 
-    vkCmdSetBlendConstants(commandBuffer: VkCommandBuffer, blendConstants: [f32; 4]) -> (),
+    vkCmdSetBlendConstants(commandBuffer: VkCommandBuffer, blendConstants: *const f32) -> (),
 
     vkCmdSetDepthBounds(
         commandBuffer: VkCommandBuffer,
@@ -414,19 +414,19 @@ load_function_ptrs!(DeviceFunctions, {
 
     vkCmdSetStencilCompareMask(
         commandBuffer: VkCommandBuffer,
-        faceMask: VkStencilFaceFlags,
+        faceMask: VkStencilFaceFlagBits,
         compareMask: u32
     ) -> (),
 
     vkCmdSetStencilWriteMask(
         commandBuffer: VkCommandBuffer,
-        faceMask: VkStencilFaceFlags,
+        faceMask: VkStencilFaceFlagBits,
         writeMask: u32
     ) -> (),
 
     vkCmdSetStencilReference(
         commandBuffer: VkCommandBuffer,
-        faceMask: VkStencilFaceFlags,
+        faceMask: VkStencilFaceFlagBits,
         reference: u32
     ) -> (),
 
@@ -599,21 +599,21 @@ load_function_ptrs!(DeviceFunctions, {
     vkCmdSetEvent(
         commandBuffer: VkCommandBuffer,
         event: VkEvent,
-        stageMask: VkPipelineStageFlags
+        stageMask: VkPipelineStageFlagBits
     ) -> (),
 
     vkCmdResetEvent(
         commandBuffer: VkCommandBuffer,
         event: VkEvent,
-        stageMask: VkPipelineStageFlags
+        stageMask: VkPipelineStageFlagBits
     ) -> (),
 
     vkCmdWaitEvents(
         commandBuffer: VkCommandBuffer,
         eventCount: u32,
         pEvents: *const VkEvent,
-        srcStageMask: VkPipelineStageFlags,
-        dstStageMask: VkPipelineStageFlags,
+        srcStageMask: VkPipelineStageFlagBits,
+        dstStageMask: VkPipelineStageFlagBits,
         memoryBarrierCount: u32,
         pMemoryBarriers: *const VkMemoryBarrier,
         bufferMemoryBarrierCount: u32,
@@ -666,7 +666,7 @@ load_function_ptrs!(DeviceFunctions, {
         dstBuffer: VkBuffer,
         dstOffset: VkDeviceSize,
         stride: VkDeviceSize,
-        flags: VkQueryResultFlags
+        flags: VkQueryResultFlagBits
     ) -> (),
 
     vkCmdPushConstants(
@@ -0,0 +1,13 @@
+use crate::load_function_ptrs;
+use crate::prelude::*;
+
+use std::os::raw::c_void;
+
+load_function_ptrs!(HdrMetadataFunctions, {
+    vkSetHdrMetadataEXT(
+        device: VkDevice,
+        swapchainCount: u32,
+        pSwapchains: *const VkSwapchainKHR,
+        pMetadata: *const VkHdrMetadataEXT
+    ) -> (),
+});
@@ -1,4 +1,5 @@
 pub mod debug_report_callback;
 pub mod debug_utils_messenger;
+pub mod hdr_metadata;
 
 pub mod prelude;
@@ -1,2 +1,3 @@
 pub use super::debug_report_callback::*;
 pub use super::debug_utils_messenger::*;
+pub use super::hdr_metadata::*;
@@ -0,0 +1,18 @@
+use crate::load_function_ptrs;
+use crate::prelude::*;
+
+use std::os::raw::c_void;
+
+load_function_ptrs!(MemoryRequirements2Functions, {
+    vkGetBufferMemoryRequirements2KHR(
+        device: VkDevice,
+        pInfo: *const VkBufferMemoryRequirementsInfo2KHR,
+        pMemoryRequirements: *mut VkMemoryRequirements2KHR
+    ) -> (),
+
+    vkGetImageMemoryRequirements2KHR(
+        device: VkDevice,
+        pInfo: *const VkImageMemoryRequirementsInfo2KHR,
+        pMemoryRequirements: *mut VkMemoryRequirements2KHR
+    ) -> (),
+});
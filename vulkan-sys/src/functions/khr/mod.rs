@@ -1,5 +1,7 @@
 pub mod device_wsi;
 pub mod instance_wsi;
+pub mod memory_requirements2;
 pub mod physical_device_properties2;
+pub mod timeline_semaphore;
 
 pub mod prelude;
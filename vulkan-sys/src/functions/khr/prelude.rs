@@ -1,3 +1,5 @@
 pub use super::physical_device_properties2::*;
 pub use super::instance_wsi::*;
-pub use super::device_wsi::*;
\ No newline at end of file
+pub use super::device_wsi::*;
+pub use super::memory_requirements2::*;
+pub use super::timeline_semaphore::*;
\ No newline at end of file
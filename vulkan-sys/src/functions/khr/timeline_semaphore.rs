@@ -0,0 +1,23 @@
+use crate::load_function_ptrs;
+use crate::prelude::*;
+
+use std::os::raw::c_void;
+
+load_function_ptrs!(TimelineSemaphoreFunctions, {
+    vkGetSemaphoreCounterValueKHR(
+        device: VkDevice,
+        semaphore: VkSemaphore,
+        pValue: *mut u64
+    ) -> VkResult,
+
+    vkWaitSemaphoresKHR(
+        device: VkDevice,
+        pWaitInfo: *const VkSemaphoreWaitInfoKHR,
+        timeout: u64
+    ) -> VkResult,
+
+    vkSignalSemaphoreKHR(
+        device: VkDevice,
+        pSignalInfo: *const VkSemaphoreSignalInfoKHR
+    ) -> VkResult,
+});
@@ -51,3 +51,8 @@ impl_pnext!(
     VkDeviceCreateInfo<'_>,
     VkPhysicalDeviceDescriptorIndexingFeaturesEXT
 );
+
+impl_pnext!(
+    VkDeviceCreateInfo<'_>,
+    VkPhysicalDeviceTimelineSemaphoreFeaturesKHR
+);
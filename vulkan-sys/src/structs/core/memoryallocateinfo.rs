@@ -1,3 +1,4 @@
+use crate::impl_pnext;
 use crate::prelude::*;
 
 use std::os::raw::c_void;
@@ -22,3 +23,5 @@ impl VkMemoryAllocateInfo {
         }
     }
 }
+
+impl_pnext!(VkMemoryAllocateInfo, VkMemoryDedicatedAllocateInfoKHR);
@@ -1,3 +1,4 @@
+use crate::impl_pnext;
 use crate::prelude::*;
 
 use std::os::raw::c_void;
@@ -23,3 +24,5 @@ impl VkSemaphoreCreateInfo {
         }
     }
 }
+
+impl_pnext!(VkSemaphoreCreateInfo, VkSemaphoreTypeCreateInfoKHR);
@@ -1,3 +1,4 @@
+use crate::impl_pnext;
 use crate::prelude::*;
 
 use std::os::raw::c_void;
@@ -37,3 +38,5 @@ impl VkSubmitInfo {
         }
     }
 }
+
+impl_pnext!(VkSubmitInfo, VkTimelineSemaphoreSubmitInfoKHR);
@@ -0,0 +1,45 @@
+use crate::prelude::*;
+
+use std::os::raw::c_void;
+use std::ptr;
+
+#[repr(C)]
+#[derive(Debug, Clone)]
+pub struct VkHdrMetadataEXT {
+    pub sType: VkStructureType,
+    pub pNext: *const c_void,
+    pub displayPrimaryRed: VkXYColorEXT,
+    pub displayPrimaryGreen: VkXYColorEXT,
+    pub displayPrimaryBlue: VkXYColorEXT,
+    pub whitePoint: VkXYColorEXT,
+    pub maxLuminance: f32,
+    pub minLuminance: f32,
+    pub maxContentLightLevel: f32,
+    pub maxFrameAverageLightLevel: f32,
+}
+
+impl VkHdrMetadataEXT {
+    pub fn new(
+        display_primary_red: VkXYColorEXT,
+        display_primary_green: VkXYColorEXT,
+        display_primary_blue: VkXYColorEXT,
+        white_point: VkXYColorEXT,
+        max_luminance: f32,
+        min_luminance: f32,
+        max_content_light_level: f32,
+        max_frame_average_light_level: f32,
+    ) -> Self {
+        VkHdrMetadataEXT {
+            sType: VK_STRUCTURE_TYPE_HDR_METADATA_EXT,
+            pNext: ptr::null(),
+            displayPrimaryRed: display_primary_red,
+            displayPrimaryGreen: display_primary_green,
+            displayPrimaryBlue: display_primary_blue,
+            whitePoint: white_point,
+            maxLuminance: max_luminance,
+            minLuminance: min_luminance,
+            maxContentLightLevel: max_content_light_level,
+            maxFrameAverageLightLevel: max_frame_average_light_level,
+        }
+    }
+}
@@ -1,8 +1,10 @@
 pub mod descriptorsetlayoutbindingflagscreateinfoext;
 pub mod descriptorsetvariabledescriptorcountallocationinfoext;
 pub mod descriptorsetvariabledescriptorcountlayoutsupportext;
+pub mod hdrmetadataext;
 pub mod physicaldevicedescriptorindexingfeaturesext;
 pub mod physicaldevicedescriptorindexingpropertiesext;
 pub mod physicaldevicememorybudgetpropertiesext;
+pub mod xycolorext;
 
 pub mod prelude;
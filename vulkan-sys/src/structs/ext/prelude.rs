@@ -1,6 +1,8 @@
 pub use super::descriptorsetlayoutbindingflagscreateinfoext::*;
 pub use super::descriptorsetvariabledescriptorcountallocationinfoext::*;
 pub use super::descriptorsetvariabledescriptorcountlayoutsupportext::*;
+pub use super::hdrmetadataext::*;
 pub use super::physicaldevicedescriptorindexingfeaturesext::*;
 pub use super::physicaldevicedescriptorindexingpropertiesext::*;
 pub use super::physicaldevicememorybudgetpropertiesext::*;
+pub use super::xycolorext::*;
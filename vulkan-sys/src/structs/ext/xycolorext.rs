@@ -0,0 +1,12 @@
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct VkXYColorEXT {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl VkXYColorEXT {
+    pub fn new(x: f32, y: f32) -> Self {
+        VkXYColorEXT { x, y }
+    }
+}
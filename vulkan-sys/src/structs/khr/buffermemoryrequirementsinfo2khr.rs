@@ -0,0 +1,22 @@
+use crate::prelude::*;
+
+use std::os::raw::c_void;
+use std::ptr;
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct VkBufferMemoryRequirementsInfo2KHR {
+    pub sType: VkStructureType,
+    pub pNext: *const c_void,
+    pub buffer: VkBuffer,
+}
+
+impl VkBufferMemoryRequirementsInfo2KHR {
+    pub fn new(buffer: VkBuffer) -> Self {
+        VkBufferMemoryRequirementsInfo2KHR {
+            sType: VK_STRUCTURE_TYPE_BUFFER_MEMORY_REQUIREMENTS_INFO_2_KHR,
+            pNext: ptr::null(),
+            buffer,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use crate::prelude::*;
+
+use std::os::raw::c_void;
+use std::ptr;
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct VkImageMemoryRequirementsInfo2KHR {
+    pub sType: VkStructureType,
+    pub pNext: *const c_void,
+    pub image: VkImage,
+}
+
+impl VkImageMemoryRequirementsInfo2KHR {
+    pub fn new(image: VkImage) -> Self {
+        VkImageMemoryRequirementsInfo2KHR {
+            sType: VK_STRUCTURE_TYPE_IMAGE_MEMORY_REQUIREMENTS_INFO_2_KHR,
+            pNext: ptr::null(),
+            image,
+        }
+    }
+}
@@ -0,0 +1,24 @@
+use crate::prelude::*;
+
+use std::os::raw::c_void;
+use std::ptr;
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct VkMemoryDedicatedAllocateInfoKHR {
+    pub sType: VkStructureType,
+    pub pNext: *const c_void,
+    pub image: VkImage,
+    pub buffer: VkBuffer,
+}
+
+impl VkMemoryDedicatedAllocateInfoKHR {
+    pub fn new(image: VkImage, buffer: VkBuffer) -> Self {
+        VkMemoryDedicatedAllocateInfoKHR {
+            sType: VK_STRUCTURE_TYPE_MEMORY_DEDICATED_ALLOCATE_INFO_KHR,
+            pNext: ptr::null(),
+            image,
+            buffer,
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use crate::prelude::*;
+
+use std::os::raw::c_void;
+use std::ptr;
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct VkMemoryDedicatedRequirementsKHR {
+    pub sType: VkStructureType,
+    pub pNext: *mut c_void,
+    pub prefersDedicatedAllocation: VkBool32,
+    pub requiresDedicatedAllocation: VkBool32,
+}
+
+impl VkMemoryDedicatedRequirementsKHR {
+    pub fn new(
+        prefers_dedicated_allocation: impl Into<VkBool32>,
+        requires_dedicated_allocation: impl Into<VkBool32>,
+    ) -> Self {
+        VkMemoryDedicatedRequirementsKHR {
+            sType: VK_STRUCTURE_TYPE_MEMORY_DEDICATED_REQUIREMENTS_KHR,
+            pNext: ptr::null_mut(),
+            prefersDedicatedAllocation: prefers_dedicated_allocation.into(),
+            requiresDedicatedAllocation: requires_dedicated_allocation.into(),
+        }
+    }
+}
+
+impl Default for VkMemoryDedicatedRequirementsKHR {
+    fn default() -> Self {
+        Self::new(VK_FALSE, VK_FALSE)
+    }
+}
@@ -1,13 +1,40 @@
 use crate::prelude::*;
 
 use std::os::raw::c_void;
+use std::ptr;
 
 #[repr(C)]
 #[derive(Debug)]
 pub struct VkMemoryRequirements2 {
     pub sType: VkStructureType,
-    pub pNext: *const c_void,
+    pub pNext: *mut c_void,
     pub memoryRequirements: VkMemoryRequirements,
 }
 
 pub type VkMemoryRequirements2KHR = VkMemoryRequirements2;
+
+impl VkMemoryRequirements2 {
+    pub fn new(memory_requirements: VkMemoryRequirements) -> Self {
+        VkMemoryRequirements2 {
+            sType: VK_STRUCTURE_TYPE_MEMORY_REQUIREMENTS_2_KHR,
+            pNext: ptr::null_mut(),
+            memoryRequirements: memory_requirements,
+        }
+    }
+}
+
+impl Default for VkMemoryRequirements2 {
+    fn default() -> Self {
+        Self::new(VkMemoryRequirements {
+            size: 0,
+            alignment: 0,
+            memoryTypeBits: 0.into(),
+        })
+    }
+}
+
+impl PNextMut<VkMemoryDedicatedRequirementsKHR> for VkMemoryRequirements2 {
+    fn chain_mut(&mut self, p_next: &mut VkMemoryDedicatedRequirementsKHR) {
+        self.pNext = p_next as *mut VkMemoryDedicatedRequirementsKHR as *mut c_void;
+    }
+}
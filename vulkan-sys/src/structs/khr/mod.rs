@@ -24,5 +24,14 @@ pub mod win32surfacecreateinfokhr;
 pub mod xcbsurfacecreateinfokhr;
 pub mod xlibsurfacecreateinfokhr;
 pub mod memoryrequirements2khr;
+pub mod buffermemoryrequirementsinfo2khr;
+pub mod imagememoryrequirementsinfo2khr;
+pub mod memorydedicatedallocateinfokhr;
+pub mod memorydedicatedrequirementskhr;
+pub mod physicaldevicetimelinesemaphorefeatureskhr;
+pub mod semaphoresignalinfokhr;
+pub mod semaphoretypecreateinfokhr;
+pub mod semaphorewaitinfokhr;
+pub mod timelinesemaphoresubmitinfokhr;
 
 pub mod prelude;
\ No newline at end of file
@@ -37,3 +37,8 @@ impl_pnext!(
     VkPhysicalDeviceFeatures2KHR,
     VkPhysicalDeviceDescriptorIndexingFeaturesEXT
 );
+
+impl_pnext!(
+    VkPhysicalDeviceFeatures2KHR,
+    VkPhysicalDeviceTimelineSemaphoreFeaturesKHR
+);
@@ -0,0 +1,38 @@
+use crate::prelude::*;
+
+use crate::impl_pnext;
+
+use std::os::raw::c_void;
+use std::ptr;
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct VkPhysicalDeviceTimelineSemaphoreFeaturesKHR {
+    pub sType: VkStructureType,
+    pub pNext: *const c_void,
+    pub timelineSemaphore: VkBool32,
+}
+
+impl VkPhysicalDeviceTimelineSemaphoreFeaturesKHR {
+    pub fn new(timeline_semaphore: impl Into<VkBool32>) -> Self {
+        VkPhysicalDeviceTimelineSemaphoreFeaturesKHR {
+            sType: VK_STRUCTURE_TYPE_PHYSICAL_DEVICE_TIMELINE_SEMAPHORE_FEATURES_KHR,
+            pNext: ptr::null(),
+            timelineSemaphore: timeline_semaphore.into(),
+        }
+    }
+}
+
+impl Default for VkPhysicalDeviceTimelineSemaphoreFeaturesKHR {
+    fn default() -> Self {
+        Self::new(VK_FALSE)
+    }
+}
+
+unsafe impl Sync for VkPhysicalDeviceTimelineSemaphoreFeaturesKHR {}
+unsafe impl Send for VkPhysicalDeviceTimelineSemaphoreFeaturesKHR {}
+
+impl_pnext!(
+    VkPhysicalDeviceTimelineSemaphoreFeaturesKHR,
+    VkPhysicalDeviceDescriptorIndexingFeaturesEXT
+);
@@ -1,4 +1,5 @@
 pub use super::androidsurfacecreateinfokhr::*;
+pub use super::buffermemoryrequirementsinfo2khr::*;
 pub use super::descriptorupdatetemplateentrykhr::*;
 pub use super::displaymodecreateinfokhr::*;
 pub use super::displaymodeparameterkhr::*;
@@ -7,6 +8,9 @@ pub use super::displaypresentinfokhr::*;
 pub use super::displaysurfacecreateinfokhr::*;
 pub use super::formatproperties2khr::*;
 pub use super::imageformatproperties2khr::*;
+pub use super::imagememoryrequirementsinfo2khr::*;
+pub use super::memorydedicatedallocateinfokhr::*;
+pub use super::memorydedicatedrequirementskhr::*;
 pub use super::memoryrequirements2khr::*;
 pub use super::physicaldevicefeatures2khr::*;
 pub use super::physicaldeviceimageformatinfo2khr::*;
@@ -14,12 +18,17 @@ pub use super::physicaldevicememoryproperties2khr::*;
 pub use super::physicaldeviceproperties2khr::*;
 pub use super::physicaldevicepushdescriptorpropertieskhr::*;
 pub use super::physicaldevicesparseimageformatinfo2khr::*;
+pub use super::physicaldevicetimelinesemaphorefeatureskhr::*;
 pub use super::presentinfokhr::*;
 pub use super::queuefamilyproperties2khr::*;
+pub use super::semaphoresignalinfokhr::*;
+pub use super::semaphoretypecreateinfokhr::*;
+pub use super::semaphorewaitinfokhr::*;
 pub use super::sparseimageformatproperties2khr::*;
 pub use super::surfacecapabilitieskhr::*;
 pub use super::surfaceformatkhr::*;
 pub use super::swapchaincreateinfokhr::*;
+pub use super::timelinesemaphoresubmitinfokhr::*;
 pub use super::waylandsurfacecreateinfokhr::*;
 pub use super::win32surfacecreateinfokhr::*;
 pub use super::xcbsurfacecreateinfokhr::*;
@@ -0,0 +1,24 @@
+use crate::prelude::*;
+
+use std::os::raw::c_void;
+use std::ptr;
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct VkSemaphoreSignalInfoKHR {
+    pub sType: VkStructureType,
+    pub pNext: *const c_void,
+    pub semaphore: VkSemaphore,
+    pub value: u64,
+}
+
+impl VkSemaphoreSignalInfoKHR {
+    pub fn new(semaphore: VkSemaphore, value: u64) -> Self {
+        VkSemaphoreSignalInfoKHR {
+            sType: VK_STRUCTURE_TYPE_SEMAPHORE_SIGNAL_INFO_KHR,
+            pNext: ptr::null(),
+            semaphore,
+            value,
+        }
+    }
+}
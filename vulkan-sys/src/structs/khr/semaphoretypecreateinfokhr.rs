@@ -0,0 +1,24 @@
+use crate::prelude::*;
+
+use std::os::raw::c_void;
+use std::ptr;
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct VkSemaphoreTypeCreateInfoKHR {
+    pub sType: VkStructureType,
+    pub pNext: *const c_void,
+    pub semaphoreType: VkSemaphoreTypeKHR,
+    pub initialValue: u64,
+}
+
+impl VkSemaphoreTypeCreateInfoKHR {
+    pub fn new(semaphore_type: VkSemaphoreTypeKHR, initial_value: u64) -> Self {
+        VkSemaphoreTypeCreateInfoKHR {
+            sType: VK_STRUCTURE_TYPE_SEMAPHORE_TYPE_CREATE_INFO_KHR,
+            pNext: ptr::null(),
+            semaphoreType: semaphore_type,
+            initialValue: initial_value,
+        }
+    }
+}
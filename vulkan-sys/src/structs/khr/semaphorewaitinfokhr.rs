@@ -0,0 +1,33 @@
+use crate::prelude::*;
+
+use std::os::raw::c_void;
+use std::ptr;
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct VkSemaphoreWaitInfoKHR {
+    pub sType: VkStructureType,
+    pub pNext: *const c_void,
+    pub flags: VkSemaphoreWaitFlagBitsKHR,
+    pub semaphoreCount: u32,
+    pub pSemaphores: *const VkSemaphore,
+    pub pValues: *const u64,
+}
+
+impl VkSemaphoreWaitInfoKHR {
+    pub fn new<T>(flags: T, semaphores: &[VkSemaphore], values: &[u64]) -> Self
+    where
+        T: Into<VkSemaphoreWaitFlagBitsKHR>,
+    {
+        debug_assert_eq!(semaphores.len(), values.len());
+
+        VkSemaphoreWaitInfoKHR {
+            sType: VK_STRUCTURE_TYPE_SEMAPHORE_WAIT_INFO_KHR,
+            pNext: ptr::null(),
+            flags: flags.into(),
+            semaphoreCount: semaphores.len() as u32,
+            pSemaphores: semaphores.as_ptr(),
+            pValues: values.as_ptr(),
+        }
+    }
+}
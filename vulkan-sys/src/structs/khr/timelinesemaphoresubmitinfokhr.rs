@@ -0,0 +1,28 @@
+use crate::prelude::*;
+
+use std::os::raw::c_void;
+use std::ptr;
+
+#[repr(C)]
+#[derive(Debug)]
+pub struct VkTimelineSemaphoreSubmitInfoKHR {
+    pub sType: VkStructureType,
+    pub pNext: *const c_void,
+    pub waitSemaphoreValueCount: u32,
+    pub pWaitSemaphoreValues: *const u64,
+    pub signalSemaphoreValueCount: u32,
+    pub pSignalSemaphoreValues: *const u64,
+}
+
+impl VkTimelineSemaphoreSubmitInfoKHR {
+    pub fn new(wait_semaphore_values: &[u64], signal_semaphore_values: &[u64]) -> Self {
+        VkTimelineSemaphoreSubmitInfoKHR {
+            sType: VK_STRUCTURE_TYPE_TIMELINE_SEMAPHORE_SUBMIT_INFO_KHR,
+            pNext: ptr::null(),
+            waitSemaphoreValueCount: wait_semaphore_values.len() as u32,
+            pWaitSemaphoreValues: wait_semaphore_values.as_ptr(),
+            signalSemaphoreValueCount: signal_semaphore_values.len() as u32,
+            pSignalSemaphoreValues: signal_semaphore_values.as_ptr(),
+        }
+    }
+}
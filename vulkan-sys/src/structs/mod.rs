@@ -10,6 +10,11 @@ pub trait PNext<T> {
     fn chain(&mut self, p_next: &T);
 }
 
+/// Chains output structs, that are written by the driver
+pub trait PNextMut<T> {
+    fn chain_mut(&mut self, p_next: &mut T);
+}
+
 #[macro_export]
 macro_rules! impl_pnext {
     ($implementor: ty, $struct_name: ident) => {
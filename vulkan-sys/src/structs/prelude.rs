@@ -1,4 +1,5 @@
 pub use super::PNext;
+pub use super::PNextMut;
 
 pub use super::amd::prelude::*;
 pub use super::core::prelude::*;